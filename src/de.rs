@@ -1,279 +1,941 @@
-use bytes::Bytes;
-use serde::de;
+use std::{
+    iter::{Peekable, Zip},
+    slice, str, vec,
+};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::{
+    de::{
+        self,
+        value::{BytesDeserializer, SeqDeserializer, U32Deserializer},
+        Error as _, IntoDeserializer,
+    },
+    forward_to_deserialize_any,
+};
 use tobu_format::{
     error::DecodeError,
-    wire::{Parser, WireField},
+    wire::{
+        decode_zig_zag, encode_zig_zag, parse_packed, put_field, FieldValue, Parser, WireField,
+        WireType, RECURSION_LIMIT,
+    },
 };
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    info::{Cardinality, DefaultValue, EnumInfo, FieldInfo, MessageInfo, Type},
+    unknown::UNKNOWN_FIELDS,
+};
+
+/// Options for reading messages from the wire format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Drops fields that the message descriptor does not know about rather than keeping them in
+    /// the [`UnknownFields`](crate::unknown::UnknownFields) of the message.
+    pub discard_unknown_fields: bool,
+    /// Accepts messages with required fields that are not set, such as a message that is only
+    /// part of what will eventually be merged together.
+    pub partial: bool,
+}
 
 pub struct Deserializer {
-    parser: Parser,
-    field: WireField,
+    buf: Bytes,
+    message_info: &'static MessageInfo,
+    options: Options,
+    // the number of messages this one is nested in
+    depth: usize,
 }
 
 impl Deserializer {
-    pub fn new(buf: Bytes) -> Result<Self, Error> {
-        let mut parser = Parser::new(buf);
-        let field = parser.next().unwrap_or(Err(DecodeError::Eof))?;
-        Ok(Deserializer { parser, field })
+    pub fn new(buf: Bytes, message_info: &'static MessageInfo) -> Self {
+        Deserializer::with_options(buf, message_info, Options::default())
+    }
+
+    pub fn with_options(buf: Bytes, message_info: &'static MessageInfo, options: Options) -> Self {
+        Deserializer {
+            buf,
+            message_info,
+            options,
+            depth: 0,
+        }
+    }
+
+    fn nested(
+        buf: Bytes,
+        message_info: &'static MessageInfo,
+        options: Options,
+        depth: usize,
+    ) -> Result<Self, Error> {
+        if depth > RECURSION_LIMIT {
+            return Err(DecodeError::RecursionLimitExceeded.into());
+        }
+        Ok(Deserializer {
+            buf,
+            message_info,
+            options,
+            depth,
+        })
+    }
+
+    // Collects every wire field into the slot of its matching field descriptor. Protobuf allows
+    // fields in any order and repeated fields may even be interleaved, so the whole message is
+    // read before any of it is handed to the visitor. Fields without a descriptor are unknown to
+    // this message and are written back out, in the order they were read, for the UnknownFields of
+    // the message.
+    fn parse_fields(&mut self) -> Result<(Vec<Vec<FieldValue>>, Bytes), Error> {
+        let fields = self.message_info.fields;
+        let mut values = vec![Vec::new(); fields.len()];
+        let mut unknown_fields = BytesMut::new();
+        for field in Parser::new(self.buf.split_off(0)) {
+            let WireField { num, val } = field?;
+            // a field sent with a wire type its descriptor does not expect is left to the unknown
+            // fields
+            let field_index = fields
+                .iter()
+                .position(|f| f.number == num)
+                .filter(|&i| has_wire_type(&fields[i], &val));
+            // a repeated scalar field may be packed or not, whichever way its descriptor says to
+            // write it
+            let vals = match (field_index.map(|i| &fields[i]), val) {
+                (Some(f), FieldValue::Bytes(b))
+                    if matches!(f.cardinality, Cardinality::Repeated) && f.ty.is_packable() =>
+                {
+                    parse_packed(b, f.ty.wire_type())?
+                }
+                (_, val) => vec![val],
+            };
+            for val in vals {
+                // a closed enum leaves values it does not know to the unknown fields
+                let index = field_index.filter(|&i| !is_unknown_enum_value(&fields[i], &val));
+                match index {
+                    Some(index) => {
+                        // setting a member of a oneof clears the others, so the last one wins
+                        if let Some(oneof_index) = fields[index].oneof_index {
+                            for (i, f) in fields.iter().enumerate() {
+                                if i != index && f.oneof_index == Some(oneof_index) {
+                                    values[i].clear();
+                                }
+                            }
+                        }
+                        values[index].push(val)
+                    }
+                    None if !self.options.discard_unknown_fields => {
+                        put_field(&mut unknown_fields, &WireField { num, val })
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok((values, unknown_fields.freeze()))
+    }
+
+    fn visit_message<'de, V>(
+        &mut self,
+        struct_fields: Option<&[&str]>,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let (values, unknown_fields) = self.parse_fields()?;
+        if !self.options.partial {
+            let missing = self
+                .message_info
+                .fields
+                .iter()
+                .zip(&values)
+                .find(|(f, v)| matches!(f.cardinality, Cardinality::Required) && v.is_empty());
+            if let Some((field_info, _)) = missing {
+                return Err(Error::RequiredFieldNotSet(field_info.name.to_string()));
+            }
+        }
+        let keep_unknown = struct_fields.is_some_and(|fields| fields.contains(&UNKNOWN_FIELDS));
+        visitor.visit_map(MessageAccess {
+            message_info: self.message_info,
+            fields: self.message_info.fields.iter().zip(values).peekable(),
+            group_oneofs: struct_fields.is_some(),
+            options: self.options,
+            depth: self.depth,
+            value: None,
+            unknown_fields: keep_unknown.then_some(unknown_fields),
+        })
     }
+}
+
+fn has_wire_type(field_info: &FieldInfo, value: &FieldValue) -> bool {
+    let wire_type = field_info.ty.wire_type();
+    match value {
+        FieldValue::Varint(_) => wire_type == WireType::Varint,
+        FieldValue::Fixed32(_) => wire_type == WireType::Fixed32,
+        FieldValue::Fixed64(_) => wire_type == WireType::Fixed64,
+        FieldValue::Bytes(_) => {
+            wire_type == WireType::Bytes
+                || matches!(field_info.cardinality, Cardinality::Repeated)
+                    && field_info.ty.is_packable()
+        }
+        FieldValue::Group(_) => wire_type == WireType::StartGroup,
+    }
+}
 
-    fn next(&mut self) -> Result<(), Error> {
-        self.field = self.parser.next().unwrap_or(Err(DecodeError::Eof))?;
-        Ok(())
+fn is_unknown_enum_value(field_info: &FieldInfo, value: &FieldValue) -> bool {
+    match (field_info.enum_info, value) {
+        (Some(enum_info), FieldValue::Varint(v)) => {
+            enum_info.is_closed() && !enum_info.contains(*v as i32)
+        }
+        // a map entry whose value a closed enum does not know is left whole to the unknown fields
+        (None, FieldValue::Bytes(entry)) => is_unknown_map_value(field_info, entry),
+        _ => false,
     }
 }
 
-impl<'de, 'r> de::Deserializer<'de> for &'r mut Deserializer {
+fn is_unknown_map_value(field_info: &FieldInfo, entry: &Bytes) -> bool {
+    let Some(entry_info) = field_info.message_info().filter(|m| m.is_map) else {
+        return false;
+    };
+    let Ok((_, value_index)) = entry_info.map_entry() else {
+        return false;
+    };
+    let value_info = &entry_info.fields[value_index];
+    Parser::new(entry.clone())
+        .map_while(Result::ok)
+        .filter(|field| field.num == value_info.number)
+        .last()
+        .is_some_and(|field| is_unknown_enum_value(value_info, &field.val))
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer {
     type Error = Error;
 
-    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        self.visit_message(None, visitor)
     }
 
-    fn deserialize_bool<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    // Unknown fields are only handed to structs with a field to hold them.
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        self.visit_message(Some(fields), visitor)
     }
 
-    fn deserialize_i8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct MessageAccess {
+    message_info: &'static MessageInfo,
+    fields: Peekable<Zip<slice::Iter<'static, FieldInfo>, vec::IntoIter<Vec<FieldValue>>>>,
+    // a struct holds the members of a oneof in a single field named after the oneof
+    group_oneofs: bool,
+    options: Options,
+    depth: usize,
+    value: Option<MessageValue>,
+    // handed out as bytes under UNKNOWN_FIELDS once every field has been visited
+    unknown_fields: Option<Bytes>,
+}
+
+enum MessageValue {
+    Field(FieldDeserializer),
+    Oneof(OneofDeserializer),
+    Unknown(Bytes),
+}
+
+impl MessageAccess {
+    // Gathers the members of the oneof that starts with the given field.
+    fn oneof(
+        &mut self,
+        oneof_index: i32,
+        mut field_info: &'static FieldInfo,
+        mut values: Vec<FieldValue>,
+    ) -> Result<(&'static str, OneofDeserializer), Error> {
+        let oneof_info = self
+            .message_info
+            .oneofs
+            .get(oneof_index as usize)
+            .ok_or_else(|| Error::custom("oneof descriptor not found"))?;
+        let mut member = None;
+        for position in 0.. {
+            if !values.is_empty() {
+                let field = FieldDeserializer {
+                    field_info,
+                    values,
+                    options: self.options,
+                    depth: self.depth,
+                };
+                member = Some((position, field));
+            }
+            match self
+                .fields
+                .next_if(|(f, _)| f.oneof_index == Some(oneof_index))
+            {
+                Some(next) => (field_info, values) = next,
+                None => break,
+            }
+        }
+        Ok((oneof_info.name, OneofDeserializer { member }))
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MessageAccess {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
     where
-        V: de::Visitor<'de>,
+        K: de::DeserializeSeed<'de>,
     {
-        todo!()
+        // every field is visited, even when absent from the wire, so that it takes on its default
+        let (key, value) = match self.fields.next() {
+            Some((field_info, values)) => match field_info.oneof_index {
+                Some(oneof_index) if self.group_oneofs => {
+                    let (name, oneof) = self.oneof(oneof_index, field_info, values)?;
+                    (name, MessageValue::Oneof(oneof))
+                }
+                _ => {
+                    let field = FieldDeserializer {
+                        field_info,
+                        values,
+                        options: self.options,
+                        depth: self.depth,
+                    };
+                    (field_info.name, MessageValue::Field(field))
+                }
+            },
+            None => match self.unknown_fields.take() {
+                Some(unknown_fields) => (UNKNOWN_FIELDS, MessageValue::Unknown(unknown_fields)),
+                None => return Ok(None),
+            },
+        };
+        self.value = Some(value);
+        seed.deserialize(key.into_deserializer()).map(Some)
     }
 
-    fn deserialize_i16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
     where
-        V: de::Visitor<'de>,
+        V: de::DeserializeSeed<'de>,
     {
-        todo!()
+        match self.value.take() {
+            Some(MessageValue::Field(field)) => {
+                let name = field.field_info.name;
+                seed.deserialize(field).map_err(|e| e.within(name))
+            }
+            Some(MessageValue::Oneof(oneof)) => seed.deserialize(oneof),
+            Some(MessageValue::Unknown(buf)) => seed.deserialize(BytesDeserializer::new(&buf)),
+            None => Err(Error::custom("value requested before key")),
+        }
     }
 
-    fn deserialize_i32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len() + usize::from(self.unknown_fields.is_some()))
+    }
+}
+
+// The members of a oneof as an enum, with the member that is set, if any, as its variant.
+struct OneofDeserializer {
+    // the position of the member within the oneof, along with its values
+    member: Option<(u32, FieldDeserializer)>,
+}
+
+impl<'de> de::Deserializer<'de> for OneofDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        match self.member {
+            Some((position, field)) => visitor.visit_enum(OneofAccess(position, field)),
+            None => visitor.visit_none(),
+        }
     }
 
-    fn deserialize_i64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        match self.member {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
     }
 
-    fn deserialize_u8<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct OneofAccess(u32, FieldDeserializer);
+
+impl<'de> de::EnumAccess<'de> for OneofAccess {
+    type Error = Error;
+    type Variant = FieldDeserializer;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where
-        V: de::Visitor<'de>,
+        V: de::DeserializeSeed<'de>,
     {
-        todo!()
+        let position: U32Deserializer<Error> = self.0.into_deserializer();
+        Ok((seed.deserialize(position)?, self.1))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for FieldDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(Error::custom("oneof member must be a newtype variant"))
     }
 
-    fn deserialize_u16<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
     where
-        V: de::Visitor<'de>,
+        T: de::DeserializeSeed<'de>,
     {
-        todo!()
+        let name = self.field_info.name;
+        seed.deserialize(self).map_err(|e| e.within(name))
     }
 
-    fn deserialize_u32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom("oneof member must be a newtype variant"))
     }
 
-    fn deserialize_u64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom("oneof member must be a newtype variant"))
     }
+}
+
+// The value of an enum as the variant that holds it. Known values are unit variants, while an open
+// enum holds a number it does not know in a newtype variant.
+pub(crate) struct EnumValueAccess {
+    variant_index: u32,
+    unknown: Option<i32>,
+}
+
+impl EnumValueAccess {
+    pub(crate) fn new(enum_info: &EnumInfo, number: i32) -> Result<Self, Error> {
+        let variant_index = enum_info.variant_index(number)?;
+        Ok(EnumValueAccess {
+            variant_index: variant_index as u32,
+            unknown: (variant_index == enum_info.value.len()).then_some(number),
+        })
+    }
+}
 
-    fn deserialize_f32<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+impl<'de> de::EnumAccess<'de> for EnumValueAccess {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where
-        V: de::Visitor<'de>,
+        V: de::DeserializeSeed<'de>,
     {
-        todo!()
+        let variant_index: U32Deserializer<Error> = self.variant_index.into_deserializer();
+        Ok((seed.deserialize(variant_index)?, self))
     }
+}
 
-    fn deserialize_f64<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+impl<'de> de::VariantAccess<'de> for EnumValueAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.unknown {
+            Some(_) => Err(Error::invalid_type(
+                de::Unexpected::NewtypeVariant,
+                &"unit variant",
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.unknown {
+            Some(number) => seed.deserialize(number.into_deserializer()),
+            None => Err(Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom(
+            "enum value must be a unit or newtype variant",
+        ))
     }
 
-    fn deserialize_char<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        Err(Error::custom(
+            "enum value must be a unit or newtype variant",
+        ))
     }
+}
+
+// The declared default of a field as it would appear on the wire.
+fn default_field_value(field_info: &FieldInfo) -> Option<FieldValue> {
+    let default = match (field_info.default_value, field_info.enum_info) {
+        (Some(default), _) => default,
+        // the first value of an enum is its default
+        (None, Some(enum_info)) => DefaultValue::Enum(enum_info.value.first()?.number),
+        (None, None) => return None,
+    };
+    Some(match (default, field_info.ty) {
+        (DefaultValue::Bool(v), _) => FieldValue::Varint(v.into()),
+        (DefaultValue::Int(v), Type::SInt32 | Type::SInt64) => {
+            FieldValue::Varint(encode_zig_zag(v))
+        }
+        (DefaultValue::Int(v), Type::SFixed32) => FieldValue::Fixed32(v as u32),
+        (DefaultValue::Int(v), Type::SFixed64) => FieldValue::Fixed64(v as u64),
+        (DefaultValue::Int(v), _) => FieldValue::Varint(v as u64),
+        (DefaultValue::Uint(v), Type::Fixed32) => FieldValue::Fixed32(v as u32),
+        (DefaultValue::Uint(v), Type::Fixed64) => FieldValue::Fixed64(v),
+        (DefaultValue::Uint(v), _) => FieldValue::Varint(v),
+        (DefaultValue::Float(v), Type::Float) => FieldValue::Fixed32((v as f32).to_bits()),
+        (DefaultValue::Float(v), _) => FieldValue::Fixed64(v.to_bits()),
+        (DefaultValue::String(v), _) => FieldValue::Bytes(Bytes::from_static(v.as_bytes())),
+        (DefaultValue::Bytes(v), _) => FieldValue::Bytes(Bytes::from_static(v)),
+        (DefaultValue::Enum(v), _) => FieldValue::Varint(v as i64 as u64),
+    })
+}
+
+// All of the wire values of a single field within a message.
+struct FieldDeserializer {
+    field_info: &'static FieldInfo,
+    values: Vec<FieldValue>,
+    options: Options,
+    // the depth of the message the field is in
+    depth: usize,
+}
+
+impl FieldDeserializer {
+    fn into_value(mut self) -> ValueDeserializer {
+        let value = match self.field_info.ty {
+            // multiple occurrences of an embedded message are merged, which for the wire format is
+            // the same as concatenating them
+            Type::Message | Type::Group if self.values.len() > 1 => {
+                let mut buf = BytesMut::new();
+                for value in self.values {
+                    if let FieldValue::Bytes(b) | FieldValue::Group(b) = value {
+                        buf.put(b);
+                    }
+                }
+                match self.field_info.ty {
+                    Type::Group => Some(FieldValue::Group(buf.freeze())),
+                    _ => Some(FieldValue::Bytes(buf.freeze())),
+                }
+            }
+            // last one wins for everything else, and an absent field takes on its declared default
+            _ => self
+                .values
+                .pop()
+                .or_else(|| default_field_value(self.field_info)),
+        };
+
+        ValueDeserializer {
+            field_info: self.field_info,
+            value,
+            options: self.options,
+            depth: self.depth,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer {
+    type Error = Error;
 
-    fn deserialize_str<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        match self.field_info.cardinality {
+            Cardinality::Repeated if self.field_info.message_info().is_some_and(|m| m.is_map) => {
+                self.deserialize_map(visitor)
+            }
+            Cardinality::Repeated => self.deserialize_seq(visitor),
+            _ => self.into_value().deserialize_any(visitor),
+        }
     }
 
-    fn deserialize_string<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        if self.values.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
     }
 
-    fn deserialize_bytes<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.field_info.cardinality {
+            Cardinality::Repeated => {
+                let field_info = self.field_info;
+                let options = self.options;
+                let depth = self.depth;
+                let values = self.values.into_iter().map(move |value| ValueDeserializer {
+                    field_info,
+                    value: Some(value),
+                    options,
+                    depth,
+                });
+                visitor.visit_seq(SeqDeserializer::new(values))
+            }
+            _ => self.into_value().deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        let entry_info = self
+            .field_info
+            .message_info()
+            .ok_or_else(|| Error::custom("message descriptor not found"))?;
+        let (key_index, value_index) = entry_info.map_entry()?;
+        visitor.visit_map(MapAccess {
+            options: self.options,
+            depth: self.depth + 1,
+            entry_info,
+            key_index,
+            value_index,
+            entries: self.values.into_iter(),
+            value: None,
+        })
     }
 
-    fn deserialize_byte_buf<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        self.into_value().deserialize_struct(name, fields, visitor)
     }
 
-    fn deserialize_option<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        self.into_value().deserialize_enum(name, variants, visitor)
     }
 
-    fn deserialize_unit<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        visitor.visit_unit()
     }
 
-    fn deserialize_unit_struct<V>(
-        self,
-        _name: &'static str,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct identifier
+    }
+}
+
+// The entries of a map field, each of which is a nested message holding a key and a value. Later
+// entries with the same key replace earlier ones.
+struct MapAccess {
+    options: Options,
+    // the depth of the entries, which are messages nested in the one holding the map
+    depth: usize,
+    entry_info: &'static MessageInfo,
+    key_index: usize,
+    value_index: usize,
+    entries: vec::IntoIter<FieldValue>,
+    value: Option<FieldDeserializer>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let entry = match self.entries.next() {
+            Some(FieldValue::Bytes(entry)) => entry,
+            Some(_) => return Err(Error::custom("map entry must be length delimited")),
+            None => return Ok(None),
+        };
+
+        let fields = self.entry_info.fields;
+        let (mut values, _) =
+            Deserializer::nested(entry, self.entry_info, self.options, self.depth)?
+                .parse_fields()?;
+        self.value = Some(FieldDeserializer {
+            field_info: &fields[self.value_index],
+            values: std::mem::take(&mut values[self.value_index]),
+            options: self.options,
+            depth: self.depth,
+        });
+        let key = FieldDeserializer {
+            field_info: &fields[self.key_index],
+            values: std::mem::take(&mut values[self.key_index]),
+            options: self.options,
+            depth: self.depth,
+        };
+        seed.deserialize(key.into_value()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::custom("value requested before key"))?;
+        seed.deserialize(value.into_value())
+    }
+}
+
+// A single wire value of a field, or its default when the field is absent.
+struct ValueDeserializer {
+    field_info: &'static FieldInfo,
+    value: Option<FieldValue>,
+    options: Options,
+    // the depth of the message the value is in
+    depth: usize,
+}
+
+impl ValueDeserializer {
+    fn mismatch(&self) -> Error {
+        Error::custom(format!(
+            "field descriptor for {} does not match wire value",
+            self.field_info.name
+        ))
+    }
+
+    fn varint(&self) -> Result<u64, Error> {
+        match self.value {
+            Some(FieldValue::Varint(v)) => Ok(v),
+            None => Ok(0),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn fixed32(&self) -> Result<u32, Error> {
+        match self.value {
+            Some(FieldValue::Fixed32(v)) => Ok(v),
+            None => Ok(0),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn fixed64(&self) -> Result<u64, Error> {
+        match self.value {
+            Some(FieldValue::Fixed64(v)) => Ok(v),
+            None => Ok(0),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn bytes(self) -> Result<Bytes, Error> {
+        match self.value {
+            Some(FieldValue::Bytes(b)) => Ok(b),
+            None => Ok(Bytes::new()),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn group(self) -> Result<Bytes, Error> {
+        match self.value {
+            Some(FieldValue::Group(b)) => Ok(b),
+            None => Ok(Bytes::new()),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn message(self) -> Result<Deserializer, Error> {
+        let message_info = self
+            .field_info
+            .message_info()
+            .ok_or_else(|| Error::custom("message descriptor not found"))?;
+        let options = self.options;
+        let depth = self.depth + 1;
+        let buf = match self.field_info.ty {
+            Type::Group => self.group()?,
+            _ => self.bytes()?,
+        };
+        Deserializer::nested(buf, message_info, options, depth)
+    }
+
+    fn enum_number(&self) -> Result<i32, Error> {
+        match self.field_info.ty {
+            Type::Enum => Ok(self.varint()? as i32),
+            _ => Err(self.mismatch()),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        match self.field_info.ty {
+            Type::Double => visitor.visit_f64(f64::from_bits(self.fixed64()?)),
+            Type::Float => visitor.visit_f32(f32::from_bits(self.fixed32()?)),
+            Type::Int64 => visitor.visit_i64(self.varint()? as i64),
+            Type::Uint64 => visitor.visit_u64(self.varint()?),
+            Type::Int32 => visitor.visit_i32(self.varint()? as i32),
+            Type::Fixed64 => visitor.visit_u64(self.fixed64()?),
+            Type::Fixed32 => visitor.visit_u32(self.fixed32()?),
+            Type::Bool => visitor.visit_bool(self.varint()? != 0),
+            Type::String => self.deserialize_str(visitor),
+            Type::Group | Type::Message => {
+                de::Deserializer::deserialize_any(&mut self.message()?, visitor)
+            }
+            Type::Bytes => self.deserialize_byte_buf(visitor),
+            Type::Uint32 => visitor.visit_u32(self.varint()? as u32),
+            Type::Enum => visitor.visit_i32(self.varint()? as i32),
+            Type::SFixed32 => visitor.visit_i32(self.fixed32()? as i32),
+            Type::SFixed64 => visitor.visit_i64(self.fixed64()? as i64),
+            Type::SInt32 => visitor.visit_i32(decode_zig_zag(self.varint()?) as i32),
+            Type::SInt64 => visitor.visit_i64(decode_zig_zag(self.varint()?)),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let b = self.bytes()?;
+        visitor.visit_str(str::from_utf8(&b).map_err(Error::custom)?)
     }
 
-    fn deserialize_newtype_struct<V>(
-        self,
-        _name: &'static str,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        self.deserialize_str(visitor)
     }
 
-    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        visitor.visit_bytes(&self.bytes()?)
     }
 
-    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        visitor.visit_byte_buf(self.bytes()?.to_vec())
     }
 
-    fn deserialize_tuple_struct<V>(
-        self,
-        _name: &'static str,
-        _len: usize,
-        _visitor: V,
-    ) -> Result<V::Value, Self::Error>
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        visitor.visit_some(self)
     }
 
-    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        match self.field_info.ty {
+            // Vec<u8> asks for a sequence rather than bytes
+            Type::Bytes => visitor.visit_seq(SeqDeserializer::new(self.bytes()?.into_iter())),
+            _ => Err(self.mismatch()),
+        }
     }
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        let mut map = MapAccess { de: self };
-        visitor.visit_map(&mut map)
+        de::Deserializer::deserialize_struct(&mut self.message()?, name, fields, visitor)
     }
 
     fn deserialize_enum<V>(
         self,
         _name: &'static str,
         _variants: &'static [&'static str],
-        _visitor: V,
+        visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        let enum_info = self
+            .field_info
+            .enum_info
+            .ok_or_else(|| Error::custom("enum descriptor not found"))?;
+        let number = self.enum_number()?;
+        visitor.visit_enum(EnumValueAccess::new(enum_info, number)?)
     }
 
-    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: de::Visitor<'de>,
     {
-        todo!()
+        visitor.visit_unit()
     }
 
-    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: de::Visitor<'de>,
-    {
-        todo!()
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char
+        unit unit_struct newtype_struct tuple tuple_struct map identifier
     }
 }
 
-struct MapAccess<'r> {
-    de: &'r mut Deserializer,
-}
-
-impl<'de, 'r> de::MapAccess<'de> for MapAccess<'r> {
-    type Error = Error;
+impl<'de> IntoDeserializer<'de, Error> for ValueDeserializer {
+    type Deserializer = Self;
 
-    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
-    where
-        K: de::DeserializeSeed<'de>,
-    {
-        seed.deserialize(&mut *self.de).map(Some)
-    }
-
-    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
-    where
-        V: de::DeserializeSeed<'de>,
-    {
-        seed.deserialize(&mut *self.de)
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
     }
 }
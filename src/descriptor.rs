@@ -0,0 +1,261 @@
+//! The parts of `google/protobuf/descriptor.proto` needed to describe messages at runtime.
+//!
+//! Fields tobu has no use for, such as services and source code info, are left out and skipped
+//! when decoding.
+
+use serde_derive::{Deserialize, Serialize};
+use tobu_format::field::FieldNumber;
+
+use crate::info::{Cardinality, FieldInfo, MessageInfo, MessageRef, Syntax, Type};
+
+macro_rules! field {
+    ($name:literal, $number:literal, $cardinality:ident, $ty:ident) => {
+        field!($name, $number, $cardinality, $ty, None)
+    };
+    ($name:literal, $number:literal, $cardinality:ident, $ty:ident, $message_info:expr) => {
+        FieldInfo {
+            name: $name,
+            number: FieldNumber::new($number),
+            cardinality: Cardinality::$cardinality,
+            ty: Type::$ty,
+            type_name: "",
+            json_name: "",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: $message_info,
+            enum_info: None,
+        }
+    };
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileDescriptorSet {
+    pub file: Vec<FileDescriptorProto>,
+}
+
+pub static FILE_DESCRIPTOR_SET: MessageInfo = MessageInfo {
+    name: "google.protobuf.FileDescriptorSet",
+    fields: &[field!(
+        "file",
+        1,
+        Repeated,
+        Message,
+        Some(MessageRef::Static(&FILE_DESCRIPTOR_PROTO))
+    )],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileDescriptorProto {
+    pub name: Option<String>,
+    pub package: Option<String>,
+    pub dependency: Vec<String>,
+    pub message_type: Vec<DescriptorProto>,
+    pub enum_type: Vec<EnumDescriptorProto>,
+    pub syntax: Option<String>,
+}
+
+pub static FILE_DESCRIPTOR_PROTO: MessageInfo = MessageInfo {
+    name: "google.protobuf.FileDescriptorProto",
+    fields: &[
+        field!("name", 1, Optional, String),
+        field!("package", 2, Optional, String),
+        field!("dependency", 3, Repeated, String),
+        field!(
+            "message_type",
+            4,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&DESCRIPTOR_PROTO))
+        ),
+        field!(
+            "enum_type",
+            5,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&ENUM_DESCRIPTOR_PROTO))
+        ),
+        field!("syntax", 12, Optional, String),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DescriptorProto {
+    pub name: Option<String>,
+    pub field: Vec<FieldDescriptorProto>,
+    pub nested_type: Vec<DescriptorProto>,
+    pub enum_type: Vec<EnumDescriptorProto>,
+    pub options: Option<MessageOptions>,
+    pub oneof_decl: Vec<OneofDescriptorProto>,
+}
+
+pub static DESCRIPTOR_PROTO: MessageInfo = MessageInfo {
+    name: "google.protobuf.DescriptorProto",
+    fields: &[
+        field!("name", 1, Optional, String),
+        field!(
+            "field",
+            2,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&FIELD_DESCRIPTOR_PROTO))
+        ),
+        field!(
+            "nested_type",
+            3,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&DESCRIPTOR_PROTO))
+        ),
+        field!(
+            "enum_type",
+            4,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&ENUM_DESCRIPTOR_PROTO))
+        ),
+        field!(
+            "options",
+            7,
+            Optional,
+            Message,
+            Some(MessageRef::Static(&MESSAGE_OPTIONS))
+        ),
+        field!(
+            "oneof_decl",
+            8,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&ONEOF_DESCRIPTOR_PROTO))
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+// Label and type are kept as their enum numbers, which map onto Cardinality and Type.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldDescriptorProto {
+    pub name: Option<String>,
+    pub number: Option<i32>,
+    pub label: Option<i32>,
+    pub r#type: Option<i32>,
+    pub type_name: Option<String>,
+    pub default_value: Option<String>,
+    pub options: Option<FieldOptions>,
+    pub oneof_index: Option<i32>,
+    pub json_name: Option<String>,
+    pub proto3_optional: Option<bool>,
+}
+
+pub static FIELD_DESCRIPTOR_PROTO: MessageInfo = MessageInfo {
+    name: "google.protobuf.FieldDescriptorProto",
+    fields: &[
+        field!("name", 1, Optional, String),
+        field!("number", 3, Optional, Int32),
+        field!("label", 4, Optional, Enum),
+        field!("type", 5, Optional, Enum),
+        field!("type_name", 6, Optional, String),
+        field!("default_value", 7, Optional, String),
+        field!(
+            "options",
+            8,
+            Optional,
+            Message,
+            Some(MessageRef::Static(&FIELD_OPTIONS))
+        ),
+        field!("oneof_index", 9, Optional, Int32),
+        field!("json_name", 10, Optional, String),
+        field!("proto3_optional", 17, Optional, Bool),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OneofDescriptorProto {
+    pub name: Option<String>,
+}
+
+pub static ONEOF_DESCRIPTOR_PROTO: MessageInfo = MessageInfo {
+    name: "google.protobuf.OneofDescriptorProto",
+    fields: &[field!("name", 1, Optional, String)],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnumDescriptorProto {
+    pub name: Option<String>,
+    pub value: Vec<EnumValueDescriptorProto>,
+}
+
+pub static ENUM_DESCRIPTOR_PROTO: MessageInfo = MessageInfo {
+    name: "google.protobuf.EnumDescriptorProto",
+    fields: &[
+        field!("name", 1, Optional, String),
+        field!(
+            "value",
+            2,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&ENUM_VALUE_DESCRIPTOR_PROTO))
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnumValueDescriptorProto {
+    pub name: Option<String>,
+    pub number: Option<i32>,
+}
+
+pub static ENUM_VALUE_DESCRIPTOR_PROTO: MessageInfo = MessageInfo {
+    name: "google.protobuf.EnumValueDescriptorProto",
+    fields: &[
+        field!("name", 1, Optional, String),
+        field!("number", 2, Optional, Int32),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageOptions {
+    pub map_entry: Option<bool>,
+}
+
+pub static MESSAGE_OPTIONS: MessageInfo = MessageInfo {
+    name: "google.protobuf.MessageOptions",
+    fields: &[field!("map_entry", 7, Optional, Bool)],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FieldOptions {
+    pub packed: Option<bool>,
+}
+
+pub static FIELD_OPTIONS: MessageInfo = MessageInfo {
+    name: "google.protobuf.FieldOptions",
+    fields: &[field!("packed", 2, Optional, Bool)],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
@@ -11,11 +11,50 @@ pub enum Error {
     #[error("{0}")]
     DecodeError(#[from] DecodeError),
 
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "json")]
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+
     #[error("field number {0} not found")]
     FieldNotFound(FieldNumber),
 
+    #[error("field {0} not found")]
+    FieldNameNotFound(String),
+
+    #[error("value does not match field {0}")]
+    ValueMismatch(&'static str),
+
+    #[error("type {0} not found")]
+    TypeNotFound(String),
+
+    #[error("type {0} is already defined")]
+    DuplicateType(String),
+
     #[error("unknown sequence length")]
     UnknownSeqLen,
+
+    /// A required field is not set. The field is named by its path from the outermost message,
+    /// such as `nested.id`.
+    #[error("required field {0} is not set")]
+    RequiredFieldNotSet(String),
+}
+
+impl Error {
+    // Adds the field holding a nested message to the path of a required field that is not set.
+    pub(crate) fn within(self, field: &str) -> Self {
+        match self {
+            Error::RequiredFieldNotSet(path) if path.is_empty() => {
+                Error::RequiredFieldNotSet(field.to_string())
+            }
+            Error::RequiredFieldNotSet(path) => {
+                Error::RequiredFieldNotSet(format!("{}.{}", field, path))
+            }
+            error => error,
+        }
+    }
 }
 
 impl ser::Error for Error {
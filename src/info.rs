@@ -1,25 +1,85 @@
-use tobu_format::{field::FieldNumber, wire::WireType};
+use std::{fmt, sync::OnceLock};
+
+use serde::{de, ser};
+use tobu_format::wire::WireType;
+
+use crate::error::Error;
+
+// re-exported for the descriptors of generated code
+pub use tobu_format::field::FieldNumber;
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct MessageInfo {
     pub name: &'static str,
     pub fields: &'static [FieldInfo],
+    // indexed by FieldInfo::oneof_index, whose members are declared next to each other in fields
+    pub oneofs: &'static [OneofInfo],
     pub syntax: Syntax,
     pub is_map: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl MessageInfo {
+    pub(crate) fn field_index(&self, number: FieldNumber) -> Option<usize> {
+        self.fields.iter().position(|f| f.number == number)
+    }
+
+    // A struct holds a oneof in a single field, so the next struct field after a member of a oneof
+    // is the one after the last member.
+    pub(crate) fn next_field_index(&self, index: usize) -> usize {
+        match self.fields.get(index).and_then(|f| f.oneof_index) {
+            Some(oneof_index) => {
+                index
+                    + self.fields[index..]
+                        .iter()
+                        .take_while(|f| f.oneof_index == Some(oneof_index))
+                        .count()
+            }
+            None => index + 1,
+        }
+    }
+
+    // Returns the index of a member of the oneof whose first member is at index. Members are
+    // numbered in the order they are declared, the same as the variants of the enum that holds
+    // them.
+    pub(crate) fn oneof_member(&self, index: usize, member: usize) -> Result<usize, Error> {
+        let Some(oneof_index) = self.fields.get(index).and_then(|f| f.oneof_index) else {
+            return Err(ser::Error::custom("oneof descriptor expected"));
+        };
+        match self.fields.get(index + member) {
+            Some(f) if f.oneof_index == Some(oneof_index) => Ok(index + member),
+            _ => Err(ser::Error::custom("oneof member not found")),
+        }
+    }
+
+    // Returns the index of the key and value fields of a map entry.
+    pub(crate) fn map_entry(&self) -> Result<(usize, usize), Error> {
+        if !self.is_map {
+            return Err(ser::Error::custom("map entry descriptor expected"));
+        }
+
+        let key_number = FieldNumber::new(1);
+        let value_number = FieldNumber::new(2);
+        Ok((
+            self.field_index(key_number)
+                .ok_or(Error::FieldNotFound(key_number))?,
+            self.field_index(value_number)
+                .ok_or(Error::FieldNotFound(value_number))?,
+        ))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OneofInfo {
+    pub name: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum Syntax {
+    #[default]
     Proto2,
     Proto3,
 }
 
-impl Default for Syntax {
-    fn default() -> Self {
-        Syntax::Proto2
-    }
-}
-
 #[derive(Debug, Default, Clone, Copy)]
 pub struct FieldInfo {
     pub name: &'static str,
@@ -30,25 +90,88 @@ pub struct FieldInfo {
     pub json_name: &'static str,
     pub packed: bool,
     pub oneof_index: Option<i32>,
-    pub message_info: Option<&'static MessageInfo>,
+    // a proto3 field declared optional, which tracks presence like a proto2 field
+    pub proto3_optional: bool,
+    // the value a proto2 field declares with [default = ...]
+    pub default_value: Option<DefaultValue>,
+    pub message_info: Option<MessageRef>,
     pub enum_info: Option<&'static EnumInfo>,
 }
 
+impl FieldInfo {
+    pub fn message_info(&self) -> Option<&'static MessageInfo> {
+        self.message_info.and_then(MessageRef::get)
+    }
+}
+
+/// A field declared in an `extend` block, whose value is carried among the unknown fields of the
+/// message it extends.
 #[derive(Debug, Clone, Copy)]
+pub struct ExtensionInfo {
+    // the full name of the extended message, without a leading dot
+    pub extendee: &'static str,
+    pub field: FieldInfo,
+}
+
+/// The message descriptor of a message, group or map field.
+///
+/// Generated code refers to other descriptors statically. Descriptors built at runtime may refer
+/// to each other in cycles, so they refer to a cell that is filled in once every descriptor exists.
+#[derive(Clone, Copy)]
+pub enum MessageRef {
+    Static(&'static MessageInfo),
+    Lazy(&'static OnceLock<&'static MessageInfo>),
+}
+
+impl MessageRef {
+    pub fn get(self) -> Option<&'static MessageInfo> {
+        match self {
+            MessageRef::Static(message_info) => Some(message_info),
+            MessageRef::Lazy(cell) => cell.get().copied(),
+        }
+    }
+}
+
+// Messages may be recursive, so only the name is printed.
+impl fmt::Debug for MessageRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get() {
+            Some(message_info) => f
+                .debug_tuple("MessageRef")
+                .field(&message_info.name)
+                .finish(),
+            None => f.write_str("MessageRef(<unresolved>)"),
+        }
+    }
+}
+
+/// The value a singular proto2 field takes on when it is not set, as declared with
+/// `[default = ...]`.
+///
+/// Integers are widened to 64 bits, floats are held as the `f64` closest to their `f32` value, and
+/// enums are held by number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefaultValue {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    String(&'static str),
+    Bytes(&'static [u8]),
+    Enum(i32),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
 pub enum Cardinality {
+    #[default]
     Optional,
     Required,
     Repeated,
 }
 
-impl Default for Cardinality {
-    fn default() -> Self {
-        Cardinality::Optional
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum Type {
+    #[default]
     Double,
     Float,
     Int64,
@@ -92,18 +215,57 @@ impl Type {
             Type::SInt64 => WireType::Varint,
         }
     }
-}
 
-impl Default for Type {
-    fn default() -> Self {
-        Type::Double
+    // Only scalar numeric types may be packed.
+    pub(crate) fn is_packable(&self) -> bool {
+        matches!(
+            self.wire_type(),
+            WireType::Varint | WireType::Fixed32 | WireType::Fixed64
+        )
     }
 }
 
+/// The values of an enum, in the same order as the variants of the Rust enum that holds them.
+///
+/// Proto3 enums are open: a value they do not know is kept in a newtype variant holding its number,
+/// declared after the variants of the known values. Proto2 enums are closed and leave values they
+/// do not know in the unknown fields of the message.
 #[derive(Debug, Clone, Copy)]
 pub struct EnumInfo {
     pub name: &'static str,
     pub value: &'static [EnumValue],
+    pub syntax: Syntax,
+}
+
+impl EnumInfo {
+    pub fn is_closed(&self) -> bool {
+        self.syntax == Syntax::Proto2
+    }
+
+    // Returns the index of the variant that holds a number, which for an open enum that does not
+    // know the number is the variant after the known values.
+    pub(crate) fn variant_index(&self, number: i32) -> Result<usize, Error> {
+        match self.value.iter().position(|v| v.number == number) {
+            Some(index) => Ok(index),
+            None if !self.is_closed() => Ok(self.value.len()),
+            None => Err(de::Error::custom(format!(
+                "unknown {} value {}",
+                self.name, number
+            ))),
+        }
+    }
+
+    // Returns the number of the value at the index of a variant.
+    pub(crate) fn number(&self, variant_index: u32) -> Result<i32, Error> {
+        self.value
+            .get(variant_index as usize)
+            .map(|v| v.number)
+            .ok_or_else(|| ser::Error::custom(format!("{} value not found", self.name)))
+    }
+
+    pub(crate) fn contains(&self, number: i32) -> bool {
+        self.value.iter().any(|v| v.number == number)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,762 @@
+use std::{
+    iter::{Peekable, Zip},
+    slice, vec,
+};
+
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use serde::{
+    de::{
+        self,
+        value::{BytesDeserializer, SeqDeserializer, U32Deserializer},
+        Error as _, IntoDeserializer,
+    },
+    forward_to_deserialize_any,
+};
+use serde_json::{Map, Value};
+
+use crate::{
+    de::EnumValueAccess,
+    error::Error,
+    info::{Cardinality, FieldInfo, MessageInfo, Type},
+    unknown::UNKNOWN_FIELDS,
+};
+
+use super::{json_name, wkt, Options};
+
+// Parsers must accept both the standard and URL safe alphabets, with or without padding.
+const STANDARD: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Deserializes a message from a JSON value.
+pub struct Deserializer<'a> {
+    // None for a message field that is absent, which leaves every field of the message unset
+    value: Option<Value>,
+    message_info: &'static MessageInfo,
+    options: &'a Options,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new(value: Value, message_info: &'static MessageInfo, options: &'a Options) -> Self {
+        Deserializer {
+            value: Some(value),
+            message_info,
+            options,
+        }
+    }
+
+    // Moves every member of the object into the slot of its matching field descriptor, which may
+    // be named by either its JSON name or its original name.
+    fn parse_fields(self) -> Result<Vec<Option<Value>>, Error> {
+        let value = self
+            .value
+            .map(|value| wkt::from_json(self.message_info, value, self.options))
+            .transpose()?;
+        let object = match value {
+            Some(Value::Object(object)) => object,
+            Some(Value::Null) | None => Map::new(),
+            _ => {
+                return Err(Error::custom(format!(
+                    "message {} must be an object",
+                    self.message_info.name
+                )))
+            }
+        };
+
+        let fields = self.message_info.fields;
+        let mut values = vec![None; fields.len()];
+        for (key, value) in object {
+            let index = fields
+                .iter()
+                .position(|f| f.name == key || json_name(f) == key);
+            match index {
+                // null is the same as leaving the field out, unless it is a google.protobuf.Value
+                Some(index) if value.is_null() && !wkt::is_value(&fields[index]) => {
+                    values[index] = None
+                }
+                Some(index) => values[index] = Some(value),
+                None if self.options.ignore_unknown_fields => {}
+                None => return Err(Error::FieldNameNotFound(key)),
+            }
+        }
+        Ok(values)
+    }
+
+    fn visit_message<'de, V>(
+        self,
+        struct_fields: Option<&[&str]>,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let message_info = self.message_info;
+        let options = self.options;
+        let values = self.parse_fields()?;
+        visitor.visit_map(MessageAccess {
+            message_info,
+            fields: message_info.fields.iter().zip(values).peekable(),
+            group_oneofs: struct_fields.is_some(),
+            options,
+            value: None,
+            unknown_fields: struct_fields.is_some_and(|f| f.contains(&UNKNOWN_FIELDS)),
+        })
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.visit_message(None, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.visit_message(Some(fields), visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct MessageAccess<'a> {
+    message_info: &'static MessageInfo,
+    fields: Peekable<Zip<slice::Iter<'static, FieldInfo>, vec::IntoIter<Option<Value>>>>,
+    // a struct holds the members of a oneof in a single field named after the oneof
+    group_oneofs: bool,
+    options: &'a Options,
+    value: Option<MessageValue<'a>>,
+    // JSON has no unknown fields to give, so a struct that holds them is handed an empty set
+    unknown_fields: bool,
+}
+
+enum MessageValue<'a> {
+    Field(FieldDeserializer<'a>),
+    Oneof(OneofDeserializer<'a>),
+    Unknown,
+}
+
+impl<'a> MessageAccess<'a> {
+    // Gathers the members of the oneof that starts with the given field. At most one of them may
+    // be set.
+    fn oneof(
+        &mut self,
+        oneof_index: i32,
+        mut field_info: &'static FieldInfo,
+        mut value: Option<Value>,
+    ) -> Result<(&'static str, OneofDeserializer<'a>), Error> {
+        let oneof_info = self
+            .message_info
+            .oneofs
+            .get(oneof_index as usize)
+            .ok_or_else(|| Error::custom("oneof descriptor not found"))?;
+        let mut member = None;
+        for position in 0.. {
+            if value.is_some() {
+                if member.is_some() {
+                    return Err(Error::custom(format!(
+                        "more than one member of oneof {} is set",
+                        oneof_info.name
+                    )));
+                }
+                let field = FieldDeserializer {
+                    field_info,
+                    value,
+                    options: self.options,
+                };
+                member = Some((position, field));
+            }
+            match self
+                .fields
+                .next_if(|(f, _)| f.oneof_index == Some(oneof_index))
+            {
+                Some(next) => (field_info, value) = next,
+                None => break,
+            }
+        }
+        Ok((oneof_info.name, OneofDeserializer { member }))
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for MessageAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        // every field is visited, even when absent from the object, so that it takes on its default
+        let (key, value) = match self.fields.next() {
+            Some((field_info, value)) => match field_info.oneof_index {
+                Some(oneof_index) if self.group_oneofs => {
+                    let (name, oneof) = self.oneof(oneof_index, field_info, value)?;
+                    (name, MessageValue::Oneof(oneof))
+                }
+                _ => {
+                    let field = FieldDeserializer {
+                        field_info,
+                        value,
+                        options: self.options,
+                    };
+                    (field_info.name, MessageValue::Field(field))
+                }
+            },
+            None if self.unknown_fields => {
+                self.unknown_fields = false;
+                (UNKNOWN_FIELDS, MessageValue::Unknown)
+            }
+            None => return Ok(None),
+        };
+        self.value = Some(value);
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        match self.value.take() {
+            Some(MessageValue::Field(field)) => seed.deserialize(field),
+            Some(MessageValue::Oneof(oneof)) => seed.deserialize(oneof),
+            Some(MessageValue::Unknown) => seed.deserialize(BytesDeserializer::new(&[])),
+            None => Err(Error::custom("value requested before key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len() + usize::from(self.unknown_fields))
+    }
+}
+
+// The members of a oneof as an enum, with the member that is set, if any, as its variant.
+struct OneofDeserializer<'a> {
+    // the position of the member within the oneof, along with its value
+    member: Option<(u32, FieldDeserializer<'a>)>,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for OneofDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.member {
+            Some((position, field)) => visitor.visit_enum(OneofAccess(position, field)),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.member {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct OneofAccess<'a>(u32, FieldDeserializer<'a>);
+
+impl<'de, 'a> de::EnumAccess<'de> for OneofAccess<'a> {
+    type Error = Error;
+    type Variant = FieldDeserializer<'a>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let position: U32Deserializer<Error> = self.0.into_deserializer();
+        Ok((seed.deserialize(position)?, self.1))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for FieldDeserializer<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Err(Error::custom("oneof member must be a newtype variant"))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::custom("oneof member must be a newtype variant"))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(Error::custom("oneof member must be a newtype variant"))
+    }
+}
+
+// The JSON value of a single field within a message, which is an array for repeated fields and an
+// object for map fields.
+struct FieldDeserializer<'a> {
+    field_info: &'static FieldInfo,
+    value: Option<Value>,
+    options: &'a Options,
+}
+
+impl<'a> FieldDeserializer<'a> {
+    fn into_value(self) -> ValueDeserializer<'a> {
+        ValueDeserializer {
+            field_info: self.field_info,
+            value: self.value,
+            options: self.options,
+        }
+    }
+
+    fn mismatch(&self) -> Error {
+        Error::custom(format!(
+            "field descriptor for {} does not match JSON value",
+            self.field_info.name
+        ))
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for FieldDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.field_info.cardinality {
+            Cardinality::Repeated if self.field_info.message_info().is_some_and(|m| m.is_map) => {
+                self.deserialize_map(visitor)
+            }
+            Cardinality::Repeated => self.deserialize_seq(visitor),
+            _ => self.into_value().deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.value.is_none() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.field_info.cardinality {
+            Cardinality::Repeated => {
+                let values = match self.value {
+                    Some(Value::Array(values)) => values,
+                    None => Vec::new(),
+                    Some(_) => return Err(self.mismatch()),
+                };
+                let field_info = self.field_info;
+                let options = self.options;
+                let values = values.into_iter().map(move |value| ValueDeserializer {
+                    field_info,
+                    value: Some(value),
+                    options,
+                });
+                visitor.visit_seq(SeqDeserializer::new(values))
+            }
+            _ => self.into_value().deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let entry_info = self
+            .field_info
+            .message_info()
+            .ok_or_else(|| Error::custom("message descriptor not found"))?;
+        let (key_index, value_index) = entry_info.map_entry()?;
+        let entries = match self.value {
+            Some(Value::Object(entries)) => entries,
+            None => Map::new(),
+            Some(_) => return Err(self.mismatch()),
+        };
+        visitor.visit_map(EntryAccess {
+            key_info: &entry_info.fields[key_index],
+            value_info: &entry_info.fields[value_index],
+            entries: entries.into_iter(),
+            options: self.options,
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.into_value().deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.into_value().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct identifier
+    }
+}
+
+// The members of a map field. Keys are always strings in JSON and are parsed into the type of the
+// key field.
+struct EntryAccess<'a> {
+    key_info: &'static FieldInfo,
+    value_info: &'static FieldInfo,
+    entries: serde_json::map::IntoIter,
+    options: &'a Options,
+    value: Option<ValueDeserializer<'a>>,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for EntryAccess<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        let (key, value) = match self.entries.next() {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        self.value = Some(ValueDeserializer {
+            field_info: self.value_info,
+            value: Some(value),
+            options: self.options,
+        });
+        let key = match (self.key_info.ty, key.as_str()) {
+            (Type::Bool, "true") => Value::Bool(true),
+            (Type::Bool, "false") => Value::Bool(false),
+            (Type::Bool, _) => return Err(Error::custom(format!("invalid bool map key {}", key))),
+            _ => Value::String(key),
+        };
+        let key = ValueDeserializer {
+            field_info: self.key_info,
+            value: Some(key),
+            options: self.options,
+        };
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::custom("value requested before key"))?;
+        seed.deserialize(value)
+    }
+}
+
+// A single JSON value of a field, or its default when the field is absent.
+struct ValueDeserializer<'a> {
+    field_info: &'static FieldInfo,
+    value: Option<Value>,
+    options: &'a Options,
+}
+
+impl<'a> ValueDeserializer<'a> {
+    fn message(self) -> Result<Deserializer<'a>, Error> {
+        let message_info = self
+            .field_info
+            .message_info()
+            .ok_or_else(|| Error::custom("message descriptor not found"))?;
+        Ok(Deserializer {
+            value: self.value,
+            message_info,
+            options: self.options,
+        })
+    }
+
+    fn mismatch(&self) -> Error {
+        Error::custom(format!(
+            "field descriptor for {} does not match JSON value",
+            self.field_info.name
+        ))
+    }
+
+    fn out_of_range(&self) -> Error {
+        Error::custom(format!(
+            "value out of range for field {}",
+            self.field_info.name
+        ))
+    }
+
+    // Integers may be written as numbers or strings, and numbers may have an exponent or a zero
+    // fraction as long as they are integral.
+    fn int(&self) -> Result<i64, Error> {
+        match &self.value {
+            Some(Value::Number(n)) => n
+                .as_i64()
+                .or_else(|| n.as_f64().and_then(integral))
+                .ok_or_else(|| self.out_of_range()),
+            Some(Value::String(s)) => s
+                .parse()
+                .ok()
+                .or_else(|| s.parse().ok().and_then(integral))
+                .ok_or_else(|| self.mismatch()),
+            None => Ok(0),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn uint(&self) -> Result<u64, Error> {
+        match &self.value {
+            Some(Value::Number(n)) => n
+                .as_u64()
+                .or_else(|| n.as_f64().and_then(integral))
+                .ok_or_else(|| self.out_of_range()),
+            Some(Value::String(s)) => s
+                .parse()
+                .ok()
+                .or_else(|| s.parse().ok().and_then(integral))
+                .ok_or_else(|| self.mismatch()),
+            None => Ok(0),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn int32(&self) -> Result<i32, Error> {
+        i32::try_from(self.int()?).map_err(|_| self.out_of_range())
+    }
+
+    fn uint32(&self) -> Result<u32, Error> {
+        u32::try_from(self.uint()?).map_err(|_| self.out_of_range())
+    }
+
+    fn float(&self) -> Result<f64, Error> {
+        match &self.value {
+            Some(Value::Number(n)) => n.as_f64().ok_or_else(|| self.mismatch()),
+            Some(Value::String(s)) => match s.as_str() {
+                "NaN" => Ok(f64::NAN),
+                "Infinity" => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                s => s.parse().map_err(|_| self.mismatch()),
+            },
+            None => Ok(0.0),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn bool(&self) -> Result<bool, Error> {
+        match self.value {
+            Some(Value::Bool(v)) => Ok(v),
+            None => Ok(false),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn string(self) -> Result<String, Error> {
+        match self.value {
+            Some(Value::String(s)) => Ok(s),
+            None => Ok(String::new()),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn bytes(self) -> Result<Vec<u8>, Error> {
+        match &self.value {
+            Some(Value::String(s)) => STANDARD
+                .decode(s)
+                .or_else(|_| URL_SAFE.decode(s))
+                .map_err(Error::custom),
+            None => Ok(Vec::new()),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    // Enums may be written by the name of their value or by number.
+    fn enum_number(&self) -> Result<i32, Error> {
+        match &self.value {
+            Some(Value::String(name)) => {
+                let enum_info = self
+                    .field_info
+                    .enum_info
+                    .ok_or_else(|| Error::custom("enum descriptor not found"))?;
+                enum_info
+                    .value
+                    .iter()
+                    .find(|v| v.name == name)
+                    .map(|v| v.number)
+                    .ok_or_else(|| {
+                        Error::custom(format!("unknown {} value {}", enum_info.name, name))
+                    })
+            }
+            _ => self.int32(),
+        }
+    }
+}
+
+fn integral<T: TryFrom<i128>>(v: f64) -> Option<T> {
+    if v.fract() == 0.0 {
+        T::try_from(v as i128).ok()
+    } else {
+        None
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for ValueDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.field_info.ty {
+            Type::Double => visitor.visit_f64(self.float()?),
+            Type::Float => visitor.visit_f32(self.float()? as f32),
+            Type::Int64 | Type::SInt64 | Type::SFixed64 => visitor.visit_i64(self.int()?),
+            Type::Uint64 | Type::Fixed64 => visitor.visit_u64(self.uint()?),
+            Type::Int32 | Type::SInt32 | Type::SFixed32 => visitor.visit_i32(self.int32()?),
+            Type::Uint32 | Type::Fixed32 => visitor.visit_u32(self.uint32()?),
+            Type::Bool => visitor.visit_bool(self.bool()?),
+            Type::String => visitor.visit_string(self.string()?),
+            Type::Group | Type::Message => self.message()?.deserialize_any(visitor),
+            Type::Bytes => visitor.visit_byte_buf(self.bytes()?),
+            Type::Enum => visitor.visit_i32(self.enum_number()?),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.field_info.ty {
+            // Vec<u8> asks for a sequence rather than bytes
+            Type::Bytes => visitor.visit_seq(SeqDeserializer::new(self.bytes()?.into_iter())),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.message()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let enum_info = self
+            .field_info
+            .enum_info
+            .ok_or_else(|| Error::custom("enum descriptor not found"))?;
+        let number = self.enum_number()?;
+        visitor.visit_enum(EnumValueAccess::new(enum_info, number)?)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple tuple_struct map identifier
+    }
+}
+
+impl<'de, 'a> IntoDeserializer<'de, Error> for ValueDeserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
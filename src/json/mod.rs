@@ -0,0 +1,99 @@
+//! The [proto3 JSON mapping](https://protobuf.dev/programming-guides/proto3/#json).
+//!
+//! Fields are keyed by their lowerCamelCase JSON name, 64-bit integers are written as strings,
+//! bytes as base64 and enums by the name of their value. Parsing accepts either the JSON name or
+//! the original field name, and integers given as strings or numbers.
+//!
+//! Some of the well-known types in `google.protobuf` have a form of their own, such as an RFC 3339
+//! string for a `Timestamp` or the value itself for a wrapper like `Int32Value`.
+
+mod de;
+mod ser;
+mod wkt;
+
+use std::{borrow::Cow, sync::Arc};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::Error,
+    info::{FieldInfo, MessageInfo},
+    registry::TypeRegistry,
+};
+
+pub use self::de::Deserializer;
+pub use self::ser::Serializer;
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Writes fields that hold their default value, which are otherwise left out. Fields that track
+    /// presence, such as messages, are still left out when unset.
+    pub emit_defaults: bool,
+    /// Keys fields by their name in the `.proto` file rather than their JSON name.
+    pub proto_names: bool,
+    /// Skips fields that the message does not have, which are otherwise an error.
+    pub ignore_unknown_fields: bool,
+    /// Finds the messages packed in a `google.protobuf.Any`, which are written inline. An `Any`
+    /// that holds a message the registry does not have is an error.
+    pub type_registry: Option<Arc<TypeRegistry>>,
+}
+
+pub fn to_value(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<serde_json::Value, Error> {
+    let value = value.serialize(Serializer::new(info, options))?;
+    Ok(value.unwrap_or_default())
+}
+
+pub fn to_string(value: &impl Serialize, info: &'static MessageInfo) -> Result<String, Error> {
+    to_string_with_options(value, info, &Options::default())
+}
+
+pub fn to_string_with_options(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<String, Error> {
+    Ok(serde_json::to_string(&to_value(value, info, options)?)?)
+}
+
+pub fn from_value<T>(
+    value: serde_json::Value,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(Deserializer::new(value, info, options))
+}
+
+pub fn from_str<T>(s: &str, info: &'static MessageInfo) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_str_with_options(s, info, &Options::default())
+}
+
+pub fn from_str_with_options<T>(
+    s: &str,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_value(serde_json::from_str(s)?, info, options)
+}
+
+// Descriptors written by hand may leave the JSON name out, in which case it is derived the same
+// way protoc does.
+fn json_name(field_info: &FieldInfo) -> Cow<'static, str> {
+    if field_info.json_name.is_empty() {
+        Cow::Owned(tobu_format::descriptor::json_name(field_info.name))
+    } else {
+        Cow::Borrowed(field_info.json_name)
+    }
+}
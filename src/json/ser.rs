@@ -0,0 +1,1050 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::ser::{self, Impossible, SerializeMap, SerializeSeq, SerializeStruct};
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    error::Error,
+    info::{Cardinality, FieldInfo, MessageInfo, Syntax, Type},
+    ser::FieldKeySerializer,
+    unknown::UNKNOWN_FIELDS,
+};
+
+use super::{json_name, Options};
+
+// Where a value sits within its field, which decides whether it may be left out when it holds the
+// default value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    // a singular or repeated field, left out when it holds its default and has no presence
+    Field,
+    // an element of a repeated field or the key or value of a map entry, always written
+    Element,
+    // a byte of a bytes field serialized as a sequence of u8
+    Byte,
+}
+
+/// Serializes a message into a JSON value, or into nothing when given `None`.
+pub struct Serializer<'a> {
+    message_info: &'static MessageInfo,
+    options: &'a Options,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(message_info: &'static MessageInfo, options: &'a Options) -> Self {
+        Serializer {
+            message_info,
+            options,
+        }
+    }
+
+    fn unsupported(self) -> Result<Option<Value>, Error> {
+        Err(ser::Error::custom(format!(
+            "message {} must be a struct or map",
+            self.message_info.name
+        )))
+    }
+}
+
+impl<'a> serde::Serializer for Serializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = MessageSerializer<'a>;
+    type SerializeStruct = MessageSerializer<'a>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.unsupported()
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.unsupported()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(ser::Error::custom("message must be a struct or map"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ser::Error::custom("message must be a struct or map"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ser::Error::custom("message must be a struct or map"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ser::Error::custom("message must be a struct or map"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(MessageSerializer::new(self.message_info, self.options))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(MessageSerializer::new(self.message_info, self.options))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ser::Error::custom("message must be a struct or map"))
+    }
+}
+
+// A message written as a JSON object. Fields arrive in descriptor order when serialized as a struct
+// and are looked up by name or number when serialized as a map.
+pub struct MessageSerializer<'a> {
+    message_info: &'static MessageInfo,
+    options: &'a Options,
+    field_index: usize,
+    object: Map<String, Value>,
+}
+
+impl<'a> MessageSerializer<'a> {
+    fn new(message_info: &'static MessageInfo, options: &'a Options) -> Self {
+        MessageSerializer {
+            message_info,
+            options,
+            field_index: 0,
+            object: Map::new(),
+        }
+    }
+
+    fn key(&self, field_info: &FieldInfo) -> String {
+        if self.options.proto_names {
+            field_info.name.to_string()
+        } else {
+            json_name(field_info).into_owned()
+        }
+    }
+
+    fn serialize_field_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let field_info = self
+            .message_info
+            .fields
+            .get(self.field_index)
+            .ok_or_else(|| Error::Message("field descriptor not found".to_string()))?;
+        let ser = FieldSerializer {
+            message_info: self.message_info,
+            field_info,
+            context: Context::Field,
+            options: self.options,
+        };
+        if let Some(value) = value.serialize(ser)? {
+            self.object.insert(self.key(field_info), value);
+        }
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<Option<Value>, Error> {
+        if self.options.emit_defaults {
+            for field_info in self.message_info.fields {
+                if has_presence(self.message_info, field_info) {
+                    continue;
+                }
+                let key = self.key(field_info);
+                if !self.object.contains_key(&key) {
+                    self.object.insert(key, default_value(field_info));
+                }
+            }
+        }
+        super::wkt::to_json(self.message_info, self.object, self.options).map(Some)
+    }
+}
+
+impl<'a> SerializeStruct for MessageSerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        // JSON has no way to write unknown fields, so they are dropped
+        if key == UNKNOWN_FIELDS {
+            return Ok(());
+        }
+
+        let is_oneof = self
+            .message_info
+            .fields
+            .get(self.field_index)
+            .is_some_and(|f| f.oneof_index.is_some());
+        if is_oneof {
+            // the member that is set is written under its own name
+            let ser = OneofSerializer {
+                message_info: self.message_info,
+                field_index: self.field_index,
+                options: self.options,
+            };
+            if let Some((field_info, value)) = value.serialize(ser)? {
+                self.object.insert(self.key(field_info), value);
+            }
+        } else {
+            self.serialize_field_value(value)?;
+        }
+        self.field_index = self.message_info.next_field_index(self.field_index);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        MessageSerializer::end(self)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        if key != UNKNOWN_FIELDS {
+            self.field_index = self.message_info.next_field_index(self.field_index);
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> SerializeMap for MessageSerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.field_index = key.serialize(FieldKeySerializer(self.message_info))?;
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.serialize_field_value(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        MessageSerializer::end(self)
+    }
+}
+
+// A oneof held in a single struct field as an enum with a variant for each member. Returns the
+// member that is set along with its value.
+struct OneofSerializer<'a> {
+    message_info: &'static MessageInfo,
+    // the index of the first member
+    field_index: usize,
+    options: &'a Options,
+}
+
+impl<'a> OneofSerializer<'a> {
+    fn unsupported(self) -> Result<Option<(&'static FieldInfo, Value)>, Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+}
+
+impl<'a> serde::Serializer for OneofSerializer<'a> {
+    type Ok = Option<(&'static FieldInfo, Value)>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let index = self
+            .message_info
+            .oneof_member(self.field_index, variant_index as usize)?;
+        let field_info = &self.message_info.fields[index];
+        let ser = FieldSerializer {
+            message_info: self.message_info,
+            field_info,
+            context: Context::Field,
+            options: self.options,
+        };
+        Ok(value.serialize(ser)?.map(|value| (field_info, value)))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ser::Error::custom(
+            "oneof must be an enum of newtype variants",
+        ))
+    }
+}
+
+// Fields that remember whether they were set are written whenever they are, even when they hold
+// the default value. Everything else is only written when it differs from the default.
+fn has_presence(message_info: &MessageInfo, field_info: &FieldInfo) -> bool {
+    match field_info.cardinality {
+        Cardinality::Repeated => false,
+        _ => {
+            message_info.syntax == Syntax::Proto2
+                || field_info.oneof_index.is_some()
+                || field_info.proto3_optional
+                || matches!(field_info.ty, Type::Message | Type::Group)
+        }
+    }
+}
+
+pub(super) fn default_value(field_info: &FieldInfo) -> Value {
+    if let Cardinality::Repeated = field_info.cardinality {
+        return match field_info.message_info() {
+            Some(m) if m.is_map => Value::Object(Map::new()),
+            _ => Value::Array(Vec::new()),
+        };
+    }
+
+    match field_info.ty {
+        Type::Bool => Value::Bool(false),
+        Type::String | Type::Bytes => Value::String(String::new()),
+        Type::Int64 | Type::Uint64 | Type::Fixed64 | Type::SFixed64 | Type::SInt64 => {
+            Value::String("0".to_string())
+        }
+        Type::Enum => enum_value(field_info, 0),
+        Type::Message | Type::Group => Value::Null,
+        _ => Value::Number(0.into()),
+    }
+}
+
+// An enum is written by the name of its value, or by number when the value is not known.
+fn enum_value(field_info: &FieldInfo, number: i32) -> Value {
+    field_info
+        .enum_info
+        .and_then(|e| e.value.iter().find(|v| v.number == number))
+        .map_or_else(
+            || Value::Number(number.into()),
+            |v| Value::String(v.name.to_string()),
+        )
+}
+
+// JSON has no way to write the special floating point values, so they are written as strings.
+fn float_value(v: f64) -> Value {
+    if v.is_nan() {
+        Value::String("NaN".to_string())
+    } else if v == f64::INFINITY {
+        Value::String("Infinity".to_string())
+    } else if v == f64::NEG_INFINITY {
+        Value::String("-Infinity".to_string())
+    } else {
+        Number::from_f64(v).map_or(Value::Null, Value::Number)
+    }
+}
+
+// A value of a single field.
+struct FieldSerializer<'a> {
+    message_info: &'static MessageInfo,
+    field_info: &'static FieldInfo,
+    context: Context,
+    options: &'a Options,
+}
+
+impl<'a> FieldSerializer<'a> {
+    fn value(&self, is_default: bool, value: Value) -> Result<Option<Value>, Error> {
+        let skip = self.context == Context::Field
+            && is_default
+            && !self.options.emit_defaults
+            && !has_presence(self.message_info, self.field_info);
+        Ok((!skip).then_some(value))
+    }
+
+    fn mismatch(&self) -> Error {
+        ser::Error::custom(format!(
+            "field descriptor for {} does not match value",
+            self.field_info.name
+        ))
+    }
+
+    fn message_info(&self) -> Result<&'static MessageInfo, Error> {
+        self.field_info
+            .message_info()
+            .ok_or_else(|| ser::Error::custom("message descriptor not found"))
+    }
+
+    fn element(&self, field_info: &'static FieldInfo, context: Context) -> FieldSerializer<'a> {
+        FieldSerializer {
+            message_info: self.message_info,
+            field_info,
+            context,
+            options: self.options,
+        }
+    }
+}
+
+impl<'a> serde::Serializer for FieldSerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = MessageSerializer<'a>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        match self.field_info.ty {
+            Type::Bool => self.value(!v, Value::Bool(v)),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom("i8 not supported"))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom("i16 not supported"))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        match self.field_info.ty {
+            Type::Int32 | Type::SInt32 | Type::SFixed32 | Type::Uint32 | Type::Fixed32 => {
+                self.value(v == 0, Value::Number(v.into()))
+            }
+            Type::Int64 | Type::SInt64 | Type::SFixed64 | Type::Uint64 | Type::Fixed64 => {
+                self.value(v == 0, Value::String(v.to_string()))
+            }
+            Type::Enum => self.value(v == 0, enum_value(self.field_info, v as i32)),
+            Type::Float | Type::Double => self.serialize_f64(v as f64),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        match self.context {
+            Context::Byte => Ok(Some(Value::Number(v.into()))),
+            _ => Err(ser::Error::custom("u8 not supported")),
+        }
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom("u16 not supported"))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        match self.field_info.ty {
+            Type::Int32 | Type::SInt32 | Type::SFixed32 | Type::Uint32 | Type::Fixed32 => {
+                self.value(v == 0, Value::Number(v.into()))
+            }
+            Type::Int64 | Type::SInt64 | Type::SFixed64 | Type::Uint64 | Type::Fixed64 => {
+                self.value(v == 0, Value::String(v.to_string()))
+            }
+            Type::Enum => self.serialize_i64(v as i64),
+            Type::Float | Type::Double => self.serialize_f64(v as f64),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        match self.field_info.ty {
+            // widening an f32 to f64 adds digits that were never there, so the shortest
+            // representation of the f32 is parsed back instead
+            Type::Float => {
+                let v = v as f32;
+                let widened = v.to_string().parse().unwrap_or(v as f64);
+                self.value(v == 0.0, float_value(widened))
+            }
+            Type::Double => self.value(v == 0.0, float_value(v)),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom("char not supported"))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        match self.field_info.ty {
+            Type::String => self.value(v.is_empty(), Value::String(v.to_string())),
+            Type::Bytes => self.serialize_bytes(v.as_bytes()),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        match self.field_info.ty {
+            Type::Bytes => self.value(v.is_empty(), Value::String(STANDARD.encode(v))),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom("unit not supported"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom("unit struct not supported"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        let number = self
+            .field_info
+            .enum_info
+            .ok_or_else(|| self.mismatch())?
+            .number(variant_index)?;
+        self.serialize_i32(number)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        Err(ser::Error::custom("newtype struct not supported"))
+    }
+
+    // An open enum holds a number it does not know in a variant of its own.
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self.field_info.ty {
+            Type::Enum => value.serialize(self),
+            _ => Err(ser::Error::custom("newtype variant not supported")),
+        }
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let is_repeated = matches!(self.field_info.cardinality, Cardinality::Repeated);
+        match self.field_info.ty {
+            // Vec<u8> is a sequence of u8 rather than bytes
+            Type::Bytes if !is_repeated || self.context == Context::Element => {
+                Ok(SeqSerializer::Bytes {
+                    bytes: Vec::with_capacity(len.unwrap_or(0)),
+                    ser: self,
+                })
+            }
+            _ if is_repeated && self.context == Context::Field => Ok(SeqSerializer::List {
+                list: Vec::with_capacity(len.unwrap_or(0)),
+                ser: self,
+            }),
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ser::Error::custom("tuple not supported"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ser::Error::custom("tuple struct not supported"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ser::Error::custom("tuple variant not supported"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let message_info = self.message_info()?;
+        if !message_info.is_map || self.context != Context::Field {
+            return self.serialize_struct("", 0).map(MapSerializer::Fields);
+        }
+
+        let (key_index, value_index) = message_info.map_entry()?;
+        Ok(MapSerializer::Entries(EntrySerializer {
+            ser: self,
+            key_info: &message_info.fields[key_index],
+            value_info: &message_info.fields[value_index],
+            key: None,
+            object: Map::new(),
+        }))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        match self.field_info.ty {
+            Type::Message | Type::Group => {
+                Ok(MessageSerializer::new(self.message_info()?, self.options))
+            }
+            _ => Err(self.mismatch()),
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ser::Error::custom("struct variant not supported"))
+    }
+}
+
+enum SeqSerializer<'a> {
+    List {
+        ser: FieldSerializer<'a>,
+        list: Vec<Value>,
+    },
+    Bytes {
+        ser: FieldSerializer<'a>,
+        bytes: Vec<u8>,
+    },
+}
+
+impl<'a> SerializeSeq for SeqSerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self {
+            SeqSerializer::List { ser, list } => {
+                let element = ser.element(ser.field_info, Context::Element);
+                if let Some(value) = value.serialize(element)? {
+                    list.push(value);
+                }
+            }
+            SeqSerializer::Bytes { ser, bytes } => {
+                let element = ser.element(ser.field_info, Context::Byte);
+                let byte = value
+                    .serialize(element)?
+                    .and_then(|v| v.as_u64())
+                    .and_then(|v| u8::try_from(v).ok())
+                    .ok_or_else(|| ser.mismatch())?;
+                bytes.push(byte);
+            }
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            SeqSerializer::List { ser, list } => ser.value(list.is_empty(), Value::Array(list)),
+            SeqSerializer::Bytes { ser, bytes } => {
+                ser.value(bytes.is_empty(), Value::String(STANDARD.encode(bytes)))
+            }
+        }
+    }
+}
+
+enum MapSerializer<'a> {
+    Entries(EntrySerializer<'a>),
+    Fields(MessageSerializer<'a>),
+}
+
+impl<'a> SerializeMap for MapSerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self {
+            MapSerializer::Entries(entries) => entries.serialize_key(key),
+            MapSerializer::Fields(fields) => fields.serialize_key(key),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self {
+            MapSerializer::Entries(entries) => entries.serialize_value(value),
+            MapSerializer::Fields(fields) => fields.serialize_value(value),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            MapSerializer::Entries(entries) => SerializeMap::end(entries),
+            MapSerializer::Fields(fields) => SerializeMap::end(fields),
+        }
+    }
+}
+
+// A map field, written as an object whose keys are the map keys turned into strings.
+struct EntrySerializer<'a> {
+    ser: FieldSerializer<'a>,
+    key_info: &'static FieldInfo,
+    value_info: &'static FieldInfo,
+    key: Option<String>,
+    object: Map<String, Value>,
+}
+
+impl<'a> SerializeMap for EntrySerializer<'a> {
+    type Ok = Option<Value>;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let key = key.serialize(self.ser.element(self.key_info, Context::Element))?;
+        self.key = Some(match key {
+            Some(Value::String(key)) => key,
+            Some(Value::Bool(key)) => key.to_string(),
+            Some(Value::Number(key)) => key.to_string(),
+            _ => {
+                return Err(ser::Error::custom(
+                    "map key must be an integer, bool or string",
+                ))
+            }
+        });
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Message("value serialized before key".to_string()))?;
+        let value = value
+            .serialize(self.ser.element(self.value_info, Context::Element))?
+            .unwrap_or_else(|| default_value(self.value_info));
+        self.object.insert(key, value);
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser
+            .value(self.object.is_empty(), Value::Object(self.object))
+    }
+}
@@ -0,0 +1,527 @@
+// The well-known types in google.protobuf that the JSON mapping writes in a form of their own
+// rather than as an object of their fields. Messages are recognized by their full name, so the
+// same goes for generated messages and those built by a DescriptorPool.
+//
+// A message is first written as an object of its fields as usual and then turned into its own
+// form, while parsing turns its own form back into an object of its fields.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bytes::Bytes;
+use serde_json::{Map, Value};
+
+use crate::{
+    error::Error,
+    info::{Cardinality, FieldInfo, MessageInfo},
+    value::DynamicMessage,
+};
+
+use super::{json_name, Options};
+
+const ANY: &str = "google.protobuf.Any";
+const TIMESTAMP: &str = "google.protobuf.Timestamp";
+const DURATION: &str = "google.protobuf.Duration";
+const FIELD_MASK: &str = "google.protobuf.FieldMask";
+const STRUCT: &str = "google.protobuf.Struct";
+const LIST_VALUE: &str = "google.protobuf.ListValue";
+const VALUE: &str = "google.protobuf.Value";
+const WRAPPERS: &[&str] = &[
+    "google.protobuf.DoubleValue",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int64Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Int32Value",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.BoolValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.BytesValue",
+];
+
+// 0001-01-01T00:00:00Z and 9999-12-31T23:59:59Z
+const MIN_TIMESTAMP: i64 = -62_135_596_800;
+const MAX_TIMESTAMP: i64 = 253_402_300_799;
+// roughly 10,000 years
+const MAX_DURATION: i64 = 315_576_000_000;
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+const SECONDS_PER_DAY: i64 = 86_400;
+
+// A google.protobuf.Value is the one message for which null means something other than leaving
+// the field out.
+pub(super) fn is_value(field_info: &FieldInfo) -> bool {
+    !matches!(field_info.cardinality, Cardinality::Repeated)
+        && field_info.message_info().is_some_and(|m| m.name == VALUE)
+}
+
+// Whether the message is written in a form of its own rather than as an object of its fields.
+fn has_own_form(name: &str) -> bool {
+    matches!(
+        name,
+        ANY | TIMESTAMP | DURATION | FIELD_MASK | STRUCT | LIST_VALUE | VALUE
+    ) || WRAPPERS.contains(&name)
+}
+
+pub(super) fn to_json(
+    message_info: &'static MessageInfo,
+    mut object: Map<String, Value>,
+    options: &Options,
+) -> Result<Value, Error> {
+    let mut field = |name: &str| take_field(message_info, &mut object, name);
+    match message_info.name {
+        ANY => {
+            let type_url = field("type_url");
+            let value = field("value");
+            any_to_json(type_url, value, options)
+        }
+        TIMESTAMP => {
+            let seconds = int(field("seconds"))?;
+            let nanos = int(field("nanos"))?;
+            format_timestamp(seconds, nanos).map(Value::String)
+        }
+        DURATION => {
+            let seconds = int(field("seconds"))?;
+            let nanos = int(field("nanos"))?;
+            format_duration(seconds, nanos).map(Value::String)
+        }
+        FIELD_MASK => {
+            let paths = match field("paths") {
+                Some(Value::Array(paths)) => paths,
+                _ => Vec::new(),
+            };
+            let paths = paths
+                .iter()
+                .map(|path| camel_case_path(path.as_str().unwrap_or_default()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::String(paths.join(",")))
+        }
+        STRUCT => Ok(field("fields").unwrap_or_else(|| Value::Object(Map::new()))),
+        LIST_VALUE => Ok(field("values").unwrap_or_else(|| Value::Array(Vec::new()))),
+        VALUE => {
+            if field("null_value").is_some() {
+                return Ok(Value::Null);
+            }
+            if let Some(number) = field("number_value") {
+                return match number {
+                    Value::Number(_) => Ok(number),
+                    _ => Err(invalid(VALUE, "number_value must be finite")),
+                };
+            }
+            ["string_value", "bool_value", "struct_value", "list_value"]
+                .into_iter()
+                .find_map(field)
+                .ok_or_else(|| invalid(VALUE, "kind is not set"))
+        }
+        name if WRAPPERS.contains(&name) => match field("value") {
+            Some(value) => Ok(value),
+            None => {
+                let field_info = message_info
+                    .fields
+                    .first()
+                    .ok_or_else(|| Error::FieldNameNotFound("value".to_string()))?;
+                Ok(super::ser::default_value(field_info))
+            }
+        },
+        _ => Ok(Value::Object(object)),
+    }
+}
+
+pub(super) fn from_json(
+    message_info: &'static MessageInfo,
+    value: Value,
+    options: &Options,
+) -> Result<Value, Error> {
+    let name = message_info.name;
+    let object = match (name, value) {
+        (VALUE, Value::Null) => fields([("null_value", Value::from(0))]),
+        (_, Value::Null) => return Ok(Value::Null),
+        (ANY, Value::Object(object)) => any_from_json(object, options)?,
+        (TIMESTAMP, Value::String(s)) => {
+            let (seconds, nanos) =
+                parse_timestamp(&s).ok_or_else(|| invalid(name, "invalid RFC 3339 timestamp"))?;
+            fields([("seconds", seconds.into()), ("nanos", nanos.into())])
+        }
+        (DURATION, Value::String(s)) => {
+            let (seconds, nanos) =
+                parse_duration(&s).ok_or_else(|| invalid(name, "invalid duration"))?;
+            fields([("seconds", seconds.into()), ("nanos", nanos.into())])
+        }
+        (FIELD_MASK, Value::String(s)) => {
+            let paths = s
+                .split(',')
+                .filter(|path| !path.is_empty())
+                .map(|path| Value::String(snake_case_path(path)))
+                .collect();
+            fields([("paths", Value::Array(paths))])
+        }
+        (STRUCT, object @ Value::Object(_)) => fields([("fields", object)]),
+        (LIST_VALUE, values @ Value::Array(_)) => fields([("values", values)]),
+        (VALUE, value) => {
+            let kind = match value {
+                Value::Bool(_) => "bool_value",
+                Value::Number(_) => "number_value",
+                Value::String(_) => "string_value",
+                Value::Array(_) => "list_value",
+                Value::Object(_) => "struct_value",
+                Value::Null => unreachable!("handled above"),
+            };
+            fields([(kind, value)])
+        }
+        (name, value) if WRAPPERS.contains(&name) => fields([("value", value)]),
+        (TIMESTAMP | DURATION | FIELD_MASK, _) => return Err(invalid(name, "expected a string")),
+        (ANY | STRUCT, _) => return Err(invalid(name, "expected an object")),
+        (LIST_VALUE, _) => return Err(invalid(name, "expected an array")),
+        (_, value) => return Ok(value),
+    };
+    Ok(Value::Object(object))
+}
+
+// The message packed in an Any is written inline along with its type URL under @type, as in
+// {"@type": "type.googleapis.com/pkg.Message", "field": 1}. A message with a form of its own is
+// written under value instead, as in {"@type": "...Duration", "value": "1s"}.
+fn any_to_json(
+    type_url: Option<Value>,
+    value: Option<Value>,
+    options: &Options,
+) -> Result<Value, Error> {
+    let type_url = match type_url {
+        Some(Value::String(type_url)) => type_url,
+        // an Any that holds nothing
+        _ if value.is_none() => return Ok(Value::Object(Map::new())),
+        _ => return Err(invalid(ANY, "type_url is not set")),
+    };
+    let message_info = find_type(&type_url, options)?;
+    let buf = match value {
+        Some(Value::String(value)) => STANDARD
+            .decode(value)
+            .map_err(|e| invalid(ANY, &e.to_string()))?,
+        _ => Vec::new(),
+    };
+    let message = DynamicMessage::decode(Bytes::from(buf), message_info)?;
+
+    let mut object = Map::new();
+    object.insert("@type".to_string(), Value::String(type_url));
+    match super::to_value(&message, message_info, options)? {
+        value if has_own_form(message_info.name) => {
+            object.insert("value".to_string(), value);
+        }
+        Value::Object(fields) => object.extend(fields),
+        _ => return Err(invalid(ANY, "expected an object")),
+    }
+    Ok(Value::Object(object))
+}
+
+fn any_from_json(
+    mut object: Map<String, Value>,
+    options: &Options,
+) -> Result<Map<String, Value>, Error> {
+    let type_url = match object.remove("@type") {
+        Some(Value::String(type_url)) => type_url,
+        None if object.is_empty() => return Ok(Map::new()),
+        _ => return Err(invalid(ANY, "@type is not set")),
+    };
+    let message_info = find_type(&type_url, options)?;
+    let value = if has_own_form(message_info.name) {
+        object.remove("value").unwrap_or_default()
+    } else {
+        Value::Object(object)
+    };
+    let message = DynamicMessage::deserialize(
+        message_info,
+        super::Deserializer::new(value, message_info, options),
+    )?;
+    let buf = message.encode()?;
+    Ok(fields([
+        ("type_url", Value::String(type_url)),
+        ("value", Value::String(STANDARD.encode(buf))),
+    ]))
+}
+
+fn find_type(type_url: &str, options: &Options) -> Result<&'static MessageInfo, Error> {
+    options
+        .type_registry
+        .as_ref()
+        .and_then(|registry| registry.find(type_url))
+        .ok_or_else(|| Error::TypeNotFound(type_url.to_string()))
+}
+
+fn invalid(name: &str, reason: &str) -> Error {
+    Error::Message(format!("{}: {}", name, reason))
+}
+
+// Fields are keyed by their name or JSON name depending on the options they were written with.
+fn take_field(
+    message_info: &MessageInfo,
+    object: &mut Map<String, Value>,
+    name: &str,
+) -> Option<Value> {
+    let field_info = message_info.fields.iter().find(|f| f.name == name)?;
+    object
+        .remove(name)
+        .or_else(|| object.remove(json_name(field_info).as_ref()))
+}
+
+fn fields<const N: usize>(fields: [(&str, Value); N]) -> Map<String, Value> {
+    fields
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+// 64-bit integers are written as strings.
+fn int(value: Option<Value>) -> Result<i64, Error> {
+    match value {
+        None => Ok(0),
+        Some(Value::Number(n)) => n
+            .as_i64()
+            .ok_or_else(|| Error::Message(format!("invalid integer {}", n))),
+        Some(Value::String(s)) => s
+            .parse()
+            .map_err(|_| Error::Message(format!("invalid integer {}", s))),
+        Some(value) => Err(Error::Message(format!("invalid integer {}", value))),
+    }
+}
+
+// Fractional seconds are written with 3, 6 or 9 digits, whichever is the fewest that loses
+// nothing, and left out when zero.
+fn format_nanos(nanos: i64) -> String {
+    if nanos == 0 {
+        String::new()
+    } else if nanos % 1_000_000 == 0 {
+        format!(".{:03}", nanos / 1_000_000)
+    } else if nanos % 1_000 == 0 {
+        format!(".{:06}", nanos / 1_000)
+    } else {
+        format!(".{:09}", nanos)
+    }
+}
+
+fn format_timestamp(seconds: i64, nanos: i64) -> Result<String, Error> {
+    if !(MIN_TIMESTAMP..=MAX_TIMESTAMP).contains(&seconds) {
+        return Err(invalid(TIMESTAMP, "seconds out of range"));
+    }
+    if !(0..NANOS_PER_SECOND).contains(&nanos) {
+        return Err(invalid(TIMESTAMP, "nanos out of range"));
+    }
+
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+    Ok(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        format_nanos(nanos)
+    ))
+}
+
+// Parses an RFC 3339 date and time, such as 1972-01-01T10:00:20.021-05:00, into seconds and nanos
+// since the Unix epoch.
+fn parse_timestamp(s: &str) -> Option<(i64, i64)> {
+    let mut parser = Parser(s.as_bytes());
+    let year = parser.digits(4)?;
+    parser.expect(b'-')?;
+    let month = parser.digits(2)?;
+    parser.expect(b'-')?;
+    let day = parser.digits(2)?;
+    parser.expect(b'T')?;
+    let hour = parser.digits(2)?;
+    parser.expect(b':')?;
+    let minute = parser.digits(2)?;
+    parser.expect(b':')?;
+    let second = parser.digits(2)?;
+    let nanos = parser.nanos()?;
+    let offset = match parser.next()? {
+        b'Z' => 0,
+        sign @ (b'+' | b'-') => {
+            let hours = parser.digits(2)?;
+            parser.expect(b':')?;
+            let minutes = parser.digits(2)?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+    if !parser.0.is_empty()
+        || !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let seconds =
+        days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second
+            - offset;
+    (MIN_TIMESTAMP..=MAX_TIMESTAMP)
+        .contains(&seconds)
+        .then_some((seconds, nanos))
+}
+
+// Durations are written as seconds with a suffix of s, such as 1.5s or -0.000001s.
+fn format_duration(seconds: i64, nanos: i64) -> Result<String, Error> {
+    if !(-MAX_DURATION..=MAX_DURATION).contains(&seconds) {
+        return Err(invalid(DURATION, "seconds out of range"));
+    }
+    if nanos.abs() >= NANOS_PER_SECOND || seconds.signum() * nanos.signum() < 0 {
+        return Err(invalid(DURATION, "nanos out of range"));
+    }
+
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    Ok(format!(
+        "{}{}{}s",
+        sign,
+        seconds.abs(),
+        format_nanos(nanos.abs())
+    ))
+}
+
+fn parse_duration(s: &str) -> Option<(i64, i64)> {
+    let s = s.strip_suffix('s')?;
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let mut parser = Parser(s.as_bytes());
+    let len = parser.digit_count();
+    // any more digits than MAX_DURATION has is out of range anyway
+    if !(1..=12).contains(&len) {
+        return None;
+    }
+    let seconds = parser.digits(len)?;
+    let nanos = parser.nanos()?;
+    if !parser.0.is_empty() || seconds > MAX_DURATION {
+        return None;
+    }
+
+    if negative {
+        Some((-seconds, -nanos))
+    } else {
+        Some((seconds, nanos))
+    }
+}
+
+struct Parser<'a>(&'a [u8]);
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<u8> {
+        let (&first, rest) = self.0.split_first()?;
+        self.0 = rest;
+        Some(first)
+    }
+
+    fn expect(&mut self, b: u8) -> Option<()> {
+        (self.next()? == b).then_some(())
+    }
+
+    fn digit_count(&self) -> usize {
+        self.0.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+
+    fn digits(&mut self, len: usize) -> Option<i64> {
+        (0..len).try_fold(0, |n, _| match self.next()? {
+            b @ b'0'..=b'9' => Some(n * 10 + i64::from(b - b'0')),
+            _ => None,
+        })
+    }
+
+    // An optional fraction of a second with up to 9 digits.
+    fn nanos(&mut self) -> Option<i64> {
+        if self.0.first() != Some(&b'.') {
+            return Some(0);
+        }
+        self.0 = &self.0[1..];
+        let len = self.digit_count();
+        if !(1..=9).contains(&len) {
+            return None;
+        }
+        let nanos = self.digits(len)?;
+        Some(nanos * 10_i64.pow(9 - len as u32))
+    }
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The number of days since 1970-01-01 of a date in the proleptic Gregorian calendar, from
+// http://howardhinnant.github.io/date_algorithms.html
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The inverse of days_from_civil.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Paths are written in lowerCamelCase, so they must not hold anything that would not survive the
+// trip back to snake_case.
+fn camel_case_path(path: &str) -> Result<String, Error> {
+    let mut camel = String::with_capacity(path.len());
+    let mut upper = false;
+    for c in path.chars() {
+        match c {
+            'A'..='Z' => return Err(invalid(FIELD_MASK, "paths must be snake_case")),
+            '_' if upper => return Err(invalid(FIELD_MASK, "paths must be snake_case")),
+            '_' => upper = true,
+            'a'..='z' if upper => {
+                camel.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            _ if upper => return Err(invalid(FIELD_MASK, "paths must be snake_case")),
+            _ => camel.push(c),
+        }
+    }
+    if upper {
+        return Err(invalid(FIELD_MASK, "paths must be snake_case"));
+    }
+    Ok(camel)
+}
+
+fn snake_case_path(path: &str) -> String {
+    let mut snake = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
@@ -1,43 +1,151 @@
+use std::io::Read;
+
 use bytes::{Bytes, BytesMut};
+use de::{Deserializer, Options};
 use error::Error;
 use info::MessageInfo;
+pub use message::Message;
 use ser::{Serializer, SizeHint};
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Serialize};
 
 pub mod de;
+pub mod descriptor;
 pub mod error;
 pub mod info;
+#[cfg(feature = "json")]
+pub mod json;
+mod message;
+pub mod pool;
+pub mod registry;
 pub mod ser;
+pub mod text;
+pub mod unknown;
 pub mod value;
 
+mod tests;
+
 pub fn to_vec(value: &impl Serialize, info: &'static MessageInfo) -> Result<Vec<u8>, Error> {
-    let mut vec = Vec::with_capacity(serialized_size(value, info)?);
+    to_vec_with_options(value, info, ser::Options::default())
+}
 
-    let mut serializer = Serializer::new(&mut vec, info);
+pub fn to_vec_with_options(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: ser::Options,
+) -> Result<Vec<u8>, Error> {
+    let (size, sizes) = size_hint(value, info, options)?;
+    let mut vec = Vec::with_capacity(size);
+
+    let mut serializer = Serializer::new(&mut vec, info, sizes);
     value.serialize(&mut serializer)?;
 
     Ok(vec)
 }
 
 pub fn to_bytes(value: &impl Serialize, info: &'static MessageInfo) -> Result<Bytes, Error> {
-    let mut bytes = BytesMut::with_capacity(serialized_size(value, info)?);
-
-    let mut serializer = Serializer::new(&mut bytes, info);
-    value.serialize(&mut serializer)?;
+    Ok(to_bytes_mut(value, info)?.freeze())
+}
 
-    Ok(bytes.freeze())
+pub fn to_bytes_with_options(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: ser::Options,
+) -> Result<Bytes, Error> {
+    Ok(write_bytes(value, info, options)?.freeze())
 }
 
 pub fn to_bytes_mut(value: &impl Serialize, info: &'static MessageInfo) -> Result<BytesMut, Error> {
-    let mut bytes = BytesMut::with_capacity(serialized_size(value, info)?);
+    write_bytes(value, info, ser::Options::default())
+}
+
+fn write_bytes(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: ser::Options,
+) -> Result<BytesMut, Error> {
+    let (size, sizes) = size_hint(value, info, options)?;
+    let mut bytes = BytesMut::with_capacity(size);
 
-    let mut serializer = Serializer::new(&mut bytes, info);
+    let mut serializer = Serializer::new(&mut bytes, info, sizes);
     value.serialize(&mut serializer)?;
 
     Ok(bytes)
 }
 
+// The size of a message does not depend on whether its required fields are set, so it is computed
+// for partial messages too.
 pub fn serialized_size(value: &impl Serialize, info: &'static MessageInfo) -> Result<usize, Error> {
-    let mut size_hint = SizeHint::new(info);
-    value.serialize(&mut size_hint)
+    let options = ser::Options { partial: true };
+    Ok(size_hint(value, info, options)?.0)
+}
+
+// Returns the total size along with the size of every nested message, so they are computed once.
+fn size_hint(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: ser::Options,
+) -> Result<(usize, Vec<usize>), Error> {
+    let mut size_hint = SizeHint::new(info, options);
+    let size = value.serialize(&mut size_hint)?;
+    Ok((size, size_hint.into_sizes()))
+}
+
+// The slice is copied into Bytes, so nothing can borrow from it.
+pub fn from_slice<T>(slice: &[u8], info: &'static MessageInfo) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_slice_with_options(slice, info, Options::default())
+}
+
+pub fn from_slice_with_options<T>(
+    slice: &[u8],
+    info: &'static MessageInfo,
+    options: Options,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_bytes_with_options(Bytes::copy_from_slice(slice), info, options)
+}
+
+pub fn from_bytes<T>(bytes: Bytes, info: &'static MessageInfo) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::new(bytes, info);
+    T::deserialize(&mut deserializer)
+}
+
+pub fn from_bytes_with_options<T>(
+    bytes: Bytes,
+    info: &'static MessageInfo,
+    options: Options,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = Deserializer::with_options(bytes, info, options);
+    T::deserialize(&mut deserializer)
+}
+
+pub fn from_reader<T>(reader: impl Read, info: &'static MessageInfo) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_reader_with_options(reader, info, Options::default())
+}
+
+pub fn from_reader_with_options<T>(
+    mut reader: impl Read,
+    info: &'static MessageInfo,
+    options: Options,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut vec = Vec::new();
+    reader.read_to_end(&mut vec)?;
+
+    from_bytes_with_options(Bytes::from(vec), info, options)
 }
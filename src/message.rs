@@ -0,0 +1,34 @@
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{error::Error, info::MessageInfo};
+
+/// A message type that knows its own descriptor, so it can be read and written without passing the
+/// descriptor along. Generated messages implement it.
+pub trait Message: Serialize + DeserializeOwned {
+    fn info() -> &'static MessageInfo;
+
+    fn encode(&self) -> Result<Vec<u8>, Error> {
+        crate::to_vec(self, Self::info())
+    }
+
+    fn encoded_len(&self) -> Result<usize, Error> {
+        crate::serialized_size(self, Self::info())
+    }
+
+    fn decode(buf: Bytes) -> Result<Self, Error> {
+        crate::from_bytes(buf, Self::info())
+    }
+
+    /// Reads `buf` into the message. Singular fields read from `buf` replace those of the message,
+    /// repeated fields are appended to and embedded messages are merged, just as if the message
+    /// had been decoded from its own encoding followed by `buf`. Required fields only have to be
+    /// set once merged.
+    fn merge(&mut self, buf: Bytes) -> Result<(), Error> {
+        let options = crate::ser::Options { partial: true };
+        let mut merged = crate::to_vec_with_options(self, Self::info(), options)?;
+        merged.extend_from_slice(&buf);
+        *self = Self::decode(Bytes::from(merged))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,401 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use bytes::Bytes;
+use tobu_format::{
+    descriptor::{json_name, unescape},
+    field::FieldNumber,
+};
+
+use crate::{
+    descriptor::{
+        DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+        FileDescriptorSet, FILE_DESCRIPTOR_SET,
+    },
+    error::Error,
+    info::{
+        Cardinality, DefaultValue, EnumInfo, EnumValue, FieldInfo, MessageInfo, MessageRef,
+        OneofInfo, Syntax, Type,
+    },
+};
+
+/// Message and enum descriptors built at runtime from a `FileDescriptorSet`, such as the output of
+/// `protoc --descriptor_set_out`.
+///
+/// Descriptors handed out by the pool are `'static` so they can be used anywhere generated ones
+/// can. They are leaked to get there, so a pool is meant to be built once and kept around.
+#[derive(Debug, Default)]
+pub struct DescriptorPool {
+    messages: HashMap<String, &'static MessageInfo>,
+    enums: HashMap<String, &'static EnumInfo>,
+}
+
+impl DescriptorPool {
+    pub fn new() -> Self {
+        DescriptorPool::default()
+    }
+
+    /// Builds a pool from a serialized `FileDescriptorSet`.
+    pub fn decode(buf: Bytes) -> Result<Self, Error> {
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_set(buf)?;
+        Ok(pool)
+    }
+
+    /// Adds every file of a serialized `FileDescriptorSet`. Types may refer to types in the same
+    /// set or in any set added before it.
+    pub fn add_file_descriptor_set(&mut self, buf: Bytes) -> Result<(), Error> {
+        let set: FileDescriptorSet = crate::from_bytes(buf, &FILE_DESCRIPTOR_SET)?;
+        self.add_files(&set.file)
+    }
+
+    /// Adds files whose types may refer to one another. Nothing is added if any file is invalid.
+    pub fn add_files(&mut self, files: &[FileDescriptorProto]) -> Result<(), Error> {
+        let mut types = Types::default();
+        for file in files {
+            let package = file.package.as_deref().unwrap_or("");
+            let syntax = match file.syntax.as_deref() {
+                Some("proto3") => Syntax::Proto3,
+                _ => Syntax::Proto2,
+            };
+            for message in &file.message_type {
+                types.add_message(package, message, syntax);
+            }
+            for enumeration in &file.enum_type {
+                types.add_enum(package, enumeration, syntax);
+            }
+        }
+
+        for name in types.messages.keys().chain(types.enums.keys()) {
+            if self.messages.contains_key(name) || self.enums.contains_key(name) {
+                return Err(Error::DuplicateType(name.clone()));
+            }
+        }
+
+        // every message gets a cell up front so fields can refer to messages that are not built yet
+        let cells: HashMap<&str, &'static OnceLock<&'static MessageInfo>> = types
+            .messages
+            .keys()
+            .map(|name| (name.as_str(), &*Box::leak(Box::default())))
+            .collect();
+        let enums: HashMap<&str, &'static EnumInfo> = types
+            .enums
+            .iter()
+            .map(|(name, (enumeration, syntax))| {
+                (name.as_str(), leak_enum(name, enumeration, *syntax))
+            })
+            .collect();
+
+        let resolver = Resolver {
+            pool: self,
+            cells: &cells,
+            enums: &enums,
+        };
+        let mut messages = Vec::with_capacity(types.messages.len());
+        for (name, (message, syntax)) in &types.messages {
+            let fields = message
+                .field
+                .iter()
+                .map(|field| resolver.field_info(name, field, *syntax))
+                .collect::<Result<Vec<_>, _>>()?;
+            // protoc declares synthetic oneofs after every other oneof, so leaving them out does
+            // not disturb the indexes of the rest
+            let oneofs = message
+                .oneof_decl
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| {
+                    message.field.iter().any(|f| {
+                        f.oneof_index == Some(*index as i32) && f.proto3_optional != Some(true)
+                    })
+                })
+                .map(|(_, oneof)| OneofInfo {
+                    name: leak_str(oneof.name.as_deref().unwrap_or("")),
+                })
+                .collect::<Vec<_>>();
+            let message_info = MessageInfo {
+                name: leak_str(name),
+                fields: Box::leak(fields.into_boxed_slice()),
+                oneofs: Box::leak(oneofs.into_boxed_slice()),
+                syntax: *syntax,
+                is_map: message
+                    .options
+                    .as_ref()
+                    .and_then(|o| o.map_entry)
+                    .unwrap_or(false),
+            };
+            messages.push((name, &*Box::leak(Box::new(message_info))));
+        }
+
+        for (name, message_info) in messages {
+            let _ = cells[name.as_str()].set(message_info);
+            self.messages.insert(name.clone(), message_info);
+        }
+        for (name, enum_info) in enums {
+            self.enums.insert(name.to_string(), enum_info);
+        }
+        Ok(())
+    }
+
+    /// Looks up a message by its fully qualified name, with or without a leading dot.
+    pub fn get_message(&self, name: &str) -> Option<&'static MessageInfo> {
+        let name = name.strip_prefix('.').unwrap_or(name);
+        self.messages.get(name).copied()
+    }
+
+    /// Looks up an enum by its fully qualified name, with or without a leading dot.
+    pub fn get_enum(&self, name: &str) -> Option<&'static EnumInfo> {
+        let name = name.strip_prefix('.').unwrap_or(name);
+        self.enums.get(name).copied()
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &'static MessageInfo> + '_ {
+        self.messages.values().copied()
+    }
+
+    pub fn enums(&self) -> impl Iterator<Item = &'static EnumInfo> + '_ {
+        self.enums.values().copied()
+    }
+}
+
+// The types declared by a set of files, keyed by fully qualified name.
+#[derive(Default)]
+struct Types<'a> {
+    messages: HashMap<String, (&'a DescriptorProto, Syntax)>,
+    enums: HashMap<String, (&'a EnumDescriptorProto, Syntax)>,
+}
+
+impl<'a> Types<'a> {
+    fn add_message(&mut self, scope: &str, message: &'a DescriptorProto, syntax: Syntax) {
+        let name = qualify(scope, message.name.as_deref().unwrap_or(""));
+        for nested in &message.nested_type {
+            self.add_message(&name, nested, syntax);
+        }
+        for enumeration in &message.enum_type {
+            self.add_enum(&name, enumeration, syntax);
+        }
+        self.messages.insert(name, (message, syntax));
+    }
+
+    fn add_enum(&mut self, scope: &str, enumeration: &'a EnumDescriptorProto, syntax: Syntax) {
+        let name = qualify(scope, enumeration.name.as_deref().unwrap_or(""));
+        self.enums.insert(name, (enumeration, syntax));
+    }
+}
+
+// Finds the descriptors that fields refer to, among the types being added and those already in the
+// pool.
+struct Resolver<'a> {
+    pool: &'a DescriptorPool,
+    cells: &'a HashMap<&'a str, &'static OnceLock<&'static MessageInfo>>,
+    enums: &'a HashMap<&'a str, &'static EnumInfo>,
+}
+
+impl<'a> Resolver<'a> {
+    fn field_info(
+        &self,
+        scope: &str,
+        field: &FieldDescriptorProto,
+        syntax: Syntax,
+    ) -> Result<FieldInfo, Error> {
+        let name = field.name.as_deref().unwrap_or("");
+        let number = field
+            .number
+            .and_then(|n| FieldNumber::try_from(n).ok())
+            .ok_or_else(|| Error::Message(format!("invalid field number for {}", name)))?;
+        let cardinality = match field.label {
+            Some(2) => Cardinality::Required,
+            Some(3) => Cardinality::Repeated,
+            _ => Cardinality::Optional,
+        };
+
+        let mut message_info = None;
+        let mut enum_info = None;
+        let type_name = field.type_name.as_deref().unwrap_or("");
+        let ty = match field.r#type {
+            // types that refer to another descriptor must be resolved
+            Some(10 | 11 | 14) | None => {
+                if type_name.is_empty() {
+                    return Err(Error::Message(format!("missing type for {}", name)));
+                }
+                if let Some(message) = self.message(scope, type_name) {
+                    message_info = Some(message);
+                    match field.r#type {
+                        Some(10) => Type::Group,
+                        _ => Type::Message,
+                    }
+                } else if let Some(enumeration) = self.enumeration(scope, type_name) {
+                    enum_info = Some(enumeration);
+                    Type::Enum
+                } else {
+                    return Err(Error::TypeNotFound(type_name.to_string()));
+                }
+            }
+            Some(ty) => scalar_type(ty)
+                .ok_or_else(|| Error::Message(format!("invalid type {} for {}", ty, name)))?,
+        };
+
+        // repeated scalar numeric fields are packed by default in proto3
+        let packed = matches!(cardinality, Cardinality::Repeated)
+            && ty.is_packable()
+            && field
+                .options
+                .as_ref()
+                .and_then(|o| o.packed)
+                .unwrap_or(syntax == Syntax::Proto3);
+
+        let proto3_optional = field.proto3_optional.unwrap_or(false);
+        let default_value = field
+            .default_value
+            .as_deref()
+            .map(|default| {
+                default_value(default, ty, enum_info).ok_or_else(|| {
+                    Error::Message(format!("invalid default {} for {}", default, name))
+                })
+            })
+            .transpose()?;
+        Ok(FieldInfo {
+            name: leak_str(name),
+            number,
+            cardinality,
+            ty,
+            type_name: leak_str(type_name),
+            json_name: match &field.json_name {
+                Some(json_name) => leak_str(json_name),
+                None => leak_str(&json_name(name)),
+            },
+            packed,
+            // proto3 optional fields are members of a synthetic oneof, which is left out
+            oneof_index: field.oneof_index.filter(|_| !proto3_optional),
+            proto3_optional,
+            default_value,
+            message_info,
+            enum_info,
+        })
+    }
+
+    fn message(&self, scope: &str, type_name: &str) -> Option<MessageRef> {
+        resolve(scope, type_name, |name| {
+            self.cells
+                .get(name)
+                .map(|cell| MessageRef::Lazy(cell))
+                .or_else(|| self.pool.get_message(name).map(MessageRef::Static))
+        })
+    }
+
+    fn enumeration(&self, scope: &str, type_name: &str) -> Option<&'static EnumInfo> {
+        resolve(scope, type_name, |name| {
+            self.enums
+                .get(name)
+                .copied()
+                .or_else(|| self.pool.get_enum(name))
+        })
+    }
+}
+
+// Resolves a type name the way protoc does. Fully qualified names start with a dot, anything else
+// is looked up in the enclosing scopes from the innermost outwards.
+fn resolve<T>(scope: &str, type_name: &str, find: impl Fn(&str) -> Option<T>) -> Option<T> {
+    if type_name.is_empty() {
+        return None;
+    }
+    if let Some(name) = type_name.strip_prefix('.') {
+        return find(name);
+    }
+
+    let mut scope = scope;
+    loop {
+        if let Some(found) = find(&qualify(scope, type_name)) {
+            return Some(found);
+        }
+        if scope.is_empty() {
+            return None;
+        }
+        scope = scope.rsplit_once('.').map_or("", |(parent, _)| parent);
+    }
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+fn scalar_type(ty: i32) -> Option<Type> {
+    match ty {
+        1 => Some(Type::Double),
+        2 => Some(Type::Float),
+        3 => Some(Type::Int64),
+        4 => Some(Type::Uint64),
+        5 => Some(Type::Int32),
+        6 => Some(Type::Fixed64),
+        7 => Some(Type::Fixed32),
+        8 => Some(Type::Bool),
+        9 => Some(Type::String),
+        12 => Some(Type::Bytes),
+        13 => Some(Type::Uint32),
+        15 => Some(Type::SFixed32),
+        16 => Some(Type::SFixed64),
+        17 => Some(Type::SInt32),
+        18 => Some(Type::SInt64),
+        _ => None,
+    }
+}
+
+// Parses a default the way protoc writes it in a descriptor: numbers and bools as they appear in
+// the .proto file, enums by the name of their value and bytes with C escapes.
+fn default_value(
+    default: &str,
+    ty: Type,
+    enum_info: Option<&'static EnumInfo>,
+) -> Option<DefaultValue> {
+    match ty {
+        Type::Bool => default.parse().ok().map(DefaultValue::Bool),
+        Type::Int32 | Type::SInt32 | Type::SFixed32 => default
+            .parse::<i32>()
+            .ok()
+            .map(|v| DefaultValue::Int(v.into())),
+        Type::Int64 | Type::SInt64 | Type::SFixed64 => default.parse().ok().map(DefaultValue::Int),
+        Type::Uint32 | Type::Fixed32 => default
+            .parse::<u32>()
+            .ok()
+            .map(|v| DefaultValue::Uint(v.into())),
+        Type::Uint64 | Type::Fixed64 => default.parse().ok().map(DefaultValue::Uint),
+        Type::Float => default
+            .parse::<f32>()
+            .ok()
+            .map(|v| DefaultValue::Float(v.into())),
+        Type::Double => default.parse().ok().map(DefaultValue::Float),
+        Type::String => Some(DefaultValue::String(leak_str(default))),
+        Type::Bytes => {
+            unescape(default).map(|b| DefaultValue::Bytes(Box::leak(b.into_boxed_slice())))
+        }
+        Type::Enum => enum_info?
+            .value
+            .iter()
+            .find(|v| v.name == default)
+            .map(|v| DefaultValue::Enum(v.number)),
+        Type::Message | Type::Group => None,
+    }
+}
+
+fn leak_enum(name: &str, enumeration: &EnumDescriptorProto, syntax: Syntax) -> &'static EnumInfo {
+    let value = enumeration
+        .value
+        .iter()
+        .map(|v| EnumValue {
+            name: leak_str(v.name.as_deref().unwrap_or("")),
+            number: v.number.unwrap_or(0),
+        })
+        .collect::<Vec<_>>();
+    Box::leak(Box::new(EnumInfo {
+        name: leak_str(name),
+        value: Box::leak(value.into_boxed_slice()),
+        syntax,
+    }))
+}
+
+fn leak_str(s: &str) -> &'static str {
+    Box::leak(s.to_string().into_boxed_str())
+}
@@ -0,0 +1,61 @@
+//! Message descriptors looked up by type URL, for the messages packed in a `google.protobuf.Any`.
+//!
+//! A type URL names a message by its full name after the last `/`, as in
+//! `type.googleapis.com/google.protobuf.Duration`. The JSON and text formats use a
+//! [`TypeRegistry`] to write the message held by an `Any` inline rather than as bytes.
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::{error::Error, info::MessageInfo, pool::DescriptorPool, value::DynamicMessage};
+
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+/// The type URL of a message, such as `type.googleapis.com/google.protobuf.Duration`.
+pub fn type_url(message_info: &MessageInfo) -> String {
+    format!("{}{}", TYPE_URL_PREFIX, message_info.name)
+}
+
+/// The full name of the message a type URL names.
+pub fn type_name(type_url: &str) -> &str {
+    match type_url.rfind('/') {
+        Some(i) => &type_url[i + 1..],
+        None => type_url,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TypeRegistry {
+    messages: HashMap<&'static str, &'static MessageInfo>,
+}
+
+impl TypeRegistry {
+    pub fn new() -> Self {
+        TypeRegistry::default()
+    }
+
+    /// Adds a message, replacing any added before with the same full name.
+    pub fn add(&mut self, message_info: &'static MessageInfo) {
+        self.messages.insert(message_info.name, message_info);
+    }
+
+    /// Adds every message in the pool, leaving out map entries.
+    pub fn add_pool(&mut self, pool: &DescriptorPool) {
+        for message_info in pool.messages().filter(|m| !m.is_map) {
+            self.add(message_info);
+        }
+    }
+
+    pub fn find(&self, type_url: &str) -> Option<&'static MessageInfo> {
+        self.messages.get(type_name(type_url)).copied()
+    }
+
+    /// Decodes the message packed in an `Any` with the given type URL.
+    pub fn decode(&self, type_url: &str, buf: Bytes) -> Result<DynamicMessage, Error> {
+        let message_info = self
+            .find(type_url)
+            .ok_or_else(|| Error::TypeNotFound(type_url.to_string()))?;
+        DynamicMessage::decode(buf, message_info)
+    }
+}
@@ -1,29 +1,126 @@
+use std::vec;
+
 use bytes::BufMut;
 use serde::ser::{self, Impossible, SerializeMap, SerializeSeq, SerializeStruct};
 use tobu_format::{
     field::FieldNumber,
     wire::{
-        encode_tag, encode_zig_zag, put_bytes, put_fixed32, put_fixed64, put_tag, put_varint,
-        size_bytes, size_fixed32, size_fixed64, size_tag, size_varint,
+        encode_zig_zag, put_bytes, put_fixed32, put_fixed64, put_tag, put_varint, size_bytes,
+        size_fixed32, size_fixed64, size_group, size_tag, size_varint, WireType,
     },
 };
 
 use crate::{
     error::Error,
-    info::{FieldInfo, MessageInfo, Syntax, Type},
+    info::{Cardinality, EnumInfo, FieldInfo, MessageInfo, Syntax, Type},
+    unknown::UNKNOWN_FIELDS,
 };
 
+/// Options for writing messages to the wire format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Writes messages with required fields that are not set, such as a message that is only part
+    /// of what will eventually be merged together.
+    pub partial: bool,
+}
+
+// Where a value sits within its field, which decides whether it is preceded by a tag and whether
+// it may be left out when it holds the default value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Context {
+    // a singular field, tagged and skipped when it holds a proto3 default
+    Field,
+    // the member of a oneof that is set, always tagged
+    Member,
+    // an element of an unpacked repeated field, always tagged
+    Element,
+    // an element of a packed repeated field or a byte of a bytes field, never tagged
+    Packed,
+    // the unknown fields of a message, already encoded and written as is
+    Unknown,
+}
+
+// The state of a message that a nested message is serialized within.
+#[derive(Clone, Copy)]
+struct Parent {
+    message_info: &'static MessageInfo,
+    field_index: usize,
+    context: Context,
+}
+
+// SizeHint walks the tree of messages once and records the length of every nested message in the
+// order they are visited, which is the same order the Serializer visits them in. This way the
+// Serializer can write the length prefix of a nested message without walking it again.
+//
+// Every message is checked for required fields that are not set along the way, so the Serializer
+// never starts on a message it cannot finish.
 pub(crate) struct SizeHint {
     message_info: &'static MessageInfo,
     field_index: usize,
     is_nested: bool,
+    context: Context,
+    sizes: Vec<usize>,
+    options: Options,
 }
+
 impl SizeHint {
-    pub fn new(message_info: &'static MessageInfo) -> Self {
+    pub fn new(message_info: &'static MessageInfo, options: Options) -> Self {
         SizeHint {
             message_info,
             field_index: 0,
             is_nested: false,
+            context: Context::Field,
+            sizes: Vec::new(),
+            options,
+        }
+    }
+
+    pub fn into_sizes(self) -> Vec<usize> {
+        self.sizes
+    }
+
+    fn parent(&self) -> Parent {
+        Parent {
+            message_info: self.message_info,
+            field_index: self.field_index,
+            context: self.context,
+        }
+    }
+
+    fn restore(&mut self, parent: Parent) {
+        self.message_info = parent.message_info;
+        self.field_index = parent.field_index;
+        self.context = parent.context;
+    }
+
+    fn skip_default(&self, is_default: bool) -> Result<bool, Error> {
+        if self.context != Context::Field
+            || self.message_info.syntax != Syntax::Proto3
+            || !is_default
+        {
+            return Ok(false);
+        }
+        // members of a oneof and optional fields are written whenever they are set
+        let field_info = self.field_info()?;
+        Ok(field_info.oneof_index.is_none() && !field_info.proto3_optional)
+    }
+
+    // A required field may only be left unset in a partial message. The field is named by the
+    // message that holds it.
+    fn check_required(&self) -> Result<(), Error> {
+        if self.context == Context::Field
+            && !self.options.partial
+            && matches!(self.field_info()?.cardinality, Cardinality::Required)
+        {
+            return Err(Error::RequiredFieldNotSet(String::new()));
+        }
+        Ok(())
+    }
+
+    fn size_tag(&self, field_info: &FieldInfo) -> usize {
+        match self.context {
+            Context::Packed => 0,
+            _ => size_tag(field_info.number),
         }
     }
 
@@ -34,11 +131,45 @@ impl SizeHint {
             .ok_or_else(|| ser::Error::custom("field descriptor not found"))
     }
 
+    fn is_float(&self) -> Result<bool, Error> {
+        Ok(matches!(self.field_info()?.ty, Type::Float | Type::Double))
+    }
+
+    // A map within a message is a map field, anything else is a message keyed by field.
+    fn is_map_field(&self) -> Result<bool, Error> {
+        Ok(self.is_nested && self.field_info()?.message_info().is_some_and(|m| m.is_map))
+    }
+
+    // A varint or fixed width value, with floats passed as their bits.
+    fn scalar(&self, v: u64) -> Result<usize, Error> {
+        let field_info = self.field_info()?;
+        if self.skip_default(v == 0)? {
+            return Ok(0);
+        }
+
+        let size_tag = self.size_tag(field_info);
+        match field_info.ty {
+            Type::Int32 | Type::Uint32 | Type::Int64 | Type::Uint64 | Type::Bool | Type::Enum => {
+                Ok(size_tag + size_varint(v))
+            }
+            Type::Fixed32 | Type::SFixed32 | Type::Float => Ok(size_tag + size_fixed32()),
+            Type::Fixed64 | Type::SFixed64 | Type::Double => Ok(size_tag + size_fixed64()),
+            Type::SInt32 | Type::SInt64 => Ok(size_tag + size_varint(encode_zig_zag(v as i64))),
+            _ => Err(ser::Error::custom("field descriptor does not match value")),
+        }
+    }
+
     fn message_info(&self) -> Result<&'static MessageInfo, Error> {
         self.field_info()?
-            .message_info
+            .message_info()
             .ok_or_else(|| ser::Error::custom("message descriptor not found"))
     }
+
+    fn enum_info(&self) -> Result<&'static EnumInfo, Error> {
+        self.field_info()?
+            .enum_info
+            .ok_or_else(|| ser::Error::custom("enum descriptor not found"))
+    }
 }
 
 impl<'a> serde::Serializer for &'a mut SizeHint {
@@ -54,7 +185,7 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v as u64)
+        self.scalar(v as u64)
     }
 
     fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
@@ -66,15 +197,22 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u32(v as u32)
+        self.serialize_i64(v as i64)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v as u64)
+        if self.is_float()? {
+            self.serialize_f64(v as f64)
+        } else {
+            self.scalar(v as u64)
+        }
     }
 
     fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
-        Err(ser::Error::custom("u8 not supported"))
+        match (self.field_info()?.ty, self.context) {
+            (Type::Bytes, Context::Packed) => Ok(1),
+            _ => Err(ser::Error::custom("u8 not supported")),
+        }
     }
 
     fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
@@ -86,29 +224,23 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        let field_info = self.field_info()?;
-        if self.message_info.syntax == Syntax::Proto3 && v == 0 && field_info.oneof_index.is_none()
-        {
-            return Ok(0);
-        }
-
-        match field_info.ty {
-            Type::Int32 | Type::Uint32 | Type::Int64 | Type::Uint64 | Type::Bool | Type::Enum => {
-                Ok(size_varint(v))
-            }
-            Type::Fixed32 | Type::SFixed32 | Type::Float => Ok(size_fixed32()),
-            Type::Fixed64 | Type::SFixed64 | Type::Double => Ok(size_fixed64()),
-            Type::SInt32 | Type::SInt64 => Ok(size_varint(encode_zig_zag(v as i64))),
-            _ => Err(ser::Error::custom("field descriptor does not match value")),
+        if self.is_float()? {
+            self.serialize_f64(v as f64)
+        } else {
+            self.scalar(v)
         }
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v.to_bits() as u64)
+        self.serialize_f64(v as f64)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v.to_bits())
+        match self.field_info()?.ty {
+            Type::Float => self.scalar((v as f32).to_bits() as u64),
+            Type::Double => self.scalar(v.to_bits()),
+            _ => Err(ser::Error::custom("field descriptor does not match value")),
+        }
     }
 
     fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
@@ -120,23 +252,23 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        if self.message_info.syntax == Syntax::Proto3
-            && v.is_empty()
-            && self.field_info()?.oneof_index.is_none()
-        {
+        if self.context == Context::Unknown {
+            Ok(v.len())
+        } else if self.skip_default(v.is_empty())? {
             Ok(0)
         } else {
-            Ok(size_bytes(v.len()))
+            Ok(self.size_tag(self.field_info()?) + size_bytes(v.len()))
         }
     }
 
     fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.check_required()?;
         Ok(0)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         value.serialize(self)
     }
@@ -149,57 +281,92 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
         Err(ser::Error::custom("unit struct not supported"))
     }
 
+    // An enum is written as the number of its value.
     fn serialize_unit_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Err(ser::Error::custom("unit variant not supported"))
+        let number = self.enum_info()?.number(variant_index)?;
+        self.serialize_i32(number)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
         _value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         Err(ser::Error::custom("newtype struct not supported"))
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    // A oneof is an enum with a variant for each of its members. An open enum holds a number it
+    // does not know in a variant of its own.
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        Err(ser::Error::custom("newtype variant not supported"))
+        let field_info = self.field_info()?;
+        if self.context == Context::Field && field_info.oneof_index.is_some() {
+            let parent = self.parent();
+            self.field_index = self
+                .message_info
+                .oneof_member(self.field_index, variant_index as usize)?;
+            self.context = Context::Member;
+            let name = self.field_info()?.name;
+            let result = value.serialize(&mut *self).map_err(|e| e.within(name));
+            self.restore(parent);
+            result
+        } else if matches!(field_info.ty, Type::Enum) {
+            value.serialize(self)
+        } else {
+            Err(ser::Error::custom("newtype variant not supported"))
+        }
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let field_info = self.field_info()?;
-        let size_tag = size_varint(field_info.number.get() as u64);
-        if field_info.packed {
-            // tag + len + element_1..element_len
+        if is_byte_seq(field_info, self.context) {
+            // tag + len + byte_1..byte_len
             let len = len.ok_or(Error::UnknownSeqLen)?;
-            let size_len = size_varint(len as u64);
+            let total = if self.skip_default(len == 0)? {
+                0
+            } else {
+                self.size_tag(field_info) + size_varint(len as u64)
+            };
+            self.context = Context::Packed;
             Ok(RepeatedSizeHint {
-                total: size_tag + size_len,
+                total,
                 ser: self,
-                size_tag: 0,
+                packed: None,
+            })
+        } else if field_info.packed {
+            // tag + len + element_1..element_len, where len is the size of the elements in bytes.
+            // Reserve a slot for it, which is only known once every element is sized.
+            let size_index = self.sizes.len();
+            self.sizes.push(0);
+            self.context = Context::Packed;
+            Ok(RepeatedSizeHint {
+                total: 0,
+                ser: self,
+                packed: Some(size_index),
             })
         } else {
             // (tag + element_1)..(tag + element_len)
+            self.context = Context::Element;
             Ok(RepeatedSizeHint {
                 total: 0,
                 ser: self,
-                size_tag,
+                packed: None,
             })
         }
     }
@@ -227,10 +394,23 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(MapSizeHint {
+        if !self.is_map_field()? {
+            return self.serialize_struct("", 0).map(MapSizeHint::Fields);
+        }
+
+        let number = self.field_info()?.number;
+        let entry_info = self.message_info()?;
+        let (key_index, value_index) = entry_info.map_entry()?;
+        Ok(MapSizeHint::Entries(EntrySizeHint {
             total: 0,
+            parent: self.parent(),
             ser: self,
-        })
+            number,
+            entry_info,
+            key_index,
+            value_index,
+            size_index: 0,
+        }))
     }
 
     fn serialize_struct(
@@ -239,13 +419,21 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         if self.is_nested {
-            let parent = Some((self.message_info, self.field_index));
+            let parent = Some(self.parent());
+            // reserve a slot for the length, which is only known once the message ends. Groups
+            // are delimited by tags instead, so they have no length.
+            let size_index = self.sizes.len();
+            if !matches!(self.field_info()?.ty, Type::Group) {
+                self.sizes.push(0);
+            }
             self.message_info = self.message_info()?;
             self.field_index = 0;
+            self.context = Context::Field;
             Ok(MessageSizeHint {
                 total: 0,
                 ser: self,
                 parent,
+                size_index,
             })
         } else {
             self.is_nested = true;
@@ -253,6 +441,7 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
                 total: 0,
                 ser: self,
                 parent: None,
+                size_index: 0,
             })
         }
     }
@@ -271,55 +460,121 @@ impl<'a> serde::Serializer for &'a mut SizeHint {
 pub(crate) struct RepeatedSizeHint<'a> {
     total: usize,
     ser: &'a mut SizeHint,
-    size_tag: usize,
+    // the slot of a packed field's length
+    packed: Option<usize>,
 }
 
 impl<'a> SerializeSeq for RepeatedSizeHint<'a> {
     type Ok = usize;
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        self.total += self.size_tag;
         self.total += value.serialize(&mut *self.ser)?;
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        Ok(self.total)
+        match self.packed {
+            // an empty packed field is left out entirely
+            Some(_) if self.total == 0 => Ok(0),
+            Some(size_index) => {
+                self.ser.sizes[size_index] = self.total;
+                let number = self.ser.field_info()?.number;
+                Ok(size_tag(number) + size_bytes(self.total))
+            }
+            None => Ok(self.total),
+        }
+    }
+}
+
+// A serde map is either a map field, or a message with its fields keyed by name or number as is the
+// case for a Value.
+pub(crate) enum MapSizeHint<'a> {
+    Entries(EntrySizeHint<'a>),
+    Fields(MessageSizeHint<'a>),
+}
+
+impl<'a> SerializeMap for MapSizeHint<'a> {
+    type Ok = usize;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self {
+            MapSizeHint::Entries(entries) => entries.serialize_key(key),
+            MapSizeHint::Fields(fields) => fields.serialize_key(key),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self {
+            MapSizeHint::Entries(entries) => entries.serialize_value(value),
+            MapSizeHint::Fields(fields) => fields.serialize_value(value),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            MapSizeHint::Entries(entries) => SerializeMap::end(entries),
+            MapSizeHint::Fields(fields) => SerializeStruct::end(fields),
+        }
     }
 }
 
-pub(crate) struct MapSizeHint<'a> {
+// Each map entry is a nested message with the key in field 1 and the value in field 2, which is
+// sized the same way as any other nested message.
+pub(crate) struct EntrySizeHint<'a> {
     total: usize,
     ser: &'a mut SizeHint,
+    parent: Parent,
+    number: FieldNumber,
+    entry_info: &'static MessageInfo,
+    key_index: usize,
+    value_index: usize,
+    size_index: usize,
 }
 
-impl<'a> SerializeMap for MapSizeHint<'a> {
+impl<'a> SerializeMap for EntrySizeHint<'a> {
     type Ok = usize;
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        // field number for key is always 1, so tag size is always 1
-        self.total = 1 + key.serialize(&mut *self.ser)?;
+        self.size_index = self.ser.sizes.len();
+        self.ser.sizes.push(0);
+
+        // keys and values are always written, even when they hold the default
+        self.ser.message_info = self.entry_info;
+        self.ser.field_index = self.key_index;
+        self.ser.context = Context::Element;
+        self.ser.sizes[self.size_index] = key.serialize(&mut *self.ser)?;
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        // field number for key is always 2, so tag size is always 1
-        self.total = 1 + value.serialize(&mut *self.ser)?;
+        self.ser.field_index = self.value_index;
+        self.ser.context = Context::Element;
+        let size = self.ser.sizes[self.size_index] + value.serialize(&mut *self.ser)?;
+        self.ser.sizes[self.size_index] = size;
+        self.total += size_tag(self.number) + size_bytes(size);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.restore(self.parent);
         Ok(self.total)
     }
 }
@@ -327,59 +582,150 @@ impl<'a> SerializeMap for MapSizeHint<'a> {
 pub(crate) struct MessageSizeHint<'a> {
     total: usize,
     ser: &'a mut SizeHint,
-    parent: Option<(&'static MessageInfo, usize)>,
+    parent: Option<Parent>,
+    size_index: usize,
 }
 
 impl<'a> SerializeStruct for MessageSizeHint<'a> {
     type Ok = usize;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        _key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
+        // unknown fields have no field descriptor, so they do not take up a field index
+        if key == UNKNOWN_FIELDS {
+            self.ser.context = Context::Unknown;
+            self.total += value.serialize(&mut *self.ser)?;
+            return Ok(());
+        }
+
+        self.ser.context = Context::Field;
         let field_info = self.ser.field_info()?;
-        self.total += size_tag(field_info.number);
-        self.total += value.serialize(&mut *self.ser)?;
-        self.ser.field_index += 1;
+        self.total += value.serialize(&mut *self.ser).map_err(|e| {
+            // a oneof is named by its member instead
+            match field_info.oneof_index {
+                Some(_) => e,
+                None => e.within(field_info.name),
+            }
+        })?;
+        self.ser.field_index = self.ser.message_info.next_field_index(self.ser.field_index);
         Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if let Some((info, index)) = self.parent {
-            self.ser.message_info = info;
-            self.ser.field_index = index;
+        match self.parent {
+            Some(parent) => {
+                self.ser.restore(parent);
+                let field_info = self.ser.field_info()?;
+                match field_info.ty {
+                    Type::Group => Ok(size_group(field_info.number, self.total)),
+                    _ => {
+                        self.ser.sizes[self.size_index] = self.total;
+                        Ok(self.ser.size_tag(field_info) + size_bytes(self.total))
+                    }
+                }
+            }
+            None => Ok(self.total),
         }
-        Ok(self.total)
     }
 
-    fn skip_field(&mut self, _key: &'static str) -> Result<(), Self::Error> {
-        self.ser.field_index += 1;
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        if key != UNKNOWN_FIELDS {
+            self.ser.context = Context::Field;
+            self.ser.check_required().map_err(|e| e.within(key))?;
+            self.ser.field_index = self.ser.message_info.next_field_index(self.ser.field_index);
+        }
         Ok(())
     }
 }
 
+impl<'a> SerializeMap for MessageSizeHint<'a> {
+    type Ok = usize;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.ser.field_index = key.serialize(FieldKeySerializer(self.ser.message_info))?;
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.ser.context = Context::Field;
+        self.total += value.serialize(&mut *self.ser)?;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeStruct::end(self)
+    }
+}
+
 pub(crate) struct Serializer<'b, B> {
     buffer: &'b mut B,
     message_info: &'static MessageInfo,
     field_index: usize,
     is_nested: bool,
+    context: Context,
+    sizes: vec::IntoIter<usize>,
 }
 
 impl<'b, B> Serializer<'b, B>
 where
     B: BufMut,
 {
-    pub fn new(buffer: &'b mut B, message_info: &'static MessageInfo) -> Self {
+    pub fn new(buffer: &'b mut B, message_info: &'static MessageInfo, sizes: Vec<usize>) -> Self {
         Serializer {
             buffer,
             message_info,
             field_index: 0,
             is_nested: false,
+            context: Context::Field,
+            sizes: sizes.into_iter(),
+        }
+    }
+
+    fn parent(&self) -> Parent {
+        Parent {
+            message_info: self.message_info,
+            field_index: self.field_index,
+            context: self.context,
+        }
+    }
+
+    fn restore(&mut self, parent: Parent) {
+        self.message_info = parent.message_info;
+        self.field_index = parent.field_index;
+        self.context = parent.context;
+    }
+
+    fn skip_default(&self, is_default: bool) -> Result<bool, Error> {
+        if self.context != Context::Field
+            || self.message_info.syntax != Syntax::Proto3
+            || !is_default
+        {
+            return Ok(false);
+        }
+        // members of a oneof and optional fields are written whenever they are set
+        let field_info = self.field_info()?;
+        Ok(field_info.oneof_index.is_none() && !field_info.proto3_optional)
+    }
+
+    fn next_size(&mut self) -> Result<usize, Error> {
+        self.sizes
+            .next()
+            .ok_or_else(|| ser::Error::custom("message size not found"))
+    }
+
+    fn put_tag(&mut self, field_info: &FieldInfo, wire_type: WireType) {
+        if self.context != Context::Packed {
+            put_tag(&mut self.buffer, field_info.number, wire_type);
         }
     }
 
@@ -390,11 +736,55 @@ where
             .ok_or_else(|| ser::Error::custom("field descriptor not found"))
     }
 
+    fn is_float(&self) -> Result<bool, Error> {
+        Ok(matches!(self.field_info()?.ty, Type::Float | Type::Double))
+    }
+
+    // A map within a message is a map field, anything else is a message keyed by field.
+    fn is_map_field(&self) -> Result<bool, Error> {
+        Ok(self.is_nested && self.field_info()?.message_info().is_some_and(|m| m.is_map))
+    }
+
+    // A varint or fixed width value, with floats passed as their bits.
+    fn scalar(&mut self, v: u64) -> Result<(), Error> {
+        let field_info = self.field_info()?;
+        if self.skip_default(v == 0)? {
+            return Ok(());
+        }
+
+        self.put_tag(field_info, field_info.ty.wire_type());
+        match field_info.ty {
+            Type::Int32 | Type::Uint32 | Type::Int64 | Type::Uint64 | Type::Bool | Type::Enum => {
+                put_varint(&mut self.buffer, v);
+                Ok(())
+            }
+            Type::Fixed32 | Type::SFixed32 | Type::Float => {
+                put_fixed32(&mut self.buffer, v as u32);
+                Ok(())
+            }
+            Type::Fixed64 | Type::SFixed64 | Type::Double => {
+                put_fixed64(&mut self.buffer, v);
+                Ok(())
+            }
+            Type::SInt32 | Type::SInt64 => {
+                put_varint(&mut self.buffer, encode_zig_zag(v as i64));
+                Ok(())
+            }
+            _ => Err(ser::Error::custom("field descriptor does not match value")),
+        }
+    }
+
     fn message_info(&self) -> Result<&'static MessageInfo, Error> {
         self.field_info()?
-            .message_info
+            .message_info()
             .ok_or_else(|| ser::Error::custom("message descriptor not found"))
     }
+
+    fn enum_info(&self) -> Result<&'static EnumInfo, Error> {
+        self.field_info()?
+            .enum_info
+            .ok_or_else(|| ser::Error::custom("enum descriptor not found"))
+    }
 }
 
 impl<'a, 'b, B> serde::Serializer for &'a mut Serializer<'b, B>
@@ -412,7 +802,7 @@ where
     type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
 
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v as u64)
+        self.scalar(v as u64)
     }
 
     fn serialize_i8(self, _v: i8) -> Result<Self::Ok, Self::Error> {
@@ -424,15 +814,25 @@ where
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u32(v as u32)
+        self.serialize_i64(v as i64)
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v as u64)
+        if self.is_float()? {
+            self.serialize_f64(v as f64)
+        } else {
+            self.scalar(v as u64)
+        }
     }
 
-    fn serialize_u8(self, _v: u8) -> Result<Self::Ok, Self::Error> {
-        Err(ser::Error::custom("u8 not supported"))
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        match (self.field_info()?.ty, self.context) {
+            (Type::Bytes, Context::Packed) => {
+                self.buffer.put_u8(v);
+                Ok(())
+            }
+            _ => Err(ser::Error::custom("u8 not supported")),
+        }
     }
 
     fn serialize_u16(self, _v: u16) -> Result<Self::Ok, Self::Error> {
@@ -444,39 +844,23 @@ where
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        let field_info = self.field_info()?;
-        if self.message_info.syntax == Syntax::Proto3 && v == 0 && field_info.oneof_index.is_none()
-        {
-            return Ok(());
-        }
-
-        match field_info.ty {
-            Type::Int32 | Type::Uint32 | Type::Int64 | Type::Uint64 | Type::Bool | Type::Enum => {
-                put_varint(&mut self.buffer, v);
-                Ok(())
-            }
-            Type::Fixed32 | Type::SFixed32 | Type::Float => {
-                put_fixed32(&mut self.buffer, v as u32);
-                Ok(())
-            }
-            Type::Fixed64 | Type::SFixed64 | Type::Double => {
-                put_fixed64(&mut self.buffer, v);
-                Ok(())
-            }
-            Type::SInt32 | Type::SInt64 => {
-                put_varint(&mut self.buffer, encode_zig_zag(v as i64));
-                Ok(())
-            }
-            _ => Err(ser::Error::custom("field descriptor does not match value")),
+        if self.is_float()? {
+            self.serialize_f64(v as f64)
+        } else {
+            self.scalar(v)
         }
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v.to_bits() as u64)
+        self.serialize_f64(v as f64)
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.serialize_u64(v.to_bits())
+        match self.field_info()?.ty {
+            Type::Float => self.scalar((v as f32).to_bits() as u64),
+            Type::Double => self.scalar(v.to_bits()),
+            _ => Err(ser::Error::custom("field descriptor does not match value")),
+        }
     }
 
     fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
@@ -488,12 +872,13 @@ where
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        if self.message_info.syntax == Syntax::Proto3
-            && v.is_empty()
-            && self.field_info()?.oneof_index.is_none()
-        {
+        if self.context == Context::Unknown {
+            self.buffer.put_slice(v);
+            Ok(())
+        } else if self.skip_default(v.is_empty())? {
             Ok(())
         } else {
+            self.put_tag(self.field_info()?, WireType::Bytes);
             put_bytes(&mut self.buffer, v);
             Ok(())
         }
@@ -503,9 +888,9 @@ where
         Ok(())
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         value.serialize(self)
     }
@@ -518,57 +903,80 @@ where
         Err(ser::Error::custom("unit struct not supported"))
     }
 
+    // An enum is written as the number of its value.
     fn serialize_unit_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
-        Err(ser::Error::custom("unit variant not supported"))
+        let number = self.enum_info()?.number(variant_index)?;
+        self.serialize_i32(number)
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
         _value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
         Err(ser::Error::custom("newtype struct not supported"))
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    // A oneof is an enum with a variant for each of its members. An open enum holds a number it
+    // does not know in a variant of its own.
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         _variant: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        Err(ser::Error::custom("newtype variant not supported"))
+        let field_info = self.field_info()?;
+        if self.context == Context::Field && field_info.oneof_index.is_some() {
+            let parent = self.parent();
+            self.field_index = self
+                .message_info
+                .oneof_member(self.field_index, variant_index as usize)?;
+            self.context = Context::Member;
+            let result = value.serialize(&mut *self);
+            self.restore(parent);
+            result
+        } else if matches!(field_info.ty, Type::Enum) {
+            value.serialize(self)
+        } else {
+            Err(ser::Error::custom("newtype variant not supported"))
+        }
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let field_info = self.field_info()?;
-        let tag = encode_tag(field_info.number, field_info.ty.wire_type());
-        if field_info.packed {
-            put_varint(&mut self.buffer, tag);
+        if is_byte_seq(field_info, self.context) {
+            // tag + len + byte_1..byte_len
             let len = len.ok_or(Error::UnknownSeqLen)?;
-            put_varint(&mut self.buffer, len as u64);
-            Ok(RepeatedSerializer {
-                ser: self,
-                tag: None,
-            })
+            if !self.skip_default(len == 0)? {
+                self.put_tag(field_info, WireType::Bytes);
+                put_varint(&mut self.buffer, len as u64);
+            }
+            self.context = Context::Packed;
+        } else if field_info.packed {
+            // tag + len + element_1..element_len
+            let len = self.next_size()?;
+            if len > 0 {
+                put_tag(&mut self.buffer, field_info.number, WireType::Bytes);
+                put_varint(&mut self.buffer, len as u64);
+            }
+            self.context = Context::Packed;
         } else {
             // (tag + element_1)..(tag + element_len)
-            Ok(RepeatedSerializer {
-                ser: self,
-                tag: Some(tag),
-            })
+            self.context = Context::Element;
         }
+        Ok(RepeatedSerializer { ser: self })
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
@@ -594,23 +1002,21 @@ where
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        let mut map_fields = self.message_info()?.fields.iter();
-
-        let key_number = FieldNumber::new(1);
-        let value_number = FieldNumber::new(2);
-
-        let key_field = map_fields
-            .find(|f| f.number == key_number)
-            .ok_or(Error::FieldNotFound(key_number))?;
-        let value_field = map_fields
-            .find(|f| f.number == value_number)
-            .ok_or(Error::FieldNotFound(value_number))?;
+        if !self.is_map_field()? {
+            return self.serialize_struct("", 0).map(MapSerializer::Fields);
+        }
 
-        Ok(MapSerializer {
+        let number = self.field_info()?.number;
+        let entry_info = self.message_info()?;
+        let (key_index, value_index) = entry_info.map_entry()?;
+        Ok(MapSerializer::Entries(EntrySerializer {
+            parent: self.parent(),
             ser: self,
-            key_tag: encode_tag(key_number, key_field.ty.wire_type()),
-            value_tag: encode_tag(value_number, value_field.ty.wire_type()),
-        })
+            number,
+            entry_info,
+            key_index,
+            value_index,
+        }))
     }
 
     fn serialize_struct(
@@ -619,9 +1025,20 @@ where
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         if self.is_nested {
-            let parent = Some((self.message_info, self.field_index));
+            let field_info = self.field_info()?;
+            match field_info.ty {
+                Type::Group => self.put_tag(field_info, WireType::StartGroup),
+                _ => {
+                    let size = self.next_size()?;
+                    self.put_tag(field_info, WireType::Bytes);
+                    put_varint(&mut self.buffer, size as u64);
+                }
+            }
+
+            let parent = Some(self.parent());
             self.message_info = self.message_info()?;
             self.field_index = 0;
+            self.context = Context::Field;
             Ok(MessageSerializer { ser: self, parent })
         } else {
             self.is_nested = true;
@@ -645,7 +1062,6 @@ where
 
 pub(crate) struct RepeatedSerializer<'a, 'b, B> {
     ser: &'a mut Serializer<'b, B>,
-    tag: Option<u64>,
 }
 
 impl<'a, 'b, B> SerializeSeq for RepeatedSerializer<'a, 'b, B>
@@ -656,13 +1072,10 @@ where
 
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        if let Some(tag) = self.tag {
-            put_varint(&mut self.ser.buffer, tag);
-        }
         value.serialize(&mut *self.ser)
     }
 
@@ -671,10 +1084,9 @@ where
     }
 }
 
-pub(crate) struct MapSerializer<'a, 'b, B> {
-    ser: &'a mut Serializer<'b, B>,
-    key_tag: u64,
-    value_tag: u64,
+pub(crate) enum MapSerializer<'a, 'b, B> {
+    Entries(EntrySerializer<'a, 'b, B>),
+    Fields(MessageSerializer<'a, 'b, B>),
 }
 
 impl<'a, 'b, B> SerializeMap for MapSerializer<'a, 'b, B>
@@ -684,30 +1096,82 @@ where
     type Ok = ();
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        put_varint(&mut self.ser.buffer, self.key_tag);
+        match self {
+            MapSerializer::Entries(entries) => entries.serialize_key(key),
+            MapSerializer::Fields(fields) => fields.serialize_key(key),
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        match self {
+            MapSerializer::Entries(entries) => entries.serialize_value(value),
+            MapSerializer::Fields(fields) => fields.serialize_value(value),
+        }
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            MapSerializer::Entries(entries) => SerializeMap::end(entries),
+            MapSerializer::Fields(fields) => SerializeStruct::end(fields),
+        }
+    }
+}
+
+pub(crate) struct EntrySerializer<'a, 'b, B> {
+    ser: &'a mut Serializer<'b, B>,
+    parent: Parent,
+    number: FieldNumber,
+    entry_info: &'static MessageInfo,
+    key_index: usize,
+    value_index: usize,
+}
+
+impl<'a, 'b, B> SerializeMap for EntrySerializer<'a, 'b, B>
+where
+    B: BufMut,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        let size = self.ser.next_size()?;
+        put_tag(&mut self.ser.buffer, self.number, WireType::Bytes);
+        put_varint(&mut self.ser.buffer, size as u64);
+
+        self.ser.message_info = self.entry_info;
+        self.ser.field_index = self.key_index;
+        self.ser.context = Context::Element;
         key.serialize(&mut *self.ser)
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        put_varint(&mut self.ser.buffer, self.value_tag);
+        self.ser.field_index = self.value_index;
+        self.ser.context = Context::Element;
         value.serialize(&mut *self.ser)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.ser.restore(self.parent);
         Ok(())
     }
 }
 
 pub(crate) struct MessageSerializer<'a, 'b, B> {
     ser: &'a mut Serializer<'b, B>,
-    parent: Option<(&'static MessageInfo, usize)>,
+    parent: Option<Parent>,
 }
 
 impl<'a, 'b, B> SerializeStruct for MessageSerializer<'a, 'b, B>
@@ -717,35 +1181,269 @@ where
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        _key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: serde::Serialize,
+        T: ?Sized + serde::Serialize,
     {
-        let field_info = self.ser.field_info()?;
-        put_tag(
-            &mut self.ser.buffer,
-            field_info.number,
-            field_info.ty.wire_type(),
-        );
-        value.serialize(&mut *self.ser)
+        // unknown fields have no field descriptor, so they do not take up a field index
+        if key == UNKNOWN_FIELDS {
+            self.ser.context = Context::Unknown;
+            return value.serialize(&mut *self.ser);
+        }
+
+        self.ser.context = Context::Field;
+        value.serialize(&mut *self.ser)?;
+        self.ser.field_index = self.ser.message_info.next_field_index(self.ser.field_index);
+
+        Ok(())
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
-        if let Some((info, index)) = self.parent {
-            self.ser.message_info = info;
-            self.ser.field_index = index;
+        if let Some(parent) = self.parent {
+            self.ser.restore(parent);
+            let field_info = self.ser.field_info()?;
+            if let Type::Group = field_info.ty {
+                self.ser.put_tag(field_info, WireType::EndGroup);
+            }
         }
 
         Ok(())
     }
 
-    fn skip_field(&mut self, _key: &'static str) -> Result<(), Self::Error> {
-        self.ser.field_index += 1;
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        if key != UNKNOWN_FIELDS {
+            self.ser.field_index = self.ser.message_info.next_field_index(self.ser.field_index);
+        }
 
         Ok(())
     }
 }
+
+impl<'a, 'b, B> SerializeMap for MessageSerializer<'a, 'b, B>
+where
+    B: BufMut,
+{
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.ser.field_index = key.serialize(FieldKeySerializer(self.ser.message_info))?;
+
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.ser.context = Context::Field;
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        SerializeStruct::end(self)
+    }
+}
+
+// A singular bytes field, or an element of a repeated one, serialized as a sequence of u8 as is the
+// case for Vec<u8>.
+fn is_byte_seq(field_info: &FieldInfo, context: Context) -> bool {
+    matches!(field_info.ty, Type::Bytes)
+        && (!matches!(field_info.cardinality, Cardinality::Repeated) || context == Context::Element)
+}
+
+// Resolves the key of a message serialized as a map to the index of its field. The key is either
+// the field name or the field number.
+pub(crate) struct FieldKeySerializer(pub(crate) &'static MessageInfo);
+
+impl FieldKeySerializer {
+    fn number(self, n: i64) -> Result<usize, Error> {
+        let number = i32::try_from(n)
+            .ok()
+            .and_then(|n| FieldNumber::try_from(n).ok())
+            .ok_or_else(|| Error::Message(format!("invalid field number {}", n)))?;
+        self.0
+            .field_index(number)
+            .ok_or(Error::FieldNotFound(number))
+    }
+
+    fn unsupported(self) -> Result<usize, Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+}
+
+impl serde::Serializer for FieldKeySerializer {
+    type Ok = usize;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        self.number(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        self.number(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        self.number(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        self.number(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        self.number(v as i64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        self.number(v as i64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        self.number(v as i64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        self.number(i64::try_from(v).unwrap_or(i64::MAX))
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.0
+            .fields
+            .iter()
+            .position(|f| f.name == v)
+            .ok_or_else(|| Error::FieldNameNotFound(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.unsupported()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + serde::Serialize,
+    {
+        self.unsupported()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(ser::Error::custom("field key must be a name or number"))
+    }
+}
@@ -0,0 +1,622 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_derive::{Deserialize, Serialize};
+use tobu_format::field::FieldNumber;
+
+use crate::{
+    info::{
+        Cardinality, DefaultValue, EnumInfo, EnumValue, FieldInfo, MessageInfo, MessageRef,
+        OneofInfo, Syntax, Type,
+    },
+    unknown::UnknownFields,
+    Message,
+};
+
+macro_rules! field {
+    ($name:literal, $number:literal, $cardinality:ident, $ty:ident) => {
+        field!($name, $number, $cardinality, $ty, None, None)
+    };
+    ($name:literal, $number:literal, $cardinality:ident, $ty:ident, $message_info:expr, $enum_info:expr) => {
+        FieldInfo {
+            name: $name,
+            number: FieldNumber::new($number),
+            cardinality: Cardinality::$cardinality,
+            ty: Type::$ty,
+            type_name: "",
+            json_name: "",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: $message_info,
+            enum_info: $enum_info,
+        }
+    };
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scalars {
+    pub int32: i32,
+    pub int64: i64,
+    pub uint32: u32,
+    pub uint64: u64,
+    pub sint32: i32,
+    pub sint64: i64,
+    pub fixed32: u32,
+    pub fixed64: u64,
+    pub sfixed32: i32,
+    pub sfixed64: i64,
+    pub float: f32,
+    pub double: f64,
+    pub bool: bool,
+    pub string: String,
+    pub bytes: Vec<u8>,
+}
+
+pub static SCALARS: MessageInfo = MessageInfo {
+    name: "Scalars",
+    fields: &[
+        field!("int32", 1, Optional, Int32),
+        field!("int64", 2, Optional, Int64),
+        field!("uint32", 3, Optional, Uint32),
+        field!("uint64", 4, Optional, Uint64),
+        field!("sint32", 5, Optional, SInt32),
+        field!("sint64", 6, Optional, SInt64),
+        field!("fixed32", 7, Optional, Fixed32),
+        field!("fixed64", 8, Optional, Fixed64),
+        field!("sfixed32", 9, Optional, SFixed32),
+        field!("sfixed64", 10, Optional, SFixed64),
+        field!("float", 11, Optional, Float),
+        field!("double", 12, Optional, Double),
+        field!("bool", 13, Optional, Bool),
+        field!("string", 14, Optional, String),
+        field!("bytes", 15, Optional, Bytes),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+// A Scalars with every field set away from its default.
+pub fn scalars() -> Scalars {
+    Scalars {
+        int32: -1,
+        int64: -2,
+        uint32: 3,
+        uint64: u64::MAX,
+        sint32: -5,
+        sint64: 6,
+        fixed32: 7,
+        fixed64: 8,
+        sfixed32: -9,
+        sfixed64: -10,
+        float: 1.1,
+        double: -2.5,
+        bool: true,
+        string: "tobu".to_string(),
+        bytes: b"\x00\xffbytes".to_vec(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Color {
+    Red = 0,
+    Green = 1,
+    Blue = -1,
+}
+
+pub static COLOR: EnumInfo = EnumInfo {
+    name: "Color",
+    value: &[
+        EnumValue {
+            name: "RED",
+            number: 0,
+        },
+        EnumValue {
+            name: "GREEN",
+            number: 1,
+        },
+        EnumValue {
+            name: "BLUE",
+            number: -1,
+        },
+    ],
+    syntax: Syntax::Proto2,
+};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Nested {
+    pub id: Option<i32>,
+    pub name: Option<String>,
+}
+
+pub static NESTED: MessageInfo = MessageInfo {
+    name: "Nested",
+    fields: &[
+        field!("id", 1, Optional, Int32),
+        field!("name", 2, Optional, String),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+impl Message for Nested {
+    fn info() -> &'static MessageInfo {
+        &NESTED
+    }
+}
+
+pub fn nested(id: i32, name: &str) -> Nested {
+    Nested {
+        id: Some(id),
+        name: Some(name.to_string()),
+    }
+}
+
+// An older version of Nested that predates its name field.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NestedV1 {
+    pub id: Option<i32>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: UnknownFields,
+}
+
+pub static NESTED_V1: MessageInfo = MessageInfo {
+    name: "Nested",
+    fields: &[field!("id", 1, Optional, Int32)],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Outer {
+    pub nested: Option<Nested>,
+    pub repeated_nested: Vec<Nested>,
+    pub numbers: Vec<i32>,
+    pub color: Option<Color>,
+    pub colors: Vec<Color>,
+}
+
+pub static OUTER: MessageInfo = MessageInfo {
+    name: "Outer",
+    fields: &[
+        field!(
+            "nested",
+            1,
+            Optional,
+            Message,
+            Some(MessageRef::Static(&NESTED)),
+            None
+        ),
+        field!(
+            "repeated_nested",
+            2,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&NESTED)),
+            None
+        ),
+        field!("numbers", 3, Repeated, Int32),
+        field!("color", 4, Optional, Enum, None, Some(&COLOR)),
+        field!("colors", 5, Repeated, Enum, None, Some(&COLOR)),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+impl Message for Outer {
+    fn info() -> &'static MessageInfo {
+        &OUTER
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Groups {
+    pub group: Option<Nested>,
+    pub repeated_group: Vec<Nested>,
+}
+
+pub static GROUPS: MessageInfo = MessageInfo {
+    name: "Groups",
+    fields: &[
+        field!(
+            "group",
+            1,
+            Optional,
+            Group,
+            Some(MessageRef::Static(&NESTED)),
+            None
+        ),
+        field!(
+            "repeated_group",
+            2,
+            Repeated,
+            Group,
+            Some(MessageRef::Static(&NESTED)),
+            None
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Maps {
+    pub counts: BTreeMap<String, i32>,
+    pub nested: HashMap<i32, Nested>,
+}
+
+pub static MAPS_COUNTS_ENTRY: MessageInfo = MessageInfo {
+    name: "Maps.CountsEntry",
+    fields: &[
+        field!("key", 1, Optional, String),
+        field!("value", 2, Optional, Int32),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: true,
+};
+
+pub static MAPS_NESTED_ENTRY: MessageInfo = MessageInfo {
+    name: "Maps.NestedEntry",
+    fields: &[
+        field!("key", 1, Optional, Int32),
+        field!(
+            "value",
+            2,
+            Optional,
+            Message,
+            Some(MessageRef::Static(&NESTED)),
+            None
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: true,
+};
+
+pub static MAPS: MessageInfo = MessageInfo {
+    name: "Maps",
+    fields: &[
+        field!(
+            "counts",
+            1,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&MAPS_COUNTS_ENTRY)),
+            None
+        ),
+        field!(
+            "nested",
+            2,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&MAPS_NESTED_ENTRY)),
+            None
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+// A map whose values are a closed enum.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColorMap {
+    pub colors: BTreeMap<i32, Color>,
+}
+
+pub static COLOR_MAP_COLORS_ENTRY: MessageInfo = MessageInfo {
+    name: "ColorMap.ColorsEntry",
+    fields: &[
+        field!("key", 1, Optional, Int32),
+        field!("value", 2, Optional, Enum, None, Some(&COLOR)),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: true,
+};
+
+pub static COLOR_MAP: MessageInfo = MessageInfo {
+    name: "ColorMap",
+    fields: &[field!(
+        "colors",
+        1,
+        Repeated,
+        Message,
+        Some(MessageRef::Static(&COLOR_MAP_COLORS_ENTRY)),
+        None
+    )],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Choice {
+    pub id: i32,
+    pub value: Option<ChoiceValue>,
+    pub tag: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum ChoiceValue {
+    Number(i32),
+    Text(String),
+    Nested(Nested),
+}
+
+pub static CHOICE: MessageInfo = MessageInfo {
+    name: "Choice",
+    fields: &[
+        field!("id", 1, Optional, Int32),
+        FieldInfo {
+            oneof_index: Some(0),
+            proto3_optional: false,
+            default_value: None,
+            ..field!("number", 2, Optional, Int32)
+        },
+        FieldInfo {
+            oneof_index: Some(0),
+            proto3_optional: false,
+            default_value: None,
+            ..field!("text", 3, Optional, String)
+        },
+        FieldInfo {
+            oneof_index: Some(0),
+            proto3_optional: false,
+            default_value: None,
+            ..field!(
+                "nested",
+                4,
+                Optional,
+                Message,
+                Some(MessageRef::Static(&NESTED)),
+                None
+            )
+        },
+        field!("tag", 5, Optional, String),
+    ],
+    oneofs: &[OneofInfo { name: "value" }],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+// An open enum, which holds values it does not know in its last variant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Shade {
+    #[default]
+    Light,
+    Dark,
+    Unrecognized(i32),
+}
+
+pub static SHADE: EnumInfo = EnumInfo {
+    name: "Shade",
+    value: &[
+        EnumValue {
+            name: "LIGHT",
+            number: 0,
+        },
+        EnumValue {
+            name: "DARK",
+            number: 1,
+        },
+    ],
+    syntax: Syntax::Proto3,
+};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Palette {
+    pub shade: Shade,
+    pub shades: Vec<Shade>,
+}
+
+pub static PALETTE: MessageInfo = MessageInfo {
+    name: "Palette",
+    fields: &[
+        field!("shade", 1, Optional, Enum, None, Some(&SHADE)),
+        field!("shades", 2, Repeated, Enum, None, Some(&SHADE)),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Packed {
+    pub int32s: Vec<i32>,
+    pub sint64s: Vec<i64>,
+    pub fixed32s: Vec<u32>,
+    pub doubles: Vec<f64>,
+    pub bools: Vec<bool>,
+    pub shades: Vec<Shade>,
+}
+
+pub static PACKED: MessageInfo = MessageInfo {
+    name: "Packed",
+    fields: &[
+        FieldInfo {
+            packed: true,
+            ..field!("int32s", 1, Repeated, Int32)
+        },
+        FieldInfo {
+            packed: true,
+            ..field!("sint64s", 2, Repeated, SInt64)
+        },
+        FieldInfo {
+            packed: true,
+            ..field!("fixed32s", 3, Repeated, Fixed32)
+        },
+        FieldInfo {
+            packed: true,
+            ..field!("doubles", 4, Repeated, Double)
+        },
+        FieldInfo {
+            packed: true,
+            ..field!("bools", 5, Repeated, Bool)
+        },
+        FieldInfo {
+            packed: true,
+            ..field!("shades", 6, Repeated, Enum, None, Some(&SHADE))
+        },
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+// A proto3 message whose optional fields keep track of whether they are set.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Optional {
+    pub plain: i32,
+    pub maybe: Option<i32>,
+    pub name: Option<String>,
+    pub shade: Option<Shade>,
+}
+
+pub static OPTIONAL: MessageInfo = MessageInfo {
+    name: "Optional",
+    fields: &[
+        field!("plain", 1, Optional, Int32),
+        FieldInfo {
+            proto3_optional: true,
+            default_value: None,
+            ..field!("maybe", 2, Optional, Int32)
+        },
+        FieldInfo {
+            proto3_optional: true,
+            default_value: None,
+            ..field!("name", 3, Optional, String)
+        },
+        FieldInfo {
+            proto3_optional: true,
+            default_value: None,
+            ..field!("shade", 4, Optional, Enum, None, Some(&SHADE))
+        },
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+// A proto2 message with declared defaults. Fields held without an Option take on their default
+// when absent.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Defaults {
+    pub count: Option<i32>,
+    pub limit: u32,
+    pub name: String,
+    pub color: Option<Color>,
+    pub ratio: f32,
+    pub data: Vec<u8>,
+}
+
+pub static DEFAULTS: MessageInfo = MessageInfo {
+    name: "Defaults",
+    fields: &[
+        FieldInfo {
+            default_value: Some(DefaultValue::Int(-5)),
+            ..field!("count", 1, Optional, Int32)
+        },
+        FieldInfo {
+            default_value: Some(DefaultValue::Uint(7)),
+            ..field!("limit", 2, Optional, Fixed32)
+        },
+        FieldInfo {
+            default_value: Some(DefaultValue::String("none")),
+            ..field!("name", 3, Optional, String)
+        },
+        FieldInfo {
+            default_value: Some(DefaultValue::Enum(1)),
+            ..field!("color", 4, Optional, Enum, None, Some(&COLOR))
+        },
+        FieldInfo {
+            default_value: Some(DefaultValue::Float(0.5)),
+            ..field!("ratio", 5, Optional, Float)
+        },
+        FieldInfo {
+            default_value: Some(DefaultValue::Bytes(b"\0\xff")),
+            ..field!("data", 6, Optional, Bytes)
+        },
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+// A proto2 message with a required field, which may be nested in itself.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Required {
+    pub id: Option<i32>,
+    pub child: Option<Box<Required>>,
+    pub children: Vec<Required>,
+}
+
+pub static REQUIRED: MessageInfo = MessageInfo {
+    name: "Required",
+    fields: &[
+        field!("id", 1, Required, Int32),
+        field!(
+            "child",
+            2,
+            Optional,
+            Message,
+            Some(MessageRef::Static(&REQUIRED)),
+            None
+        ),
+        field!(
+            "children",
+            3,
+            Repeated,
+            Message,
+            Some(MessageRef::Static(&REQUIRED)),
+            None
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
+
+impl Message for Required {
+    fn info() -> &'static MessageInfo {
+        &REQUIRED
+    }
+}
+
+// A message nested in itself, both as a message and as a group.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Tree {
+    pub child: Option<Box<Tree>>,
+    pub group: Option<Box<Tree>>,
+}
+
+pub static TREE: MessageInfo = MessageInfo {
+    name: "Tree",
+    fields: &[
+        field!(
+            "child",
+            1,
+            Optional,
+            Message,
+            Some(MessageRef::Static(&TREE)),
+            None
+        ),
+        field!(
+            "group",
+            2,
+            Optional,
+            Group,
+            Some(MessageRef::Static(&TREE)),
+            None
+        ),
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto2,
+    is_map: false,
+};
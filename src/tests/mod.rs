@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod messages;
+#[cfg(test)]
+mod test_de;
+#[cfg(test)]
+mod test_enum;
+#[cfg(all(test, feature = "json"))]
+mod test_json;
+#[cfg(test)]
+mod test_lib;
+#[cfg(test)]
+mod test_message;
+#[cfg(test)]
+mod test_oneof;
+#[cfg(test)]
+mod test_packed;
+#[cfg(test)]
+mod test_pool;
+#[cfg(test)]
+mod test_required;
+#[cfg(test)]
+mod test_ser;
+#[cfg(test)]
+mod test_text;
+#[cfg(test)]
+mod test_unknown;
+#[cfg(test)]
+mod test_value;
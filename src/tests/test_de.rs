@@ -0,0 +1,435 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use serde::Deserialize;
+use tobu_format::{
+    error::DecodeError,
+    field::FieldNumber,
+    wire::{
+        encode_zig_zag, put_bytes, put_fixed32, put_fixed64, put_tag, put_varint, WireType,
+        RECURSION_LIMIT,
+    },
+};
+
+use crate::{
+    de::{Deserializer, Options},
+    error::Error,
+    from_bytes, from_bytes_with_options,
+    info::MessageInfo,
+    to_bytes,
+};
+
+use super::messages::{
+    Color, Defaults, Groups, Maps, Nested, NestedV1, Outer, Scalars, Tree, DEFAULTS, GROUPS, MAPS,
+    NESTED_V1, OUTER, SCALARS, TREE,
+};
+
+fn deserialize<'de, T: Deserialize<'de>>(
+    buf: BytesMut,
+    info: &'static MessageInfo,
+) -> Result<T, Error> {
+    T::deserialize(&mut Deserializer::new(buf.freeze(), info))
+}
+
+fn nested_message(id: i32, name: &str) -> BytesMut {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, id as u64);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, name.as_bytes());
+    buf
+}
+
+#[test]
+fn scalars() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, -1i64 as u64);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Varint);
+    put_varint(&mut buf, i64::MIN as u64);
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Varint);
+    put_varint(&mut buf, u32::MAX as u64);
+    put_tag(&mut buf, FieldNumber::new(4), WireType::Varint);
+    put_varint(&mut buf, u64::MAX);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut buf, encode_zig_zag(-5));
+    put_tag(&mut buf, FieldNumber::new(6), WireType::Varint);
+    put_varint(&mut buf, encode_zig_zag(i64::MIN));
+    put_tag(&mut buf, FieldNumber::new(7), WireType::Fixed32);
+    put_fixed32(&mut buf, 7);
+    put_tag(&mut buf, FieldNumber::new(8), WireType::Fixed64);
+    put_fixed64(&mut buf, 8);
+    put_tag(&mut buf, FieldNumber::new(9), WireType::Fixed32);
+    put_fixed32(&mut buf, -9i32 as u32);
+    put_tag(&mut buf, FieldNumber::new(10), WireType::Fixed64);
+    put_fixed64(&mut buf, -10i64 as u64);
+    put_tag(&mut buf, FieldNumber::new(11), WireType::Fixed32);
+    put_fixed32(&mut buf, 1.5f32.to_bits());
+    put_tag(&mut buf, FieldNumber::new(12), WireType::Fixed64);
+    put_fixed64(&mut buf, (-2.5f64).to_bits());
+    put_tag(&mut buf, FieldNumber::new(13), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(14), WireType::Bytes);
+    put_bytes(&mut buf, b"hello");
+    put_tag(&mut buf, FieldNumber::new(15), WireType::Bytes);
+    put_bytes(&mut buf, b"\x00\xff");
+
+    assert_eq!(
+        deserialize::<Scalars>(buf, &SCALARS).unwrap(),
+        Scalars {
+            int32: -1,
+            int64: i64::MIN,
+            uint32: u32::MAX,
+            uint64: u64::MAX,
+            sint32: -5,
+            sint64: i64::MIN,
+            fixed32: 7,
+            fixed64: 8,
+            sfixed32: -9,
+            sfixed64: -10,
+            float: 1.5,
+            double: -2.5,
+            bool: true,
+            string: "hello".to_string(),
+            bytes: vec![0x00, 0xff],
+        }
+    );
+}
+
+#[test]
+fn scalars_absent() {
+    assert_eq!(
+        deserialize::<Scalars>(BytesMut::new(), &SCALARS).unwrap(),
+        Scalars::default()
+    );
+}
+
+#[test]
+fn defaults_absent() {
+    assert_eq!(
+        deserialize::<Defaults>(BytesMut::new(), &DEFAULTS).unwrap(),
+        Defaults {
+            // fields held in an Option still tell that they are not set
+            count: None,
+            limit: 7,
+            name: "none".to_string(),
+            color: None,
+            ratio: 0.5,
+            data: b"\0\xff".to_vec(),
+        }
+    );
+}
+
+#[test]
+fn defaults_present() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 0);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Fixed32);
+    put_fixed32(&mut buf, 0);
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Bytes);
+    put_bytes(&mut buf, b"");
+    let defaults = deserialize::<Defaults>(buf, &DEFAULTS).unwrap();
+    assert_eq!(defaults.count, Some(0));
+    assert_eq!(defaults.limit, 0);
+    assert_eq!(defaults.name, "");
+}
+
+#[test]
+fn scalars_out_of_order() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(14), WireType::Bytes);
+    put_bytes(&mut buf, b"hello");
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+
+    let scalars = deserialize::<Scalars>(buf, &SCALARS).unwrap();
+    assert_eq!(scalars.int32, 1);
+    assert_eq!(scalars.string, "hello");
+}
+
+#[test]
+fn scalars_last_one_wins() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 2);
+
+    assert_eq!(deserialize::<Scalars>(buf, &SCALARS).unwrap().int32, 2);
+}
+
+#[test]
+fn scalars_wire_type_mismatch() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Fixed32);
+    put_fixed32(&mut buf, 1);
+    let bytes = buf.freeze();
+
+    // the field is left to the unknown fields
+    let old: NestedV1 = from_bytes(bytes.clone(), &NESTED_V1).unwrap();
+    assert_eq!(old.id, None);
+    assert_eq!(to_bytes(&old, &NESTED_V1).unwrap(), bytes);
+
+    let options = Options {
+        discard_unknown_fields: true,
+        ..Default::default()
+    };
+    let old: NestedV1 = from_bytes_with_options(bytes, &NESTED_V1, options).unwrap();
+    assert_eq!(old.id, None);
+    assert!(old.unknown_fields.is_empty());
+}
+
+#[test]
+fn unknown_fields_skipped() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(100), WireType::Bytes);
+    put_bytes(&mut buf, b"unknown");
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+
+    assert_eq!(deserialize::<Scalars>(buf, &SCALARS).unwrap().int32, 1);
+}
+
+#[test]
+fn nested() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &nested_message(1, "one"));
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, &nested_message(2, "two"));
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Varint);
+    put_varint(&mut buf, 3);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, &nested_message(4, "four"));
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Varint);
+    put_varint(&mut buf, 5);
+
+    assert_eq!(
+        deserialize::<Outer>(buf, &OUTER).unwrap(),
+        Outer {
+            nested: Some(Nested {
+                id: Some(1),
+                name: Some("one".to_string()),
+            }),
+            repeated_nested: vec![
+                Nested {
+                    id: Some(2),
+                    name: Some("two".to_string()),
+                },
+                Nested {
+                    id: Some(4),
+                    name: Some("four".to_string()),
+                },
+            ],
+            numbers: vec![3, 5],
+            ..Outer::default()
+        }
+    );
+}
+
+#[test]
+fn nested_merge() {
+    let mut first = BytesMut::new();
+    put_tag(&mut first, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut first, 1);
+
+    let mut second = BytesMut::new();
+    put_tag(&mut second, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut second, b"two");
+
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &first);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &second);
+
+    assert_eq!(
+        deserialize::<Outer>(buf, &OUTER).unwrap().nested,
+        Some(Nested {
+            id: Some(1),
+            name: Some("two".to_string()),
+        })
+    );
+}
+
+#[test]
+fn groups() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::StartGroup);
+    buf.put(nested_message(1, "one"));
+    put_tag(&mut buf, FieldNumber::new(1), WireType::EndGroup);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::StartGroup);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::EndGroup);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::StartGroup);
+    buf.put(nested_message(2, "two"));
+    put_tag(&mut buf, FieldNumber::new(2), WireType::EndGroup);
+
+    assert_eq!(
+        deserialize::<Groups>(buf, &GROUPS).unwrap(),
+        Groups {
+            group: Some(Nested {
+                id: Some(1),
+                name: Some("one".to_string()),
+            }),
+            repeated_group: vec![
+                Nested::default(),
+                Nested {
+                    id: Some(2),
+                    name: Some("two".to_string()),
+                },
+            ],
+        }
+    );
+}
+
+// A tree nested depth times through the child message, or through the group.
+fn nested_tree(depth: usize, group: bool) -> BytesMut {
+    let mut buf = BytesMut::new();
+    for _ in 0..depth {
+        let mut outer = BytesMut::new();
+        if group {
+            put_tag(&mut outer, FieldNumber::new(2), WireType::StartGroup);
+            outer.put(buf);
+            put_tag(&mut outer, FieldNumber::new(2), WireType::EndGroup);
+        } else {
+            put_tag(&mut outer, FieldNumber::new(1), WireType::Bytes);
+            put_bytes(&mut outer, &buf);
+        }
+        buf = outer;
+    }
+    buf
+}
+
+fn assert_recursion_limit_exceeded(result: Result<Tree, Error>) {
+    assert!(matches!(
+        result,
+        Err(Error::DecodeError(DecodeError::RecursionLimitExceeded))
+    ));
+}
+
+#[test]
+fn nested_to_recursion_limit() {
+    for group in [false, true] {
+        let mut tree = deserialize::<Tree>(nested_tree(RECURSION_LIMIT, group), &TREE).unwrap();
+        let mut depth = 0;
+        while let Some(inner) = tree.child.or(tree.group) {
+            tree = *inner;
+            depth += 1;
+        }
+        assert_eq!(depth, RECURSION_LIMIT);
+
+        assert_recursion_limit_exceeded(deserialize(
+            nested_tree(RECURSION_LIMIT + 1, group),
+            &TREE,
+        ));
+    }
+}
+
+#[test]
+fn nested_too_deeply() {
+    // start group tags for the group field and nothing else
+    let buf = BytesMut::from(&vec![0x13; 1_000_000][..]);
+    assert_recursion_limit_exceeded(deserialize(buf, &TREE));
+}
+
+#[test]
+fn groups_merge() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::StartGroup);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::EndGroup);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::StartGroup);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, b"two");
+    put_tag(&mut buf, FieldNumber::new(1), WireType::EndGroup);
+
+    assert_eq!(
+        deserialize::<Groups>(buf, &GROUPS).unwrap().group,
+        Some(Nested {
+            id: Some(1),
+            name: Some("two".to_string()),
+        })
+    );
+}
+
+#[test]
+fn groups_wire_type_mismatch() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &nested_message(1, "one"));
+
+    // the field is left to the unknown fields
+    assert_eq!(deserialize::<Groups>(buf, &GROUPS).unwrap().group, None);
+}
+
+#[test]
+fn enums() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(4), WireType::Varint);
+    put_varint(&mut buf, -1i64 as u64);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut buf, 0);
+
+    let outer = deserialize::<Outer>(buf, &OUTER).unwrap();
+    assert_eq!(outer.color, Some(Color::Blue));
+    assert_eq!(outer.colors, vec![Color::Green, Color::Red]);
+}
+
+#[test]
+fn enums_unknown_value() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(4), WireType::Varint);
+    put_varint(&mut buf, 7);
+
+    // Color is closed, so the value is left to the unknown fields
+    let outer = deserialize::<Outer>(buf, &OUTER).unwrap();
+    assert_eq!(outer.color, None);
+}
+
+#[test]
+fn truncated() {
+    let buf = Bytes::from_static(b"\x08");
+    assert!(Scalars::deserialize(&mut Deserializer::new(buf, &SCALARS)).is_err());
+}
+
+fn map_entry(key: &str, value: Option<i32>) -> BytesMut {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, key.as_bytes());
+    if let Some(value) = value {
+        put_tag(&mut buf, FieldNumber::new(2), WireType::Varint);
+        put_varint(&mut buf, value as u64);
+    }
+    buf
+}
+
+#[test]
+fn map_duplicate_keys_last_one_wins() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &map_entry("a", Some(1)));
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &map_entry("b", Some(2)));
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &map_entry("a", Some(3)));
+
+    let maps = deserialize::<Maps>(buf, &MAPS).unwrap();
+    assert_eq!(maps.counts.len(), 2);
+    assert_eq!(maps.counts["a"], 3);
+    assert_eq!(maps.counts["b"], 2);
+}
+
+#[test]
+fn map_missing_value() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &map_entry("a", None));
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, b"");
+
+    let maps = deserialize::<Maps>(buf, &MAPS).unwrap();
+    assert_eq!(maps.counts["a"], 0);
+    assert_eq!(maps.nested[&0], Nested::default());
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use bytes::{Bytes, BytesMut};
+#[cfg(feature = "json")]
+use serde_json::json;
+use tobu_format::{
+    field::FieldNumber,
+    wire::{put_bytes, put_tag, put_varint, WireType},
+};
+
+#[cfg(feature = "json")]
+use crate::json;
+use crate::{
+    from_bytes, serialized_size, to_bytes,
+    value::{DynamicMessage, Value},
+};
+
+use super::messages::{Color, ColorMap, Outer, Palette, Shade, COLOR_MAP, OUTER, PALETTE};
+
+#[test]
+fn written_by_number() {
+    let outer = Outer {
+        color: Some(Color::Blue),
+        colors: vec![Color::Green, Color::Red],
+        ..Outer::default()
+    };
+    let bytes = to_bytes(&outer, &OUTER).unwrap();
+    assert_eq!(
+        bytes,
+        &b"\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01\x28\x01\x28\x00"[..]
+    );
+    assert_eq!(serialized_size(&outer, &OUTER).unwrap(), bytes.len());
+    assert_eq!(from_bytes::<Outer>(bytes, &OUTER).unwrap(), outer);
+}
+
+#[test]
+fn proto3_default_skipped() {
+    let palette = Palette::default();
+    assert!(to_bytes(&palette, &PALETTE).unwrap().is_empty());
+}
+
+#[test]
+fn open_enum_keeps_unknown_value() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 5);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Varint);
+    put_varint(&mut buf, -3i64 as u64);
+    let buf = buf.freeze();
+
+    let palette: Palette = from_bytes(buf.clone(), &PALETTE).unwrap();
+    assert_eq!(palette.shade, Shade::Unrecognized(5));
+    assert_eq!(palette.shades, vec![Shade::Dark, Shade::Unrecognized(-3)]);
+    assert_eq!(to_bytes(&palette, &PALETTE).unwrap(), buf);
+}
+
+#[test]
+fn closed_enum_leaves_unknown_value_to_unknown_fields() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(4), WireType::Varint);
+    put_varint(&mut buf, 7);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut buf, 8);
+    let buf = buf.freeze();
+
+    let message = DynamicMessage::decode(buf.clone(), &OUTER).unwrap();
+    assert!(!message.has("color"));
+    assert_eq!(
+        message.get("colors"),
+        Some(&Value::List(vec![Value::Enum(1)]))
+    );
+    assert_eq!(message.unknown_fields().len(), 2);
+
+    // the unknown values are written after the known fields
+    let mut expected = BytesMut::new();
+    put_tag(&mut expected, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut expected, 1);
+    put_tag(&mut expected, FieldNumber::new(4), WireType::Varint);
+    put_varint(&mut expected, 7);
+    put_tag(&mut expected, FieldNumber::new(5), WireType::Varint);
+    put_varint(&mut expected, 8);
+    assert_eq!(Bytes::from(message.encode().unwrap()), expected.freeze());
+}
+
+#[test]
+fn closed_enum_map_value_leaves_entry_to_unknown_fields() {
+    let mut buf = BytesMut::new();
+    for (key, value) in [(1, 1), (2, 7)] {
+        let mut entry = BytesMut::new();
+        put_tag(&mut entry, FieldNumber::new(1), WireType::Varint);
+        put_varint(&mut entry, key);
+        put_tag(&mut entry, FieldNumber::new(2), WireType::Varint);
+        put_varint(&mut entry, value);
+        put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+        put_bytes(&mut buf, &entry);
+    }
+    let buf = buf.freeze();
+
+    let color_map: ColorMap = from_bytes(buf.clone(), &COLOR_MAP).unwrap();
+    assert_eq!(color_map.colors, BTreeMap::from([(1, Color::Green)]));
+
+    let message = DynamicMessage::decode(buf.clone(), &COLOR_MAP).unwrap();
+    assert_eq!(message.unknown_fields().len(), 1);
+    assert_eq!(Bytes::from(message.encode().unwrap()), buf);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_mapping() {
+    let palette = Palette {
+        shade: Shade::Dark,
+        shades: vec![Shade::Light, Shade::Unrecognized(5)],
+    };
+    let value = json::to_value(&palette, &PALETTE, &Default::default()).unwrap();
+    assert_eq!(value, json!({"shade": "DARK", "shades": ["LIGHT", 5]}));
+    assert_eq!(
+        json::from_value::<Palette>(value, &PALETTE, &Default::default()).unwrap(),
+        palette
+    );
+
+    assert!(json::from_str::<Outer>(r#"{"color": 7}"#, &OUTER).is_err());
+}
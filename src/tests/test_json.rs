@@ -0,0 +1,240 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::json;
+
+use crate::{
+    error::Error,
+    json::{self, Options},
+    value::DynamicMessage,
+};
+
+use super::messages::{
+    scalars, Color, Maps, Nested, Optional, Outer, Scalars, Shade, MAPS, OPTIONAL, OUTER, SCALARS,
+};
+
+#[test]
+fn scalars_mapping() {
+    let value = json::to_value(&scalars(), &SCALARS, &Options::default()).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "int32": -1,
+            "int64": "-2",
+            "uint32": 3,
+            "uint64": "18446744073709551615",
+            "sint32": -5,
+            "sint64": "6",
+            "fixed32": 7,
+            "fixed64": "8",
+            "sfixed32": -9,
+            "sfixed64": "-10",
+            "float": 1.1,
+            "double": -2.5,
+            "bool": true,
+            "string": "tobu",
+            "bytes": "AP9ieXRlcw==",
+        })
+    );
+    assert_eq!(
+        json::from_value::<Scalars>(value, &SCALARS, &Options::default()).unwrap(),
+        scalars()
+    );
+}
+
+#[test]
+fn scalars_leave_out_defaults() {
+    assert_eq!(
+        json::to_string(&Scalars::default(), &SCALARS).unwrap(),
+        "{}"
+    );
+    assert_eq!(
+        json::from_str::<Scalars>("{}", &SCALARS).unwrap(),
+        Scalars::default()
+    );
+}
+
+#[test]
+fn scalars_emit_defaults() {
+    let options = Options {
+        emit_defaults: true,
+        ..Default::default()
+    };
+    let value = json::to_value(&Scalars::default(), &SCALARS, &options).unwrap();
+    assert_eq!(value["int32"], json!(0));
+    assert_eq!(value["int64"], json!("0"));
+    assert_eq!(value["bool"], json!(false));
+    assert_eq!(value["string"], json!(""));
+    assert_eq!(value["bytes"], json!(""));
+    assert_eq!(value.as_object().unwrap().len(), 15);
+}
+
+#[test]
+fn proto3_optional_written_when_set() {
+    let optional = Optional {
+        maybe: Some(0),
+        shade: Some(Shade::Light),
+        ..Optional::default()
+    };
+    let value = json::to_value(&optional, &OPTIONAL, &Default::default()).unwrap();
+    assert_eq!(value, json!({"maybe": 0, "shade": "LIGHT"}));
+    assert_eq!(
+        json::from_value::<Optional>(value, &OPTIONAL, &Default::default()).unwrap(),
+        optional
+    );
+}
+
+#[test]
+fn scalars_lenient_input() {
+    let scalars: Scalars = json::from_str(
+        r#"{
+            "int32": "-1",
+            "int64": -2,
+            "uint32": 3e0,
+            "uint64": "18446744073709551615",
+            "float": "NaN",
+            "double": "-Infinity",
+            "bytes": "AP9ieXRlcw",
+            "string": null
+        }"#,
+        &SCALARS,
+    )
+    .unwrap();
+    assert_eq!(scalars.int32, -1);
+    assert_eq!(scalars.int64, -2);
+    assert_eq!(scalars.uint32, 3);
+    assert_eq!(scalars.uint64, u64::MAX);
+    assert!(scalars.float.is_nan());
+    assert_eq!(scalars.double, f64::NEG_INFINITY);
+    assert_eq!(scalars.bytes, b"\x00\xffbytes");
+    assert_eq!(scalars.string, "");
+
+    assert!(json::from_str::<Scalars>(r#"{"int32": 1.5}"#, &SCALARS).is_err());
+    assert!(json::from_str::<Scalars>(r#"{"int32": "4294967296"}"#, &SCALARS).is_err());
+}
+
+#[test]
+fn special_floats() {
+    let scalars = Scalars {
+        float: f32::INFINITY,
+        double: f64::NAN,
+        ..Default::default()
+    };
+    let value = json::to_value(&scalars, &SCALARS, &Options::default()).unwrap();
+    assert_eq!(value, json!({"float": "Infinity", "double": "NaN"}));
+}
+
+#[test]
+fn nested_and_enums() {
+    let outer = Outer {
+        nested: Some(Nested {
+            id: Some(0),
+            name: None,
+        }),
+        repeated_nested: vec![Nested::default()],
+        numbers: vec![1, 2],
+        color: Some(Color::Blue),
+        colors: vec![Color::Red, Color::Green],
+    };
+    let value = json::to_value(&outer, &OUTER, &Options::default()).unwrap();
+    // proto2 fields that are set are written even when they hold the default
+    assert_eq!(
+        value,
+        json!({
+            "nested": {"id": 0},
+            "repeatedNested": [{}],
+            "numbers": [1, 2],
+            "color": "BLUE",
+            "colors": ["RED", "GREEN"],
+        })
+    );
+    assert_eq!(
+        json::from_value::<Outer>(value, &OUTER, &Options::default()).unwrap(),
+        outer
+    );
+
+    // enums may also be given by number
+    let outer: Outer = json::from_str(r#"{"color": -1, "colors": [1, "RED"]}"#, &OUTER).unwrap();
+    assert_eq!(outer.color, Some(Color::Blue));
+    assert_eq!(outer.colors, vec![Color::Green, Color::Red]);
+}
+
+#[test]
+fn field_names() {
+    let outer = Outer {
+        repeated_nested: vec![Nested::default()],
+        ..Default::default()
+    };
+    let options = Options {
+        proto_names: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        json::to_string_with_options(&outer, &OUTER, &options).unwrap(),
+        r#"{"repeated_nested":[{}]}"#
+    );
+
+    // both names are accepted on input
+    for input in [r#"{"repeated_nested":[{}]}"#, r#"{"repeatedNested":[{}]}"#] {
+        assert_eq!(json::from_str::<Outer>(input, &OUTER).unwrap(), outer);
+    }
+}
+
+#[test]
+fn unknown_fields() {
+    let input = r#"{"id": 1, "missing": true}"#;
+    assert!(matches!(
+        json::from_str::<Nested>(input, &super::messages::NESTED),
+        Err(Error::FieldNameNotFound(name)) if name == "missing"
+    ));
+
+    let options = Options {
+        ignore_unknown_fields: true,
+        ..Default::default()
+    };
+    let nested: Nested =
+        json::from_str_with_options(input, &super::messages::NESTED, &options).unwrap();
+    assert_eq!(nested.id, Some(1));
+}
+
+#[test]
+fn maps() {
+    let maps = Maps {
+        counts: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 0)]),
+        nested: HashMap::from([(
+            -3,
+            Nested {
+                id: Some(1),
+                name: None,
+            },
+        )]),
+    };
+    let value = json::to_value(&maps, &MAPS, &Options::default()).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "counts": {"a": 1, "b": 0},
+            "nested": {"-3": {"id": 1}},
+        })
+    );
+    assert_eq!(
+        json::from_value::<Maps>(value, &MAPS, &Options::default()).unwrap(),
+        maps
+    );
+}
+
+#[test]
+fn dynamic_message() {
+    let value = json!({"repeatedNested": [{"id": 1, "name": "one"}], "color": "GREEN"});
+    let options = Options::default();
+    let message = DynamicMessage::deserialize(
+        &OUTER,
+        json::Deserializer::new(value.clone(), &OUTER, &options),
+    )
+    .unwrap();
+    assert_eq!(message.fields().count(), 2);
+    assert_eq!(json::to_value(&message, &OUTER, &options).unwrap(), value);
+
+    let outer: Outer = crate::from_slice(&message.encode().unwrap(), &OUTER).unwrap();
+    assert_eq!(outer.color, Some(Color::Green));
+    assert_eq!(outer.repeated_nested[0].name.as_deref(), Some("one"));
+}
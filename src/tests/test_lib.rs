@@ -0,0 +1,66 @@
+use bytes::Bytes;
+
+use crate::{
+    de::Options, from_bytes, from_reader, from_reader_with_options, from_slice,
+    from_slice_with_options,
+};
+
+use super::messages::{nested, Nested, Required, NESTED, REQUIRED};
+
+const NESTED_BYTES: &[u8] = b"\x08\x01\x12\x03one";
+
+#[test]
+fn slice() {
+    assert_eq!(
+        from_slice::<Nested>(NESTED_BYTES, &NESTED).unwrap(),
+        nested(1, "one")
+    );
+}
+
+#[test]
+fn slice_with_options() {
+    let options = Options {
+        partial: true,
+        ..Default::default()
+    };
+    assert!(from_slice::<Required>(&[], &REQUIRED).is_err());
+    assert_eq!(
+        from_slice_with_options::<Required>(&[], &REQUIRED, options).unwrap(),
+        Required::default()
+    );
+}
+
+#[test]
+fn bytes() {
+    let bytes = Bytes::from_static(NESTED_BYTES);
+    assert_eq!(
+        from_bytes::<Nested>(bytes, &NESTED).unwrap(),
+        nested(1, "one")
+    );
+}
+
+#[test]
+fn reader() {
+    assert_eq!(
+        from_reader::<Nested>(NESTED_BYTES, &NESTED).unwrap(),
+        nested(1, "one")
+    );
+}
+
+#[test]
+fn reader_with_options() {
+    let options = Options {
+        partial: true,
+        ..Default::default()
+    };
+    assert!(from_reader::<Required>(&[][..], &REQUIRED).is_err());
+    assert_eq!(
+        from_reader_with_options::<Required>(&[][..], &REQUIRED, options).unwrap(),
+        Required::default()
+    );
+}
+
+#[test]
+fn reader_truncated() {
+    assert!(from_reader::<Nested>(&NESTED_BYTES[..4], &NESTED).is_err());
+}
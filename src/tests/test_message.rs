@@ -0,0 +1,84 @@
+use bytes::Bytes;
+
+use crate::{from_bytes, to_vec, Message};
+
+use super::messages::{Color, Nested, Outer, OUTER};
+
+fn outer() -> Outer {
+    Outer {
+        nested: Some(Nested {
+            id: Some(1),
+            name: None,
+        }),
+        repeated_nested: vec![Nested::default()],
+        numbers: vec![1, 2],
+        color: Some(Color::Red),
+        colors: vec![],
+    }
+}
+
+#[test]
+fn encode_uses_own_info() {
+    let outer = outer();
+    let bytes = outer.encode().unwrap();
+    assert_eq!(bytes, to_vec(&outer, &OUTER).unwrap());
+    assert_eq!(outer.encoded_len().unwrap(), bytes.len());
+}
+
+#[test]
+fn decode_uses_own_info() {
+    let outer = outer();
+    let bytes = Bytes::from(outer.encode().unwrap());
+    assert_eq!(Outer::decode(bytes.clone()).unwrap(), outer);
+    assert_eq!(from_bytes::<Outer>(bytes, &OUTER).unwrap(), outer);
+}
+
+#[test]
+fn merge() {
+    let mut outer = outer();
+    let other = Outer {
+        nested: Some(Nested {
+            id: None,
+            name: Some("name".to_string()),
+        }),
+        repeated_nested: vec![Nested {
+            id: Some(2),
+            name: None,
+        }],
+        numbers: vec![3],
+        color: Some(Color::Blue),
+        colors: vec![Color::Green],
+    };
+    outer.merge(Bytes::from(other.encode().unwrap())).unwrap();
+
+    assert_eq!(
+        outer,
+        Outer {
+            // embedded messages are merged
+            nested: Some(Nested {
+                id: Some(1),
+                name: Some("name".to_string()),
+            }),
+            // repeated fields are appended to
+            repeated_nested: vec![
+                Nested::default(),
+                Nested {
+                    id: Some(2),
+                    name: None,
+                }
+            ],
+            numbers: vec![1, 2, 3],
+            // singular fields are replaced
+            color: Some(Color::Blue),
+            colors: vec![Color::Green],
+        }
+    );
+}
+
+#[test]
+fn merge_invalid() {
+    let mut outer = outer();
+    assert!(outer.merge(Bytes::from_static(b"\x0a\x05")).is_err());
+    // the message is left as it was
+    assert_eq!(outer, self::outer());
+}
@@ -0,0 +1,105 @@
+use bytes::{Bytes, BytesMut};
+#[cfg(feature = "json")]
+use serde_json::json;
+use tobu_format::{
+    field::FieldNumber,
+    wire::{put_bytes, put_tag, put_varint, WireType},
+};
+
+#[cfg(feature = "json")]
+use crate::json;
+use crate::{
+    from_bytes, serialized_size, to_bytes,
+    value::{DynamicMessage, Value},
+};
+
+use super::messages::{Choice, ChoiceValue, Nested, CHOICE};
+
+#[test]
+fn roundtrip() {
+    for value in [
+        None,
+        Some(ChoiceValue::Number(7)),
+        Some(ChoiceValue::Text("seven".to_string())),
+        Some(ChoiceValue::Nested(Nested {
+            id: Some(7),
+            name: None,
+        })),
+    ] {
+        let choice = Choice {
+            id: 1,
+            value,
+            tag: "tag".to_string(),
+        };
+        let bytes = to_bytes(&choice, &CHOICE).unwrap();
+        assert_eq!(serialized_size(&choice, &CHOICE).unwrap(), bytes.len());
+        assert_eq!(from_bytes::<Choice>(bytes, &CHOICE).unwrap(), choice);
+    }
+}
+
+#[test]
+fn default_member_is_written() {
+    let choice = Choice {
+        value: Some(ChoiceValue::Number(0)),
+        ..Default::default()
+    };
+    let bytes = to_bytes(&choice, &CHOICE).unwrap();
+    assert_eq!(bytes, &b"\x10\x00"[..]);
+    assert_eq!(from_bytes::<Choice>(bytes, &CHOICE).unwrap(), choice);
+}
+
+#[test]
+fn last_one_wins() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Bytes);
+    put_bytes(&mut buf, b"first");
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Varint);
+    put_varint(&mut buf, 2);
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Bytes);
+    put_bytes(&mut buf, b"last");
+    let buf = buf.freeze();
+
+    let choice: Choice = from_bytes(buf.clone(), &CHOICE).unwrap();
+    assert_eq!(choice.value, Some(ChoiceValue::Text("last".to_string())));
+
+    let message = DynamicMessage::decode(buf, &CHOICE).unwrap();
+    assert!(!message.has("number"));
+    assert_eq!(message.get("text"), Some(&Value::from("last")));
+}
+
+#[test]
+fn dynamic_message() {
+    let mut message = DynamicMessage::new(&CHOICE);
+    message.set("number", 3).unwrap();
+    message.set("text", "three").unwrap();
+    assert!(!message.has("number"));
+
+    let bytes = Bytes::from(message.encode().unwrap());
+    let choice: Choice = from_bytes(bytes.clone(), &CHOICE).unwrap();
+    assert_eq!(choice.value, Some(ChoiceValue::Text("three".to_string())));
+    assert_eq!(DynamicMessage::decode(bytes, &CHOICE).unwrap(), message);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_mapping() {
+    let choice = Choice {
+        value: Some(ChoiceValue::Number(0)),
+        ..Default::default()
+    };
+    let value = json::to_value(&choice, &CHOICE, &Default::default()).unwrap();
+    assert_eq!(value, json!({"number": 0}));
+    assert_eq!(
+        json::from_value::<Choice>(value, &CHOICE, &Default::default()).unwrap(),
+        choice
+    );
+
+    let message = DynamicMessage::deserialize(
+        &CHOICE,
+        json::Deserializer::new(json!({"text": "t"}), &CHOICE, &Default::default()),
+    )
+    .unwrap();
+    assert_eq!(message.get("text"), Some(&Value::from("t")));
+
+    assert!(json::from_str::<Choice>(r#"{"number": 1, "text": "t"}"#, &CHOICE).is_err());
+}
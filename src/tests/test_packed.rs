@@ -0,0 +1,114 @@
+use bytes::{Bytes, BytesMut};
+use tobu_format::{
+    field::FieldNumber,
+    wire::{put_bytes, put_fixed32, put_tag, put_varint, WireType},
+};
+
+use crate::{
+    from_bytes, serialized_size, to_bytes,
+    value::{DynamicMessage, Value},
+};
+
+use super::messages::{Color, Outer, Packed, Shade, OUTER, PACKED};
+
+fn packed() -> Packed {
+    Packed {
+        int32s: vec![1, 300, -1],
+        sint64s: vec![-2, i64::MAX],
+        fixed32s: vec![0, u32::MAX],
+        doubles: vec![1.5, f64::NEG_INFINITY],
+        bools: vec![true, false],
+        shades: vec![Shade::Dark, Shade::Unrecognized(9)],
+    }
+}
+
+#[test]
+fn length_in_bytes() {
+    let packed = Packed {
+        int32s: vec![1, 300, -1],
+        ..Packed::default()
+    };
+    let bytes = to_bytes(&packed, &PACKED).unwrap();
+    assert_eq!(
+        bytes,
+        &b"\x0a\x0d\x01\xac\x02\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01"[..]
+    );
+    assert_eq!(serialized_size(&packed, &PACKED).unwrap(), bytes.len());
+}
+
+#[test]
+fn roundtrip() {
+    let packed = packed();
+    let bytes = to_bytes(&packed, &PACKED).unwrap();
+    assert_eq!(serialized_size(&packed, &PACKED).unwrap(), bytes.len());
+    assert_eq!(
+        from_bytes::<Packed>(bytes.clone(), &PACKED).unwrap(),
+        packed
+    );
+
+    let message = DynamicMessage::decode(bytes.clone(), &PACKED).unwrap();
+    assert_eq!(message.encode().unwrap(), bytes);
+}
+
+#[test]
+fn empty_left_out() {
+    assert!(to_bytes(&Packed::default(), &PACKED).unwrap().is_empty());
+}
+
+#[test]
+fn unpacked_accepted() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 2);
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Fixed32);
+    put_fixed32(&mut buf, 3);
+    // both forms may be mixed within a single message
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &[3, 4]);
+
+    let packed: Packed = from_bytes(buf.freeze(), &PACKED).unwrap();
+    assert_eq!(packed.int32s, vec![1, 2, 3, 4]);
+    assert_eq!(packed.fixed32s, vec![3]);
+}
+
+#[test]
+fn packed_accepted() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Bytes);
+    put_bytes(&mut buf, &[1, 2]);
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Varint);
+    put_varint(&mut buf, 3);
+
+    let outer: Outer = from_bytes(buf.freeze(), &OUTER).unwrap();
+    assert_eq!(outer.numbers, vec![1, 2, 3]);
+}
+
+#[test]
+fn closed_enum_elements_left_to_unknown_fields() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(5), WireType::Bytes);
+    put_bytes(&mut buf, &[1, 8, 0]);
+    let buf = buf.freeze();
+
+    let outer: Outer = from_bytes(buf.clone(), &OUTER).unwrap();
+    assert_eq!(outer.colors, vec![Color::Green, Color::Red]);
+
+    let message = DynamicMessage::decode(buf, &OUTER).unwrap();
+    assert_eq!(
+        message.get("colors"),
+        Some(&Value::List(vec![Value::Enum(1), Value::Enum(0)]))
+    );
+    let mut unknown = BytesMut::new();
+    message.unknown_fields().encode(&mut unknown);
+    assert_eq!(unknown.freeze(), Bytes::from_static(b"\x28\x08"));
+}
+
+#[test]
+fn truncated_element() {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Bytes);
+    put_bytes(&mut buf, &[1, 2, 3, 4, 5]);
+    assert!(from_bytes::<Packed>(buf.freeze(), &PACKED).is_err());
+}
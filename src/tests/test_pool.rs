@@ -0,0 +1,381 @@
+use bytes::Bytes;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::{
+    descriptor::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FieldOptions, FileDescriptorProto, FileDescriptorSet, MessageOptions, OneofDescriptorProto,
+        FILE_DESCRIPTOR_SET,
+    },
+    error::Error,
+    from_slice,
+    info::{DefaultValue, Syntax, Type},
+    pool::DescriptorPool,
+    to_vec,
+    value::{DynamicMessage, Value},
+};
+
+fn field(name: &str, number: i32, label: i32, ty: i32, type_name: &str) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        label: Some(label),
+        r#type: Some(ty),
+        type_name: (!type_name.is_empty()).then(|| type_name.to_string()),
+        ..Default::default()
+    }
+}
+
+fn message(name: &str, field: Vec<FieldDescriptorProto>) -> DescriptorProto {
+    DescriptorProto {
+        name: Some(name.to_string()),
+        field,
+        ..Default::default()
+    }
+}
+
+fn file(name: &str, package: &str, message_type: Vec<DescriptorProto>) -> FileDescriptorProto {
+    FileDescriptorProto {
+        name: Some(name.to_string()),
+        package: Some(package.to_string()),
+        message_type,
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    }
+}
+
+// package test;
+// message Node {
+//   enum Kind { LEAF = 0; BRANCH = 1; }
+//   message Label { string text = 1; }
+//   int32 id = 1;
+//   Kind kind = 2;
+//   repeated Node children = 3;
+//   map<string, int32> counts = 4;
+//   Label label = 5;
+// }
+fn node_file() -> FileDescriptorProto {
+    let mut node = message(
+        "Node",
+        vec![
+            field("id", 1, 1, 5, ""),
+            field("kind", 2, 1, 14, "Kind"),
+            field("children", 3, 3, 11, ".test.Node"),
+            field("counts", 4, 3, 11, "CountsEntry"),
+            field("label", 5, 1, 11, "Label"),
+        ],
+    );
+    node.enum_type.push(EnumDescriptorProto {
+        name: Some("Kind".to_string()),
+        value: vec![
+            EnumValueDescriptorProto {
+                name: Some("LEAF".to_string()),
+                number: Some(0),
+            },
+            EnumValueDescriptorProto {
+                name: Some("BRANCH".to_string()),
+                number: Some(1),
+            },
+        ],
+    });
+    node.nested_type
+        .push(message("Label", vec![field("text", 1, 1, 9, "")]));
+    node.nested_type.push(DescriptorProto {
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+        }),
+        ..message(
+            "CountsEntry",
+            vec![field("key", 1, 1, 9, ""), field("value", 2, 1, 5, "")],
+        )
+    });
+    file("node.proto", "test", vec![node])
+}
+
+fn encode(files: Vec<FileDescriptorProto>) -> Bytes {
+    Bytes::from(to_vec(&FileDescriptorSet { file: files }, &FILE_DESCRIPTOR_SET).unwrap())
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Label {
+    text: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Node {
+    id: i32,
+    kind: i32,
+    children: Vec<Node>,
+    counts: std::collections::BTreeMap<String, i32>,
+    label: Option<Label>,
+}
+
+#[test]
+fn descriptors() {
+    let pool = DescriptorPool::decode(encode(vec![node_file()])).unwrap();
+    assert_eq!(pool.messages().count(), 3);
+    assert_eq!(pool.enums().count(), 1);
+
+    let node = pool.get_message(".test.Node").unwrap();
+    assert_eq!(node.name, "test.Node");
+    assert_eq!(node.syntax, Syntax::Proto3);
+    assert!(!node.is_map);
+    assert_eq!(node.fields[1].enum_info.unwrap().name, "test.Node.Kind");
+    assert!(std::ptr::eq(node.fields[2].message_info().unwrap(), node));
+    assert!(node.fields[3].message_info().unwrap().is_map);
+    assert_eq!(
+        node.fields[4].message_info().unwrap().name,
+        "test.Node.Label"
+    );
+    assert!(matches!(node.fields[4].ty, Type::Message));
+
+    let kind = pool.get_enum("test.Node.Kind").unwrap();
+    assert_eq!(kind.value[1].name, "BRANCH");
+    assert_eq!(kind.value[1].number, 1);
+}
+
+#[test]
+fn json_names() {
+    let mut with_json_name = field("with_json_name", 2, 1, 5, "");
+    with_json_name.json_name = Some("custom".to_string());
+    let files = vec![file(
+        "names.proto",
+        "",
+        vec![message(
+            "Names",
+            vec![field("snake_case_name", 1, 1, 5, ""), with_json_name],
+        )],
+    )];
+    let pool = DescriptorPool::decode(encode(files)).unwrap();
+    let names = pool.get_message("Names").unwrap();
+    assert_eq!(names.fields[0].json_name, "snakeCaseName");
+    assert_eq!(names.fields[1].json_name, "custom");
+}
+
+#[test]
+fn packed_by_default_in_proto3() {
+    let mut unpacked = field("unpacked", 3, 3, 5, "");
+    unpacked.options = Some(FieldOptions {
+        packed: Some(false),
+    });
+    let lists = message(
+        "Lists",
+        vec![
+            field("numbers", 1, 3, 5, ""),
+            field("names", 2, 3, 9, ""),
+            unpacked,
+        ],
+    );
+    let mut packed = field("packed", 2, 3, 5, "");
+    packed.options = Some(FieldOptions { packed: Some(true) });
+    let lists2 = FileDescriptorProto {
+        syntax: None,
+        ..file(
+            "lists2.proto",
+            "v2",
+            vec![message(
+                "Lists",
+                vec![field("numbers", 1, 3, 5, ""), packed],
+            )],
+        )
+    };
+    let pool = DescriptorPool::decode(encode(vec![file("lists.proto", "v3", vec![lists]), lists2]))
+        .unwrap();
+
+    let lists = pool.get_message("v3.Lists").unwrap();
+    assert!(lists.fields[0].packed);
+    assert!(!lists.fields[1].packed);
+    assert!(!lists.fields[2].packed);
+
+    let lists = pool.get_message("v2.Lists").unwrap();
+    assert!(!lists.fields[0].packed);
+    assert!(lists.fields[1].packed);
+}
+
+#[test]
+fn synthetic_oneofs_left_out() {
+    let mut choice = field("choice", 1, 1, 5, "");
+    choice.oneof_index = Some(0);
+    let mut maybe = field("maybe", 2, 1, 5, "");
+    maybe.oneof_index = Some(1);
+    maybe.proto3_optional = Some(true);
+    let mut optional = message("Optional", vec![choice, maybe, field("plain", 3, 1, 5, "")]);
+    optional.oneof_decl = vec![
+        OneofDescriptorProto {
+            name: Some("kind".to_string()),
+        },
+        OneofDescriptorProto {
+            name: Some("_maybe".to_string()),
+        },
+    ];
+    let pool = DescriptorPool::decode(encode(vec![file("optional.proto", "test", vec![optional])]))
+        .unwrap();
+
+    let optional = pool.get_message("test.Optional").unwrap();
+    assert_eq!(optional.oneofs.len(), 1);
+    assert_eq!(optional.oneofs[0].name, "kind");
+    assert_eq!(optional.fields[0].oneof_index, Some(0));
+    assert!(!optional.fields[0].proto3_optional);
+    assert_eq!(optional.fields[1].oneof_index, None);
+    assert!(optional.fields[1].proto3_optional);
+
+    // an optional field is written when set, even to its default
+    let mut message = DynamicMessage::new(optional);
+    message.set("maybe", 0i32).unwrap();
+    message.set("plain", 0i32).unwrap();
+    assert_eq!(message.encode().unwrap(), b"\x10\x00");
+}
+
+#[test]
+fn defaults() {
+    let with_default = |mut field: FieldDescriptorProto, default: &str| {
+        field.default_value = Some(default.to_string());
+        field
+    };
+    let mut defaults = message(
+        "Defaults",
+        vec![
+            with_default(field("count", 1, 1, 17, ""), "-5"),
+            with_default(field("ratio", 2, 1, 2, ""), "0.1"),
+            with_default(field("limit", 3, 1, 1, ""), "-inf"),
+            with_default(field("data", 4, 1, 12, ""), "a\\000\\xff\\n"),
+            with_default(field("kind", 5, 1, 14, "Kind"), "BRANCH"),
+            field("plain", 6, 1, 5, ""),
+        ],
+    );
+    defaults.enum_type.push(EnumDescriptorProto {
+        name: Some("Kind".to_string()),
+        value: vec![
+            EnumValueDescriptorProto {
+                name: Some("LEAF".to_string()),
+                number: Some(1),
+            },
+            EnumValueDescriptorProto {
+                name: Some("BRANCH".to_string()),
+                number: Some(2),
+            },
+        ],
+    });
+    let file = FileDescriptorProto {
+        syntax: None,
+        ..file("defaults.proto", "test", vec![defaults])
+    };
+    let pool = DescriptorPool::decode(encode(vec![file.clone()])).unwrap();
+
+    let defaults = pool.get_message("test.Defaults").unwrap();
+    let default_values = defaults
+        .fields
+        .iter()
+        .map(|f| f.default_value)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        default_values,
+        vec![
+            Some(DefaultValue::Int(-5)),
+            Some(DefaultValue::Float(0.1f32.into())),
+            Some(DefaultValue::Float(f64::NEG_INFINITY)),
+            Some(DefaultValue::Bytes(b"a\0\xff\n")),
+            Some(DefaultValue::Enum(2)),
+            None,
+        ]
+    );
+
+    // absent fields read as their defaults
+    let message = DynamicMessage::decode(Bytes::new(), defaults).unwrap();
+    assert_eq!(message.get_or_default("count").unwrap(), Value::from(-5));
+    assert_eq!(message.get_or_default("plain").unwrap(), Value::from(0));
+
+    let mut invalid = file;
+    invalid.message_type[0].field[0].default_value = Some("five".to_string());
+    invalid.package = Some("invalid".to_string());
+    assert!(DescriptorPool::decode(encode(vec![invalid])).is_err());
+}
+
+#[test]
+fn dynamic_and_typed_agree() {
+    let pool = DescriptorPool::decode(encode(vec![node_file()])).unwrap();
+    let info = pool.get_message("test.Node").unwrap();
+
+    let typed = Node {
+        id: 1,
+        kind: 1,
+        children: vec![Node {
+            id: 2,
+            kind: 0,
+            children: vec![],
+            counts: [("a".to_string(), 3)].into(),
+            label: None,
+        }],
+        counts: Default::default(),
+        label: Some(Label {
+            text: "root".to_string(),
+        }),
+    };
+    let bytes = to_vec(&typed, info).unwrap();
+    assert_eq!(from_slice::<Node>(&bytes, info).unwrap(), typed);
+
+    let dynamic = DynamicMessage::decode(Bytes::from(bytes.clone()), info).unwrap();
+    assert_eq!(dynamic.get("id"), Some(&Value::from(1)));
+    let Some(Value::List(children)) = dynamic.get("children") else {
+        panic!("expected children");
+    };
+    let Value::Message(child) = &children[0] else {
+        panic!("expected a message");
+    };
+    assert_eq!(child.message_info().name, "test.Node");
+    assert_eq!(child.get("id"), Some(&Value::from(2)));
+    assert_eq!(
+        from_slice::<Node>(&dynamic.encode().unwrap(), info).unwrap(),
+        typed
+    );
+}
+
+#[test]
+fn cross_file_references() {
+    // package other; message Holder { test.Node node = 1; }
+    let holder = file(
+        "holder.proto",
+        "other",
+        vec![message(
+            "Holder",
+            vec![field("node", 1, 1, 11, "test.Node")],
+        )],
+    );
+
+    // files of one set may come in any order
+    let pool = DescriptorPool::decode(encode(vec![holder.clone(), node_file()])).unwrap();
+    let node = pool.get_message("test.Node").unwrap();
+    let field = pool.get_message("other.Holder").unwrap().fields[0];
+    assert!(std::ptr::eq(field.message_info().unwrap(), node));
+
+    // or in sets added one after another
+    let mut pool = DescriptorPool::decode(encode(vec![node_file()])).unwrap();
+    pool.add_file_descriptor_set(encode(vec![holder])).unwrap();
+    let node = pool.get_message("test.Node").unwrap();
+    let field = pool.get_message("other.Holder").unwrap().fields[0];
+    assert!(std::ptr::eq(field.message_info().unwrap(), node));
+}
+
+#[test]
+fn type_not_found() {
+    let files = vec![file(
+        "missing.proto",
+        "test",
+        vec![message("Holder", vec![field("node", 1, 1, 11, "Missing")])],
+    )];
+    let mut pool = DescriptorPool::new();
+    assert!(matches!(
+        pool.add_file_descriptor_set(encode(files)),
+        Err(Error::TypeNotFound(name)) if name == "Missing"
+    ));
+    assert!(pool.get_message("test.Holder").is_none());
+}
+
+#[test]
+fn duplicate_type() {
+    let mut pool = DescriptorPool::decode(encode(vec![node_file()])).unwrap();
+    assert!(matches!(
+        pool.add_file_descriptor_set(encode(vec![node_file()])),
+        Err(Error::DuplicateType(_))
+    ));
+}
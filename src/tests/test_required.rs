@@ -0,0 +1,99 @@
+use bytes::Bytes;
+
+use crate::{
+    de, error::Error, from_bytes_with_options, from_slice, ser, serialized_size, to_vec,
+    to_vec_with_options, value::DynamicMessage, Message,
+};
+
+use super::messages::{Required, REQUIRED};
+
+fn required(id: i32) -> Required {
+    Required {
+        id: Some(id),
+        ..Default::default()
+    }
+}
+
+fn assert_not_set<T: std::fmt::Debug>(result: Result<T, Error>, path: &str) {
+    match result {
+        Err(Error::RequiredFieldNotSet(field)) => assert_eq!(field, path),
+        result => panic!("expected {path} to be reported as not set, got {result:?}"),
+    }
+}
+
+#[test]
+fn set() {
+    let message = Required {
+        id: Some(1),
+        child: Some(Box::new(required(2))),
+        children: vec![required(3)],
+    };
+    let vec = to_vec(&message, &REQUIRED).unwrap();
+    assert_eq!(from_slice::<Required>(&vec, &REQUIRED).unwrap(), message);
+}
+
+#[test]
+fn decode_not_set() {
+    assert_not_set(from_slice::<Required>(&[], &REQUIRED), "id");
+    // child { }
+    assert_not_set(
+        from_slice::<Required>(b"\x08\x01\x12\x00", &REQUIRED),
+        "child.id",
+    );
+    // child { child { } }
+    assert_not_set(
+        from_slice::<Required>(b"\x08\x01\x12\x04\x08\x02\x12\x00", &REQUIRED),
+        "child.child.id",
+    );
+    // children { }
+    assert_not_set(
+        from_slice::<Required>(b"\x08\x01\x1a\x00", &REQUIRED),
+        "children.id",
+    );
+}
+
+#[test]
+fn encode_not_set() {
+    assert_not_set(to_vec(&Required::default(), &REQUIRED), "id");
+    let message = Required {
+        id: Some(1),
+        child: Some(Box::new(required(2))),
+        children: vec![required(3), Required::default()],
+    };
+    assert_not_set(to_vec(&message, &REQUIRED), "children.id");
+    assert_not_set(DynamicMessage::new(&REQUIRED).encode(), "id");
+}
+
+#[test]
+fn partial() {
+    let message = Required {
+        child: Some(Box::default()),
+        ..Default::default()
+    };
+    let options = ser::Options { partial: true };
+    let vec = to_vec_with_options(&message, &REQUIRED, options).unwrap();
+    assert_eq!(vec, b"\x12\x00");
+    assert_eq!(serialized_size(&message, &REQUIRED).unwrap(), vec.len());
+
+    let options = de::Options {
+        partial: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        from_bytes_with_options::<Required>(Bytes::from(vec), &REQUIRED, options).unwrap(),
+        message
+    );
+}
+
+#[test]
+fn merge_completes() {
+    let mut message = Required {
+        child: Some(Box::new(required(2))),
+        ..Default::default()
+    };
+    message.merge(Bytes::from_static(b"\x08\x01")).unwrap();
+    assert_eq!(message.id, Some(1));
+
+    let mut message = Required::default();
+    assert_not_set(message.merge(Bytes::new()), "id");
+}
@@ -0,0 +1,182 @@
+use bytes::BytesMut;
+use tobu_format::{
+    field::FieldNumber,
+    wire::{put_bytes, put_tag, put_varint, WireType},
+};
+
+use crate::{from_slice, serialized_size, to_vec};
+
+use super::messages::{
+    nested, scalars, Groups, Maps, Nested, Optional, Outer, Scalars, Shade, GROUPS, MAPS, NESTED,
+    OPTIONAL, OUTER, SCALARS,
+};
+
+#[test]
+fn scalars_round_trip() {
+    let vec = to_vec(&scalars(), &SCALARS).unwrap();
+    assert_eq!(vec.len(), serialized_size(&scalars(), &SCALARS).unwrap());
+    assert_eq!(from_slice::<Scalars>(&vec, &SCALARS).unwrap(), scalars());
+}
+
+#[test]
+fn scalars_proto3_defaults_skipped() {
+    assert!(to_vec(&Scalars::default(), &SCALARS).unwrap().is_empty());
+    assert_eq!(serialized_size(&Scalars::default(), &SCALARS).unwrap(), 0);
+}
+
+#[test]
+fn scalars_negative_int32_sign_extended() {
+    let scalars = Scalars {
+        int32: -1,
+        ..Scalars::default()
+    };
+    // tag + 10 byte varint
+    assert_eq!(to_vec(&scalars, &SCALARS).unwrap().len(), 11);
+}
+
+#[test]
+fn none_skipped() {
+    let nested = Nested {
+        id: None,
+        name: Some("name".to_string()),
+    };
+
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, b"name");
+
+    assert_eq!(to_vec(&nested, &NESTED).unwrap(), buf.to_vec());
+}
+
+#[test]
+fn proto3_optional_written_when_set() {
+    let optional = Optional {
+        plain: 0,
+        maybe: Some(0),
+        name: Some(String::new()),
+        shade: Some(Shade::Light),
+    };
+    let bytes = to_vec(&optional, &OPTIONAL).unwrap();
+    assert_eq!(bytes, b"\x10\x00\x1a\x00\x20\x00");
+    assert_eq!(serialized_size(&optional, &OPTIONAL).unwrap(), bytes.len());
+    assert_eq!(from_slice::<Optional>(&bytes, &OPTIONAL).unwrap(), optional);
+
+    assert!(to_vec(&Optional::default(), &OPTIONAL).unwrap().is_empty());
+    assert_eq!(
+        from_slice::<Optional>(&[], &OPTIONAL).unwrap(),
+        Optional::default()
+    );
+}
+
+#[test]
+fn nested_length_prefixed() {
+    let outer = Outer {
+        nested: Some(nested(1, "one")),
+        ..Outer::default()
+    };
+
+    let mut inner = BytesMut::new();
+    put_tag(&mut inner, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut inner, 1);
+    put_tag(&mut inner, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut inner, b"one");
+
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Bytes);
+    put_bytes(&mut buf, &inner);
+
+    assert_eq!(to_vec(&outer, &OUTER).unwrap(), buf.to_vec());
+    assert_eq!(serialized_size(&outer, &OUTER).unwrap(), buf.len());
+}
+
+#[test]
+fn nested_empty() {
+    let outer = Outer {
+        nested: Some(Nested::default()),
+        ..Outer::default()
+    };
+
+    assert_eq!(to_vec(&outer, &OUTER).unwrap(), b"\x0a\x00");
+}
+
+#[test]
+fn nested_round_trip() {
+    let outer = Outer {
+        nested: Some(nested(1, "one")),
+        repeated_nested: vec![nested(2, "two"), Nested::default(), nested(3, "three")],
+        numbers: vec![0, 1, -1],
+        ..Outer::default()
+    };
+
+    let vec = to_vec(&outer, &OUTER).unwrap();
+    assert_eq!(vec.len(), serialized_size(&outer, &OUTER).unwrap());
+    assert_eq!(from_slice::<Outer>(&vec, &OUTER).unwrap(), outer);
+}
+
+#[test]
+fn group_delimited() {
+    let groups = Groups {
+        group: Some(nested(1, "one")),
+        ..Groups::default()
+    };
+
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(1), WireType::StartGroup);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, b"one");
+    put_tag(&mut buf, FieldNumber::new(1), WireType::EndGroup);
+
+    assert_eq!(to_vec(&groups, &GROUPS).unwrap(), buf.to_vec());
+    assert_eq!(serialized_size(&groups, &GROUPS).unwrap(), buf.len());
+}
+
+#[test]
+fn group_empty() {
+    let groups = Groups {
+        group: Some(Nested::default()),
+        ..Groups::default()
+    };
+
+    assert_eq!(to_vec(&groups, &GROUPS).unwrap(), b"\x0b\x0c");
+}
+
+#[test]
+fn group_round_trip() {
+    let groups = Groups {
+        group: Some(nested(1, "one")),
+        repeated_group: vec![nested(2, "two"), Nested::default(), nested(3, "three")],
+    };
+
+    let vec = to_vec(&groups, &GROUPS).unwrap();
+    assert_eq!(vec.len(), serialized_size(&groups, &GROUPS).unwrap());
+    assert_eq!(from_slice::<Groups>(&vec, &GROUPS).unwrap(), groups);
+}
+
+#[test]
+fn map_entries() {
+    let mut maps = Maps::default();
+    maps.counts.insert("a".to_string(), 0);
+    maps.counts.insert("b".to_string(), 2);
+
+    // each entry is its own length delimited message with the key and value always written
+    assert_eq!(
+        to_vec(&maps, &MAPS).unwrap(),
+        b"\x0a\x05\x0a\x01a\x10\x00\x0a\x05\x0a\x01b\x10\x02"
+    );
+}
+
+#[test]
+fn map_round_trip() {
+    let mut maps = Maps::default();
+    maps.counts.insert("a".to_string(), 1);
+    maps.counts.insert(String::new(), 0);
+    maps.nested.insert(1, nested(1, "one"));
+    maps.nested.insert(0, Nested::default());
+    maps.nested.insert(-2, nested(2, "two"));
+
+    let vec = to_vec(&maps, &MAPS).unwrap();
+    assert_eq!(vec.len(), serialized_size(&maps, &MAPS).unwrap());
+    assert_eq!(from_slice::<Maps>(&vec, &MAPS).unwrap(), maps);
+}
@@ -0,0 +1,197 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    text,
+    value::{DynamicMessage, Value},
+};
+
+use super::messages::{
+    Groups, Maps, Nested, Required, Scalars, GROUPS, MAPS, NESTED, OUTER, REQUIRED, SCALARS,
+};
+
+#[test]
+fn print_scalars() {
+    let scalars = Scalars {
+        int32: -1,
+        uint64: u64::MAX,
+        float: 1.1,
+        double: f64::NEG_INFINITY,
+        bool: true,
+        string: "a \"quoted\"\nline".to_string(),
+        bytes: b"\x00\xffok".to_vec(),
+        ..Default::default()
+    };
+    let printed = text::to_string(&scalars, &SCALARS).unwrap();
+    assert_eq!(
+        printed,
+        r#"int32: -1
+uint64: 18446744073709551615
+float: 1.1
+double: -inf
+bool: true
+string: "a \"quoted\"\nline"
+bytes: "\000\377ok"
+"#
+    );
+    assert_eq!(
+        text::from_str::<Scalars>(&printed, &SCALARS).unwrap(),
+        scalars
+    );
+}
+
+#[test]
+fn parse_scalars() {
+    let scalars: Scalars = text::from_str(
+        r#"
+        # integers in any base
+        int32: -0x10
+        uint32: 017;
+        int64: -9223372036854775808,
+        float: 2.5f double: -1e-3
+        bool: t
+        string: 'single' " and double" "\x41é"
+        bytes: "\001\x02"
+        "#,
+        &SCALARS,
+    )
+    .unwrap();
+    assert_eq!(scalars.int32, -16);
+    assert_eq!(scalars.uint32, 15);
+    assert_eq!(scalars.int64, i64::MIN);
+    assert_eq!(scalars.float, 2.5);
+    assert_eq!(scalars.double, -1e-3);
+    assert!(scalars.bool);
+    assert_eq!(scalars.string, "single and doubleAé");
+    assert_eq!(scalars.bytes, b"\x01\x02");
+}
+
+#[test]
+fn parse_errors() {
+    let err = text::from_str::<Scalars>("int32: 1\nmissing: 2", &SCALARS).unwrap_err();
+    assert_eq!(err.to_string(), "2:1: field missing not found");
+
+    let err = text::from_str::<Scalars>("int32: 4294967296", &SCALARS).unwrap_err();
+    assert!(err.to_string().contains("out of range"));
+
+    assert!(text::from_str::<Scalars>("int32 1", &SCALARS).is_err());
+    assert!(text::from_str::<Scalars>("string: \"open", &SCALARS).is_err());
+    assert!(text::from_str::<Nested>("id: 1 }", &NESTED).is_err());
+}
+
+#[test]
+fn print_required_not_set() {
+    let required = Required {
+        child: Some(Box::new(Required::default())),
+        ..Default::default()
+    };
+    assert_eq!(
+        text::to_string(&required, &REQUIRED).unwrap(),
+        "child {\n}\n"
+    );
+}
+
+#[test]
+fn nested_and_repeated() {
+    let mut outer = DynamicMessage::new(&OUTER);
+    let mut nested = DynamicMessage::new(&NESTED);
+    nested.set("id", 1).unwrap();
+    outer.set("nested", nested.clone()).unwrap();
+    outer
+        .set(
+            "repeated_nested",
+            vec![
+                Value::Message(nested),
+                Value::Message(DynamicMessage::new(&NESTED)),
+            ],
+        )
+        .unwrap();
+    outer
+        .set("numbers", vec![Value::from(1), Value::from(2)])
+        .unwrap();
+    outer.set("color", Value::Enum(-1)).unwrap();
+    outer
+        .set("colors", vec![Value::Enum(1), Value::Enum(7)])
+        .unwrap();
+
+    let printed = text::print(&outer);
+    assert_eq!(
+        printed,
+        "nested {
+  id: 1
+}
+repeated_nested {
+  id: 1
+}
+repeated_nested {
+}
+numbers: 1
+numbers: 2
+color: BLUE
+colors: GREEN
+colors: 7
+"
+    );
+    assert_eq!(text::parse(&printed, &OUTER).unwrap(), outer);
+
+    // the same message written with lists and angle brackets
+    let parsed = text::parse(
+        "nested: <id: 1> repeated_nested [{id: 1}, {}] numbers: [1, 2] color: -1 colors: [GREEN, 7]",
+        &OUTER,
+    )
+    .unwrap();
+    assert_eq!(parsed, outer);
+}
+
+#[test]
+fn maps() {
+    let maps = Maps {
+        counts: BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 0)]),
+        nested: HashMap::from([(
+            -3,
+            Nested {
+                id: Some(1),
+                name: None,
+            },
+        )]),
+    };
+    let printed = text::to_string(&maps, &MAPS).unwrap();
+    assert_eq!(
+        printed,
+        r#"counts {
+  key: "a"
+  value: 1
+}
+counts {
+  key: "b"
+  value: 0
+}
+nested {
+  key: -3
+  value {
+    id: 1
+  }
+}
+"#
+    );
+    assert_eq!(text::from_str::<Maps>(&printed, &MAPS).unwrap(), maps);
+
+    // absent keys and values take on their defaults
+    let maps: Maps = text::from_str("counts {} counts { key: \"c\" }", &MAPS).unwrap();
+    assert_eq!(
+        maps.counts,
+        BTreeMap::from([(String::new(), 0), ("c".to_string(), 0)])
+    );
+}
+
+#[test]
+fn groups() {
+    let groups = Groups {
+        group: Some(Nested {
+            id: Some(1),
+            name: Some("one".to_string()),
+        }),
+        repeated_group: vec![Nested::default()],
+    };
+    let printed = text::to_string(&groups, &GROUPS).unwrap();
+    assert_eq!(text::from_str::<Groups>(&printed, &GROUPS).unwrap(), groups);
+}
@@ -0,0 +1,105 @@
+use bytes::{Bytes, BytesMut};
+use tobu_format::{
+    field::FieldNumber,
+    wire::{put_bytes, put_fixed32, put_tag, put_varint, FieldValue, WireField, WireType},
+};
+
+#[cfg(feature = "json")]
+use crate::json;
+use crate::{
+    de::Options, from_bytes, from_bytes_with_options, serialized_size, to_bytes,
+    value::DynamicMessage,
+};
+
+use super::messages::{Nested, NestedV1, NESTED, NESTED_V1};
+
+// Nested as written by a newer schema: a known field surrounded by fields NestedV1 has never heard
+// of, including a group.
+fn newer_message() -> Bytes {
+    let mut buf = BytesMut::new();
+    put_tag(&mut buf, FieldNumber::new(2), WireType::Bytes);
+    put_bytes(&mut buf, b"one");
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Varint);
+    put_varint(&mut buf, 1);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::StartGroup);
+    put_tag(&mut buf, FieldNumber::new(1), WireType::Fixed32);
+    put_fixed32(&mut buf, 7);
+    put_tag(&mut buf, FieldNumber::new(5), WireType::EndGroup);
+    put_tag(&mut buf, FieldNumber::new(3), WireType::Varint);
+    put_varint(&mut buf, 300);
+    buf.freeze()
+}
+
+#[test]
+fn kept_in_order() {
+    let old: NestedV1 = from_bytes(newer_message(), &NESTED_V1).unwrap();
+    assert_eq!(old.id, Some(1));
+    let unknown: Vec<_> = old.unknown_fields.iter().cloned().collect();
+    assert_eq!(
+        unknown,
+        vec![
+            WireField {
+                num: FieldNumber::new(2),
+                val: FieldValue::Bytes(Bytes::from_static(b"one")),
+            },
+            WireField {
+                num: FieldNumber::new(5),
+                val: FieldValue::Group(Bytes::from_static(b"\x0d\x07\x00\x00\x00")),
+            },
+            WireField {
+                num: FieldNumber::new(3),
+                val: FieldValue::Varint(300),
+            },
+        ]
+    );
+}
+
+#[test]
+fn survive_reserialization() {
+    let old: NestedV1 = from_bytes(newer_message(), &NESTED_V1).unwrap();
+    let bytes = to_bytes(&old, &NESTED_V1).unwrap();
+    assert_eq!(serialized_size(&old, &NESTED_V1).unwrap(), bytes.len());
+
+    // known fields come first, then the unknown ones as they were read
+    let new: Nested = from_bytes(bytes, &NESTED).unwrap();
+    assert_eq!(
+        new,
+        Nested {
+            id: Some(1),
+            name: Some("one".to_string()),
+        }
+    );
+}
+
+#[test]
+fn discarded() {
+    let options = Options {
+        discard_unknown_fields: true,
+        ..Default::default()
+    };
+    let old: NestedV1 = from_bytes_with_options(newer_message(), &NESTED_V1, options).unwrap();
+    assert_eq!(old.id, Some(1));
+    assert!(old.unknown_fields.is_empty());
+    assert_eq!(to_bytes(&old, &NESTED_V1).unwrap(), &b"\x08\x01"[..]);
+}
+
+#[test]
+fn dynamic_message() {
+    let message = DynamicMessage::decode(newer_message(), &NESTED_V1).unwrap();
+    assert_eq!(message.fields().count(), 1);
+    assert_eq!(message.unknown_fields().len(), 3);
+
+    let new = DynamicMessage::decode(Bytes::from(message.encode().unwrap()), &NESTED).unwrap();
+    assert_eq!(new.get("name"), Some(&"one".into()));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn left_out_of_json() {
+    let old: NestedV1 = from_bytes(newer_message(), &NESTED_V1).unwrap();
+    let json = json::to_string(&old, &NESTED_V1).unwrap();
+    assert_eq!(json, r#"{"id":1}"#);
+
+    let old: NestedV1 = json::from_str(&json, &NESTED_V1).unwrap();
+    assert!(old.unknown_fields.is_empty());
+}
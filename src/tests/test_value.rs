@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bytes::Bytes;
+use tobu_format::field::FieldNumber;
+
+use crate::{
+    error::Error,
+    from_slice, to_vec,
+    value::{DynamicMessage, Map, MapKey, Value},
+};
+
+use super::messages::{
+    nested, Color, Groups, Maps, Nested, Outer, Scalars, DEFAULTS, GROUPS, MAPS, NESTED, OUTER,
+    SCALARS,
+};
+
+fn dynamic_nested(id: i32, name: &str) -> DynamicMessage {
+    let bytes = to_vec(&nested(id, name), &NESTED).unwrap();
+    DynamicMessage::decode(bytes.into(), &NESTED).unwrap()
+}
+
+#[test]
+fn set_get_clear() {
+    let mut message = DynamicMessage::new(&NESTED);
+    assert!(!message.has("id"));
+    assert_eq!(message.get("id"), None);
+
+    assert_eq!(message.set("id", 1).unwrap(), None);
+    assert!(message.has("id"));
+    assert!(message.has(FieldNumber::new(1)));
+    assert_eq!(message.get(FieldNumber::new(1)), Some(&Value::from(1)));
+
+    assert_eq!(
+        message.set(FieldNumber::new(1), 2).unwrap(),
+        Some(Value::from(1))
+    );
+    assert_eq!(message.clear("id"), Some(Value::from(2)));
+    assert!(!message.has("id"));
+    assert_eq!(message.clear(FieldNumber::new(1)), None);
+}
+
+#[test]
+fn get_or_default() {
+    let mut message = DynamicMessage::new(&DEFAULTS);
+    assert_eq!(message.get_or_default("count").unwrap(), Value::from(-5));
+    assert_eq!(message.get_or_default("limit").unwrap(), Value::from(7u32));
+    assert_eq!(message.get_or_default("name").unwrap(), Value::from("none"));
+    assert_eq!(message.get_or_default("color").unwrap(), Value::Enum(1));
+    assert_eq!(
+        message.get_or_default("ratio").unwrap(),
+        Value::from(0.5f32)
+    );
+    assert_eq!(
+        message.get_or_default("data").unwrap(),
+        Value::Bytes(b"\0\xff".to_vec())
+    );
+    assert!(!message.has("count"));
+
+    message.set("count", 0).unwrap();
+    assert_eq!(message.get_or_default("count").unwrap(), Value::from(0));
+
+    // without a declared default, a field takes on the zero value of its type
+    let message = DynamicMessage::new(&OUTER);
+    assert_eq!(message.get_or_default("color").unwrap(), Value::Enum(0));
+    assert_eq!(
+        message.get_or_default("numbers").unwrap(),
+        Value::List(Vec::new())
+    );
+    assert_eq!(
+        message.get_or_default("nested").unwrap(),
+        Value::Message(DynamicMessage::new(&NESTED))
+    );
+    assert!(message.get_or_default("missing").is_err());
+}
+
+#[test]
+fn set_unknown_field() {
+    let mut message = DynamicMessage::new(&NESTED);
+    assert!(matches!(
+        message.set("missing", 1),
+        Err(Error::FieldNameNotFound(name)) if name == "missing"
+    ));
+    assert!(matches!(
+        message.set(FieldNumber::new(3), 1),
+        Err(Error::FieldNotFound(number)) if number == FieldNumber::new(3)
+    ));
+    assert!(!message.has("missing"));
+}
+
+#[test]
+fn set_mismatched_value() {
+    let mut message = DynamicMessage::new(&OUTER);
+    assert!(matches!(
+        message.set("numbers", 1),
+        Err(Error::ValueMismatch("numbers"))
+    ));
+    assert!(matches!(
+        message.set("numbers", vec![Value::from(1.5)]),
+        Err(Error::ValueMismatch("numbers"))
+    ));
+    assert!(matches!(
+        message.set("nested", DynamicMessage::new(&OUTER)),
+        Err(Error::ValueMismatch("nested"))
+    ));
+    assert!(matches!(
+        message.set("color", 1),
+        Err(Error::ValueMismatch("color"))
+    ));
+    assert!(message.fields().next().is_none());
+}
+
+#[test]
+fn encode_matches_typed() {
+    let outer = Outer {
+        nested: Some(Nested {
+            id: Some(1),
+            name: Some("one".to_string()),
+        }),
+        repeated_nested: vec![Nested::default()],
+        numbers: vec![1, -1],
+        ..Outer::default()
+    };
+
+    let mut message = DynamicMessage::new(&OUTER);
+    message.set("nested", dynamic_nested(1, "one")).unwrap();
+    message
+        .set(
+            "repeated_nested",
+            vec![Value::Message(DynamicMessage::new(&NESTED))],
+        )
+        .unwrap();
+    message
+        .set("numbers", vec![Value::from(1), Value::from(-1)])
+        .unwrap();
+
+    assert_eq!(message.encode().unwrap(), to_vec(&outer, &OUTER).unwrap());
+}
+
+#[test]
+fn encode_enums() {
+    let mut message = DynamicMessage::new(&OUTER);
+    message.set("color", Value::Enum(-1)).unwrap();
+    message
+        .set("colors", vec![Value::Enum(0), Value::Enum(1)])
+        .unwrap();
+
+    let outer = from_slice::<Outer>(&message.encode().unwrap(), &OUTER).unwrap();
+    assert_eq!(outer.color, Some(Color::Blue));
+    assert_eq!(outer.colors, vec![Color::Red, Color::Green]);
+}
+
+#[test]
+fn decode_scalars() {
+    let scalars = Scalars {
+        int32: -1,
+        uint64: u64::MAX,
+        sint32: -5,
+        float: 1.5,
+        string: "hello".to_string(),
+        bytes: vec![0x00, 0xff],
+        ..Scalars::default()
+    };
+
+    let buf = Bytes::from(to_vec(&scalars, &SCALARS).unwrap());
+    let message = DynamicMessage::decode(buf, &SCALARS).unwrap();
+    assert_eq!(message.get("int32"), Some(&Value::from(-1)));
+    assert_eq!(message.get("uint64"), Some(&Value::from(u64::MAX)));
+    assert_eq!(message.get("sint32"), Some(&Value::from(-5)));
+    assert_eq!(message.get("float"), Some(&Value::from(1.5f32)));
+    assert_eq!(message.get("string"), Some(&Value::from("hello")));
+    assert_eq!(message.get("bytes"), Some(&Value::Bytes(vec![0x00, 0xff])));
+    // proto3 defaults are not on the wire
+    assert!(!message.has("int64"));
+    assert_eq!(message.fields().count(), 6);
+
+    assert_eq!(
+        from_slice::<Scalars>(&message.encode().unwrap(), &SCALARS).unwrap(),
+        scalars
+    );
+}
+
+#[test]
+fn maps_round_trip() {
+    let mut maps = Maps::default();
+    maps.counts.insert("a".to_string(), 1);
+    maps.nested.insert(-2, Nested::default());
+
+    let message =
+        DynamicMessage::decode(Bytes::from(to_vec(&maps, &MAPS).unwrap()), &MAPS).unwrap();
+    assert_eq!(
+        message.get("counts"),
+        Some(&Value::Map(BTreeMap::from([(
+            MapKey::from("a"),
+            Value::from(1)
+        )])))
+    );
+    assert_eq!(
+        message.get("nested"),
+        Some(&Value::Map(Map::from([(
+            MapKey::from(-2),
+            DynamicMessage::new(&NESTED).into()
+        )])))
+    );
+    assert_eq!(
+        from_slice::<Maps>(&message.encode().unwrap(), &MAPS).unwrap(),
+        maps
+    );
+}
+
+#[test]
+fn groups_round_trip() {
+    let mut message = DynamicMessage::new(&GROUPS);
+    message.set("group", dynamic_nested(1, "one")).unwrap();
+    message
+        .set(
+            "repeated_group",
+            vec![Value::Message(dynamic_nested(2, "two"))],
+        )
+        .unwrap();
+
+    let buf = Bytes::from(message.encode().unwrap());
+    let groups = from_slice::<Groups>(&buf, &GROUPS).unwrap();
+    assert_eq!(groups.repeated_group.len(), 1);
+    assert_eq!(DynamicMessage::decode(buf, &GROUPS).unwrap(), message);
+}
+
+#[test]
+fn value_round_trip() {
+    let outer = Outer {
+        nested: Some(Nested {
+            id: Some(1),
+            name: Some("one".to_string()),
+        }),
+        repeated_nested: vec![Nested::default()],
+        numbers: vec![1, -1],
+        ..Outer::default()
+    };
+
+    let vec = to_vec(&outer, &OUTER).unwrap();
+    let value = from_slice::<Value>(&vec, &OUTER).unwrap();
+    // absent fields are left out
+    let Value::Map(fields) = &value else {
+        panic!("expected a map, got {:?}", value)
+    };
+    assert_eq!(fields.len(), 3);
+    assert_eq!(
+        fields[&MapKey::from("numbers")],
+        Value::List(vec![Value::from(1), Value::from(-1)])
+    );
+
+    // fields are written in key order rather than field order
+    assert_eq!(
+        from_slice::<Outer>(&to_vec(&value, &OUTER).unwrap(), &OUTER).unwrap(),
+        outer
+    );
+}
+
+#[test]
+fn value_round_trip_scalars_and_maps() {
+    let scalars = Scalars {
+        int32: -1,
+        sint64: -2,
+        float: 1.5,
+        double: -2.5,
+        bytes: vec![0x00, 0xff],
+        ..Scalars::default()
+    };
+    let vec = to_vec(&scalars, &SCALARS).unwrap();
+    let value = from_slice::<Value>(&vec, &SCALARS).unwrap();
+    assert_eq!(
+        from_slice::<Scalars>(&to_vec(&value, &SCALARS).unwrap(), &SCALARS).unwrap(),
+        scalars
+    );
+
+    let mut maps = Maps::default();
+    maps.counts.insert("a".to_string(), 1);
+    maps.nested.insert(-2, Nested::default());
+    let vec = to_vec(&maps, &MAPS).unwrap();
+    let value = from_slice::<Value>(&vec, &MAPS).unwrap();
+    assert_eq!(
+        from_slice::<Maps>(&to_vec(&value, &MAPS).unwrap(), &MAPS).unwrap(),
+        maps
+    );
+}
+
+#[test]
+fn value_keyed_by_field_number() {
+    let value = Value::Map(Map::from([
+        (MapKey::from(2), Value::from("one")),
+        (MapKey::from(1), Value::from(1)),
+    ]));
+
+    assert_eq!(
+        from_slice::<Nested>(&to_vec(&value, &NESTED).unwrap(), &NESTED).unwrap(),
+        Nested {
+            id: Some(1),
+            name: Some("one".to_string()),
+        }
+    );
+}
+
+#[test]
+fn value_unknown_field() {
+    let value = Value::Map(Map::from([(MapKey::from("missing"), Value::from(1))]));
+    assert!(matches!(
+        to_vec(&value, &NESTED),
+        Err(Error::FieldNameNotFound(name)) if name == "missing"
+    ));
+}
+
+#[test]
+fn value_json_round_trip() {
+    let value = Value::Map(Map::from([
+        (MapKey::from("bool"), Value::from(true)),
+        (MapKey::from("int"), Value::from(-1)),
+        (MapKey::from("uint"), Value::from(u64::MAX)),
+        (MapKey::from("float"), Value::from(1.5)),
+        (MapKey::from("string"), Value::from("hello")),
+        (
+            MapKey::from("list"),
+            Value::List(vec![Value::from(1u32), Value::Map(Map::new())]),
+        ),
+    ]));
+
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+}
+
+#[test]
+fn value_hash() {
+    let mut set = HashSet::new();
+    assert!(set.insert(Value::from("a")));
+    assert!(set.insert(Value::Message(dynamic_nested(1, "one"))));
+    assert!(!set.insert(Value::from("a")));
+    assert!(!set.insert(Value::Message(dynamic_nested(1, "one"))));
+    assert_eq!(set.len(), 2);
+}
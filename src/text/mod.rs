@@ -0,0 +1,93 @@
+//! The protobuf text format, as found in `.textproto` files.
+//!
+//! Messages are printed one field per line with nested messages indented, using field names and
+//! enum value names. Parsing accepts what the printer writes along with the usual variations: `<>`
+//! as well as `{}` around messages, optional `:` before messages, `[]` lists for repeated fields,
+//! `,` or `;` after fields, `#` comments and adjacent string literals.
+//!
+//! Typed values are printed and parsed by way of a [`DynamicMessage`], so they go through the wire
+//! format on the way.
+//!
+//! The message packed in a `google.protobuf.Any` is printed inline under its type URL, as in
+//! `[type.googleapis.com/pkg.Message] { ... }`, when the [`TypeRegistry`] in the [`Options`] has it.
+
+mod parser;
+mod printer;
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    de, error::Error, info::MessageInfo, registry::TypeRegistry, ser, value::DynamicMessage,
+};
+
+const ANY: &str = "google.protobuf.Any";
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Finds the messages packed in a `google.protobuf.Any`, which are printed inline. An `Any`
+    /// that holds a message the registry does not have is printed as its type URL and bytes.
+    pub type_registry: Option<Arc<TypeRegistry>>,
+}
+
+pub fn to_string(value: &impl Serialize, info: &'static MessageInfo) -> Result<String, Error> {
+    to_string_with_options(value, info, &Options::default())
+}
+
+pub fn to_string_with_options(
+    value: &impl Serialize,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<String, Error> {
+    // a message missing required fields is printed all the same
+    let buf = crate::to_bytes_with_options(value, info, ser::Options { partial: true })?;
+    let de_options = de::Options {
+        partial: true,
+        ..Default::default()
+    };
+    let message = DynamicMessage::decode_with_options(buf, info, de_options)?;
+    Ok(print_with_options(&message, options))
+}
+
+pub fn from_str<T>(s: &str, info: &'static MessageInfo) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_str_with_options(s, info, &Options::default())
+}
+
+pub fn from_str_with_options<T>(
+    s: &str,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let message = parse_with_options(s, info, options)?;
+    crate::from_bytes(Bytes::from(message.encode()?), info)
+}
+
+pub fn print(message: &DynamicMessage) -> String {
+    print_with_options(message, &Options::default())
+}
+
+pub fn print_with_options(message: &DynamicMessage, options: &Options) -> String {
+    let mut printer = printer::Printer::new(options.type_registry.as_deref());
+    printer.message(message);
+    printer.into_string()
+}
+
+pub fn parse(s: &str, info: &'static MessageInfo) -> Result<DynamicMessage, Error> {
+    parse_with_options(s, info, &Options::default())
+}
+
+pub fn parse_with_options(
+    s: &str,
+    info: &'static MessageInfo,
+    options: &Options,
+) -> Result<DynamicMessage, Error> {
+    parser::Parser::new(s, options.type_registry.as_deref()).parse(info)
+}
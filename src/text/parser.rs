@@ -0,0 +1,532 @@
+use serde::de::Error as _;
+
+use crate::{
+    error::Error,
+    info::{Cardinality, FieldInfo, MessageInfo, Type},
+    registry::TypeRegistry,
+    value::{message::default_value, DynamicMessage, MapKey, Value},
+};
+
+use super::ANY;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    // kept as written, since how to read it depends on the type of the field
+    Number(String),
+    String(Vec<u8>),
+    Punct(u8),
+}
+
+pub(crate) struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+    // the token after the one most recently consumed, along with where it starts
+    peeked: Option<(usize, Token)>,
+    type_registry: Option<&'a TypeRegistry>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(src: &'a str, type_registry: Option<&'a TypeRegistry>) -> Self {
+        Parser {
+            src: src.as_bytes(),
+            pos: 0,
+            peeked: None,
+            type_registry,
+        }
+    }
+
+    pub fn parse(mut self, info: &'static MessageInfo) -> Result<DynamicMessage, Error> {
+        self.message(info, None)
+    }
+
+    fn error(&self, pos: usize, msg: impl std::fmt::Display) -> Error {
+        let before = &self.src[..pos.min(self.src.len())];
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let column = pos
+            - before
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |i| i + 1)
+            + 1;
+        Error::custom(format!("{}:{}: {}", line, column, msg))
+    }
+
+    // Fields until the closing delimiter, or until the end of input for the top level message.
+    fn message(
+        &mut self,
+        info: &'static MessageInfo,
+        close: Option<u8>,
+    ) -> Result<DynamicMessage, Error> {
+        let mut message = DynamicMessage::new(info);
+        loop {
+            let (pos, token) = match self.next()? {
+                Some(next) => next,
+                None if close.is_none() => return Ok(message),
+                None => return Err(self.error(self.pos, "unexpected end of input")),
+            };
+            match token {
+                Token::Punct(c) if Some(c) == close => return Ok(message),
+                Token::Punct(b'[') if info.name == ANY => self.any(&mut message, pos)?,
+                Token::Ident(name) => {
+                    let field_info = info
+                        .fields
+                        .iter()
+                        .find(|f| f.name == name)
+                        .ok_or_else(|| self.error(pos, Error::FieldNameNotFound(name)))?;
+                    self.field(&mut message, field_info)?;
+                }
+                token => {
+                    return Err(self.error(pos, format!("expected field name, found {:?}", token)))
+                }
+            }
+            if matches!(self.peek()?, Some(Token::Punct(b';' | b','))) {
+                self.next()?;
+            }
+        }
+    }
+
+    fn field(
+        &mut self,
+        message: &mut DynamicMessage,
+        field_info: &'static FieldInfo,
+    ) -> Result<(), Error> {
+        // the colon may only be left out before a message
+        let is_message = matches!(field_info.ty, Type::Message | Type::Group);
+        if self.peek()? == Some(&Token::Punct(b':')) || !is_message {
+            self.expect(b':')?;
+        }
+
+        let is_repeated = matches!(field_info.cardinality, Cardinality::Repeated);
+        if is_repeated && self.peek()? == Some(&Token::Punct(b'[')) {
+            self.next()?;
+            if self.peek()? == Some(&Token::Punct(b']')) {
+                self.next()?;
+                return Ok(());
+            }
+            loop {
+                let value = self.value(field_info)?;
+                add(message, field_info, value)?;
+                match self.next()? {
+                    Some((_, Token::Punct(b','))) => {}
+                    Some((_, Token::Punct(b']'))) => return Ok(()),
+                    _ => return Err(self.error(self.pos, "expected , or ]")),
+                }
+            }
+        }
+
+        let value = self.value(field_info)?;
+        if is_repeated {
+            add(message, field_info, value)
+        } else {
+            message.set(field_info.name, value).map(|_| ())
+        }
+    }
+
+    // The message packed in an Any may be written inline under its type URL, as in
+    // [type.googleapis.com/pkg.Message] { ... }, once the opening [ has been read.
+    fn any(&mut self, message: &mut DynamicMessage, pos: usize) -> Result<(), Error> {
+        // a type URL holds / and ., so it is read as is rather than as tokens
+        let start = self.pos;
+        let len = self.src[start..]
+            .iter()
+            .position(|&b| b == b']')
+            .ok_or_else(|| self.error(start, "expected ]"))?;
+        self.pos += len + 1;
+        let type_url = String::from_utf8_lossy(&self.src[start..start + len])
+            .trim()
+            .to_string();
+        let message_info = self
+            .type_registry
+            .and_then(|registry| registry.find(&type_url))
+            .ok_or_else(|| self.error(pos, Error::TypeNotFound(type_url.clone())))?;
+
+        if self.peek()? == Some(&Token::Punct(b':')) {
+            self.next()?;
+        }
+        let close = match self.next()? {
+            Some((_, Token::Punct(b'{'))) => b'}',
+            Some((_, Token::Punct(b'<'))) => b'>',
+            _ => return Err(self.error(self.pos, "expected { or <")),
+        };
+        let packed = self.message(message_info, Some(close))?;
+        message.set("type_url", type_url)?;
+        message.set("value", packed.encode()?)?;
+        Ok(())
+    }
+
+    fn value(&mut self, field_info: &'static FieldInfo) -> Result<Value, Error> {
+        match field_info.ty {
+            Type::Message | Type::Group => {
+                let message_info = field_info
+                    .message_info()
+                    .ok_or_else(|| Error::custom("message descriptor not found"))?;
+                let close = match self.next()? {
+                    Some((_, Token::Punct(b'{'))) => b'}',
+                    Some((_, Token::Punct(b'<'))) => b'>',
+                    _ => return Err(self.error(self.pos, "expected { or <")),
+                };
+                Ok(Value::Message(self.message(message_info, Some(close))?))
+            }
+            Type::String => {
+                let pos = self.pos;
+                String::from_utf8(self.string()?)
+                    .map(Value::String)
+                    .map_err(|e| self.error(pos, e))
+            }
+            Type::Bytes => self.string().map(Value::Bytes),
+            Type::Bool => match self.next()? {
+                Some((_, Token::Ident(v))) if matches!(v.as_str(), "true" | "True" | "t") => {
+                    Ok(Value::Bool(true))
+                }
+                Some((_, Token::Ident(v))) if matches!(v.as_str(), "false" | "False" | "f") => {
+                    Ok(Value::Bool(false))
+                }
+                Some((_, Token::Number(v))) if v == "1" => Ok(Value::Bool(true)),
+                Some((_, Token::Number(v))) if v == "0" => Ok(Value::Bool(false)),
+                _ => Err(self.error(self.pos, "expected bool")),
+            },
+            Type::Enum => self.enumeration(field_info),
+            Type::Float => Ok(Value::from(self.float()? as f32)),
+            Type::Double => Ok(Value::from(self.float()?)),
+            Type::Int32 | Type::SInt32 | Type::SFixed32 => {
+                let pos = self.pos;
+                let v = self.int()?;
+                i32::try_from(v)
+                    .map(Value::from)
+                    .map_err(|_| self.error(pos, "value out of range for int32"))
+            }
+            Type::Int64 | Type::SInt64 | Type::SFixed64 => {
+                let pos = self.pos;
+                let v = self.int()?;
+                i64::try_from(v)
+                    .map(Value::from)
+                    .map_err(|_| self.error(pos, "value out of range for int64"))
+            }
+            Type::Uint32 | Type::Fixed32 => {
+                let pos = self.pos;
+                let v = self.int()?;
+                u32::try_from(v)
+                    .map(Value::from)
+                    .map_err(|_| self.error(pos, "value out of range for uint32"))
+            }
+            Type::Uint64 | Type::Fixed64 => {
+                let pos = self.pos;
+                let v = self.int()?;
+                u64::try_from(v)
+                    .map(Value::from)
+                    .map_err(|_| self.error(pos, "value out of range for uint64"))
+            }
+        }
+    }
+
+    // Enums are written by the name of their value, or by number when the value is not known.
+    fn enumeration(&mut self, field_info: &'static FieldInfo) -> Result<Value, Error> {
+        let enum_info = field_info
+            .enum_info
+            .ok_or_else(|| Error::custom("enum descriptor not found"))?;
+        if let Some(Token::Ident(_)) = self.peek()? {
+            if let Some((pos, Token::Ident(name))) = self.next()? {
+                return enum_info
+                    .value
+                    .iter()
+                    .find(|v| v.name == name)
+                    .map(|v| Value::Enum(v.number))
+                    .ok_or_else(|| {
+                        self.error(pos, format!("unknown {} value {}", enum_info.name, name))
+                    });
+            }
+        }
+
+        let pos = self.pos;
+        let v = self.int()?;
+        i32::try_from(v)
+            .map(Value::Enum)
+            .map_err(|_| self.error(pos, "enum value out of range"))
+    }
+
+    // Adjacent string literals are concatenated.
+    fn string(&mut self) -> Result<Vec<u8>, Error> {
+        let mut s = match self.next()? {
+            Some((_, Token::String(s))) => s,
+            _ => return Err(self.error(self.pos, "expected string")),
+        };
+        while let Some(Token::String(_)) = self.peek()? {
+            if let Some((_, Token::String(more))) = self.next()? {
+                s.extend(more);
+            }
+        }
+        Ok(s)
+    }
+
+    // Any integer that fits either an i64 or a u64, in decimal, hex or octal.
+    fn int(&mut self) -> Result<i128, Error> {
+        let negative = self.sign()?;
+        let (pos, digits) = match self.next()? {
+            Some((pos, Token::Number(digits))) => (pos, digits),
+            _ => return Err(self.error(self.pos, "expected integer")),
+        };
+        let v = if let Some(hex) = digits
+            .strip_prefix("0x")
+            .or_else(|| digits.strip_prefix("0X"))
+        {
+            u64::from_str_radix(hex, 16)
+        } else if digits.len() > 1 && digits.starts_with('0') {
+            u64::from_str_radix(&digits[1..], 8)
+        } else {
+            digits.parse()
+        }
+        .map_err(|_| self.error(pos, format!("invalid integer {}", digits)))?;
+        Ok(if negative { -(v as i128) } else { v as i128 })
+    }
+
+    fn float(&mut self) -> Result<f64, Error> {
+        let negative = self.sign()?;
+        let v = match self.next()? {
+            Some((pos, Token::Number(digits))) => {
+                let digits = digits.trim_end_matches(['f', 'F']);
+                if digits.starts_with("0x") || digits.starts_with("0X") {
+                    return Err(self.error(pos, "expected float"));
+                }
+                digits
+                    .parse()
+                    .map_err(|_| self.error(pos, format!("invalid float {}", digits)))?
+            }
+            Some((pos, Token::Ident(name))) => match name.to_ascii_lowercase().as_str() {
+                "inf" | "infinity" => f64::INFINITY,
+                "nan" => f64::NAN,
+                _ => return Err(self.error(pos, "expected float")),
+            },
+            _ => return Err(self.error(self.pos, "expected float")),
+        };
+        Ok(if negative { -v } else { v })
+    }
+
+    fn sign(&mut self) -> Result<bool, Error> {
+        if self.peek()? == Some(&Token::Punct(b'-')) {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), Error> {
+        match self.next()? {
+            Some((_, Token::Punct(p))) if p == c => Ok(()),
+            Some((pos, token)) => {
+                Err(self.error(pos, format!("expected {}, found {:?}", c as char, token)))
+            }
+            None => Err(self.error(self.pos, format!("expected {}", c as char))),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token>, Error> {
+        if self.peeked.is_none() {
+            self.peeked = self.lex()?;
+        }
+        Ok(self.peeked.as_ref().map(|(_, token)| token))
+    }
+
+    fn next(&mut self) -> Result<Option<(usize, Token)>, Error> {
+        match self.peeked.take() {
+            Some(peeked) => Ok(Some(peeked)),
+            None => self.lex(),
+        }
+    }
+
+    fn lex(&mut self) -> Result<Option<(usize, Token)>, Error> {
+        // whitespace and comments
+        while let Some(&b) = self.src.get(self.pos) {
+            match b {
+                b'#' => {
+                    while self.src.get(self.pos).is_some_and(|&b| b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        let Some(&b) = self.src.get(start) else {
+            return Ok(None);
+        };
+        let token = match b {
+            b'"' | b'\'' => Token::String(self.lex_string(b)?),
+            b'0'..=b'9' | b'.' => {
+                self.pos += self.number_len(start);
+                Token::Number(String::from_utf8_lossy(&self.src[start..self.pos]).into_owned())
+            }
+            b if b.is_ascii_alphabetic() || b == b'_' => {
+                let len = self.src[start..]
+                    .iter()
+                    .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+                    .unwrap_or(self.src.len() - start);
+                self.pos += len;
+                Token::Ident(String::from_utf8_lossy(&self.src[start..self.pos]).into_owned())
+            }
+            _ => {
+                self.pos += 1;
+                Token::Punct(b)
+            }
+        };
+        Ok(Some((start, token)))
+    }
+
+    // Numbers run until the first byte that cannot be part of one. A sign may only follow the
+    // exponent of a decimal float.
+    fn number_len(&self, start: usize) -> usize {
+        let src = &self.src[start..];
+        let is_hex = src.len() > 1 && src[0] == b'0' && matches!(src[1], b'x' | b'X');
+        let mut len = 0;
+        while let Some(&b) = src.get(len) {
+            let is_exponent_sign = (b == b'+' || b == b'-')
+                && !is_hex
+                && len > 0
+                && matches!(src[len - 1], b'e' | b'E');
+            if b.is_ascii_alphanumeric() || b == b'.' || is_exponent_sign {
+                len += 1;
+            } else {
+                break;
+            }
+        }
+        len
+    }
+
+    fn lex_string(&mut self, quote: u8) -> Result<Vec<u8>, Error> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut s = Vec::new();
+        loop {
+            let Some(&b) = self.src.get(self.pos) else {
+                return Err(self.error(start, "unterminated string"));
+            };
+            self.pos += 1;
+            match b {
+                b if b == quote => return Ok(s),
+                b'\n' => return Err(self.error(start, "unterminated string")),
+                b'\\' => self.lex_escape(&mut s)?,
+                b => s.push(b),
+            }
+        }
+    }
+
+    fn lex_escape(&mut self, s: &mut Vec<u8>) -> Result<(), Error> {
+        let pos = self.pos - 1;
+        let Some(&b) = self.src.get(self.pos) else {
+            return Err(self.error(pos, "unterminated escape"));
+        };
+        self.pos += 1;
+        match b {
+            b'n' => s.push(b'\n'),
+            b'r' => s.push(b'\r'),
+            b't' => s.push(b'\t'),
+            b'a' => s.push(0x07),
+            b'b' => s.push(0x08),
+            b'f' => s.push(0x0c),
+            b'v' => s.push(0x0b),
+            b'\\' | b'\'' | b'"' | b'?' => s.push(b),
+            b'0'..=b'7' => {
+                let digits = self.take_digits(pos, 1, 3, 8)?;
+                let v =
+                    u8::try_from(digits).map_err(|_| self.error(pos, "invalid octal escape"))?;
+                s.push(v);
+            }
+            b'x' => {
+                self.pos += 1;
+                let v = self.take_digits(pos, 1, 2, 16)?;
+                s.push(v as u8);
+            }
+            b'u' | b'U' => {
+                self.pos += 1;
+                let len = if b == b'u' { 4 } else { 8 };
+                let v = self.take_digits(pos, len, len, 16)?;
+                let c =
+                    char::from_u32(v).ok_or_else(|| self.error(pos, "invalid unicode escape"))?;
+                let mut buf = [0; 4];
+                s.extend(c.encode_utf8(&mut buf).as_bytes());
+            }
+            _ => return Err(self.error(pos, format!("invalid escape \\{}", b as char))),
+        }
+        Ok(())
+    }
+
+    // Reads between min and max digits in the given radix, starting at the byte before the
+    // current position.
+    fn take_digits(
+        &mut self,
+        pos: usize,
+        min: usize,
+        max: usize,
+        radix: u32,
+    ) -> Result<u32, Error> {
+        let start = self.pos - 1;
+        let len = self.src[start..]
+            .iter()
+            .take(max)
+            .take_while(|&&b| (b as char).is_digit(radix))
+            .count();
+        if len < min {
+            return Err(self.error(pos, "invalid escape"));
+        }
+        self.pos = start + len;
+        let digits = std::str::from_utf8(&self.src[start..self.pos]).unwrap_or_default();
+        u32::from_str_radix(digits, radix).map_err(|_| self.error(pos, "invalid escape"))
+    }
+}
+
+// Adds a value to a repeated or map field.
+fn add(
+    message: &mut DynamicMessage,
+    field_info: &'static FieldInfo,
+    value: Value,
+) -> Result<(), Error> {
+    let entry_info = field_info.message_info().filter(|m| m.is_map);
+    let Some(entry_info) = entry_info else {
+        match message.get_mut(field_info.name) {
+            Some(Value::List(list)) => list.push(value),
+            _ => {
+                message.set(field_info.name, Value::List(vec![value]))?;
+            }
+        }
+        return Ok(());
+    };
+
+    // a map entry is written as the message it is on the wire, with absent keys and values taking
+    // on their defaults
+    let Value::Message(mut entry) = value else {
+        return Err(Error::ValueMismatch(field_info.name));
+    };
+    let (key_index, value_index) = entry_info.map_entry()?;
+    let key_info = &entry_info.fields[key_index];
+    let value_info = &entry_info.fields[value_index];
+    let key = match entry
+        .clear(key_info.number)
+        .map_or_else(|| default_value(key_info), Ok)?
+    {
+        Value::Bool(k) => MapKey::Bool(k),
+        Value::String(k) => MapKey::String(k),
+        Value::Number(n) => match key_info.ty {
+            Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64 => {
+                MapKey::Uint(n.as_u64().ok_or(Error::ValueMismatch(key_info.name))?)
+            }
+            _ => MapKey::Int(n.as_i64().ok_or(Error::ValueMismatch(key_info.name))?),
+        },
+        _ => return Err(Error::ValueMismatch(key_info.name)),
+    };
+    let value = entry
+        .clear(value_info.number)
+        .map_or_else(|| default_value(value_info), Ok)?;
+
+    match message.get_mut(field_info.name) {
+        Some(Value::Map(map)) => {
+            map.insert(key, value);
+        }
+        _ => {
+            message.set(field_info.name, Value::Map([(key, value)].into()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use bytes::Bytes;
+
+use crate::{
+    info::{FieldInfo, Type},
+    registry::TypeRegistry,
+    value::{DynamicMessage, MapKey, Value},
+};
+
+use super::ANY;
+
+#[derive(Default)]
+pub(crate) struct Printer<'a> {
+    out: String,
+    indent: usize,
+    type_registry: Option<&'a TypeRegistry>,
+}
+
+impl<'a> Printer<'a> {
+    pub fn new(type_registry: Option<&'a TypeRegistry>) -> Self {
+        Printer {
+            type_registry,
+            ..Printer::default()
+        }
+    }
+
+    pub fn into_string(self) -> String {
+        self.out
+    }
+
+    pub fn message(&mut self, message: &DynamicMessage) {
+        if self.any(message) {
+            return;
+        }
+        for (field_info, value) in message.fields() {
+            match value {
+                Value::List(list) => {
+                    for value in list {
+                        self.field(field_info, value);
+                    }
+                }
+                Value::Map(map) => {
+                    for (key, value) in map {
+                        self.entry(field_info, key, value);
+                    }
+                }
+                value => self.field(field_info, value),
+            }
+        }
+    }
+
+    fn field(&mut self, field_info: &FieldInfo, value: &Value) {
+        self.write_indent();
+        self.out.push_str(field_info.name);
+        match value {
+            Value::Message(message) => {
+                self.out.push_str(" {\n");
+                self.indent += 1;
+                self.message(message);
+                self.indent -= 1;
+                self.write_indent();
+                self.out.push_str("}\n");
+            }
+            value => {
+                self.out.push_str(": ");
+                self.scalar(field_info, value);
+                self.out.push('\n');
+            }
+        }
+    }
+
+    // The message packed in an Any is printed inline under its type URL when the registry has it,
+    // and otherwise the Any is printed as any other message. Returns whether it was.
+    fn any(&mut self, message: &DynamicMessage) -> bool {
+        if message.message_info().name != ANY {
+            return false;
+        }
+        let Some(Value::String(type_url)) = message.get("type_url") else {
+            return false;
+        };
+        let buf = match message.get("value") {
+            Some(Value::Bytes(buf)) => buf.clone(),
+            Some(_) => return false,
+            None => Default::default(),
+        };
+        let Some(Ok(packed)) = self
+            .type_registry
+            .map(|registry| registry.decode(type_url, Bytes::from(buf)))
+        else {
+            return false;
+        };
+
+        self.write_indent();
+        writeln!(self.out, "[{}] {{", type_url).unwrap();
+        self.indent += 1;
+        self.message(&packed);
+        self.indent -= 1;
+        self.write_indent();
+        self.out.push_str("}\n");
+        true
+    }
+
+    // A map entry is printed as the message it is on the wire.
+    fn entry(&mut self, field_info: &FieldInfo, key: &MapKey, value: &Value) {
+        let Some(entry_info) = field_info.message_info() else {
+            return;
+        };
+        let Ok((key_index, value_index)) = entry_info.map_entry() else {
+            return;
+        };
+        let key = match key {
+            MapKey::Bool(k) => Value::Bool(*k),
+            MapKey::Int(k) => Value::from(*k),
+            MapKey::Uint(k) => Value::from(*k),
+            MapKey::String(k) => Value::from(k.as_str()),
+        };
+
+        self.write_indent();
+        self.out.push_str(field_info.name);
+        self.out.push_str(" {\n");
+        self.indent += 1;
+        self.field(&entry_info.fields[key_index], &key);
+        self.field(&entry_info.fields[value_index], value);
+        self.indent -= 1;
+        self.write_indent();
+        self.out.push_str("}\n");
+    }
+
+    fn scalar(&mut self, field_info: &FieldInfo, value: &Value) {
+        match value {
+            Value::Bool(v) => write!(self.out, "{}", v).unwrap(),
+            Value::Number(n) => match (field_info.ty, n.as_f64()) {
+                // f32 values are widened when decoded, so they are narrowed again to print the
+                // shortest representation
+                (Type::Float, Some(v)) => self.float(v as f32 as f64, (v as f32).to_string()),
+                (Type::Double, Some(v)) => self.float(v, v.to_string()),
+                _ => write!(self.out, "{}", n).unwrap(),
+            },
+            Value::Enum(v) => {
+                let name = field_info
+                    .enum_info
+                    .and_then(|e| e.value.iter().find(|value| value.number == *v));
+                match name {
+                    Some(value) => self.out.push_str(value.name),
+                    None => write!(self.out, "{}", v).unwrap(),
+                }
+            }
+            Value::String(v) => self.string(v.as_bytes()),
+            Value::Bytes(v) => self.string(v),
+            // lists, maps and messages are handled by the caller
+            Value::List(_) | Value::Map(_) | Value::Message(_) => {}
+        }
+    }
+
+    fn float(&mut self, v: f64, repr: String) {
+        if v.is_nan() {
+            self.out.push_str("nan");
+        } else if v.is_infinite() {
+            self.out.push_str(if v > 0.0 { "inf" } else { "-inf" });
+        } else {
+            self.out.push_str(&repr);
+        }
+    }
+
+    // Printable ASCII is written as is and everything else is escaped, which keeps the output
+    // valid whether or not the bytes are UTF-8.
+    fn string(&mut self, v: &[u8]) {
+        self.out.push('"');
+        for &b in v {
+            match b {
+                b'\n' => self.out.push_str("\\n"),
+                b'\r' => self.out.push_str("\\r"),
+                b'\t' => self.out.push_str("\\t"),
+                b'"' => self.out.push_str("\\\""),
+                b'\'' => self.out.push_str("\\'"),
+                b'\\' => self.out.push_str("\\\\"),
+                0x20..=0x7e => self.out.push(b as char),
+                _ => write!(self.out, "\\{:03o}", b).unwrap(),
+            }
+        }
+        self.out.push('"');
+    }
+
+    fn write_indent(&mut self) {
+        for _ in 0..self.indent {
+            self.out.push_str("  ");
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Fields read from the wire that the [`MessageInfo`](crate::info::MessageInfo) of a message does
+//! not describe, such as fields added by a newer version of its schema.
+//!
+//! A message keeps them by holding an [`UnknownFields`] under the reserved name
+//! [`UNKNOWN_FIELDS`]. The tobu deserializer fills it with every unknown field in the order they
+//! were read, unless told to discard them, and the serializer writes them back exactly as they
+//! were read. A derived struct declares the field as
+//! `#[serde(rename = "_unknown_fields", default)] unknown_fields: UnknownFields`.
+
+use std::{fmt, slice, vec};
+
+use bytes::{BufMut, Bytes};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use tobu_format::wire::{put_field, size_field, Parser, WireField};
+
+use crate::error::Error;
+
+/// The struct field name that holds the [`UnknownFields`] of a message.
+pub const UNKNOWN_FIELDS: &str = "_unknown_fields";
+
+/// The unknown fields of a message, in the order they were read.
+///
+/// Other formats have no way to express unknown fields, so the JSON and text formats leave them
+/// out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnknownFields(Vec<WireField>);
+
+impl UnknownFields {
+    pub fn new() -> Self {
+        UnknownFields(Vec::new())
+    }
+
+    pub fn push(&mut self, field: WireField) {
+        self.0.push(field);
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, WireField> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn encode(&self, buf: &mut impl BufMut) {
+        for field in &self.0 {
+            put_field(buf, field);
+        }
+    }
+
+    pub fn encoded_len(&self) -> usize {
+        self.0.iter().map(size_field).sum()
+    }
+
+    pub fn decode(buf: Bytes) -> Result<Self, Error> {
+        Ok(UnknownFields(Parser::new(buf).collect::<Result<_, _>>()?))
+    }
+}
+
+impl From<Vec<WireField>> for UnknownFields {
+    fn from(fields: Vec<WireField>) -> Self {
+        UnknownFields(fields)
+    }
+}
+
+impl FromIterator<WireField> for UnknownFields {
+    fn from_iter<I: IntoIterator<Item = WireField>>(iter: I) -> Self {
+        UnknownFields(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for UnknownFields {
+    type Item = WireField;
+    type IntoIter = vec::IntoIter<WireField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UnknownFields {
+    type Item = &'a WireField;
+    type IntoIter = slice::Iter<'a, WireField>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+// Unknown fields serialize as the bytes they were read from, which the tobu serializer writes into
+// the message as is.
+impl Serialize for UnknownFields {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.encode(&mut buf);
+        serializer.serialize_bytes(&buf)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(UnknownFieldsVisitor)
+    }
+}
+
+struct UnknownFieldsVisitor;
+
+impl<'de> de::Visitor<'de> for UnknownFieldsVisitor {
+    type Value = UnknownFields;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("unknown fields")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_byte_buf(v.to_vec())
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        UnknownFields::decode(Bytes::from(v)).map_err(E::custom)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut buf = Vec::new();
+        while let Some(b) = seq.next_element()? {
+            buf.push(b);
+        }
+        self.visit_byte_buf(buf)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(UnknownFields::new())
+    }
+}
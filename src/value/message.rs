@@ -0,0 +1,926 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ptr,
+};
+
+use bytes::Bytes;
+use serde::{
+    de::{self, DeserializeSeed, IgnoredAny, VariantAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq, SerializeStruct},
+    Deserializer, Serialize, Serializer,
+};
+use tobu_format::field::FieldNumber;
+
+use crate::{
+    error::Error,
+    info::{Cardinality, DefaultValue, FieldInfo, MessageInfo, Type},
+    unknown::{UnknownFields, UNKNOWN_FIELDS},
+};
+
+use super::{number::Number, MapKey, Value};
+
+/// A message whose shape is only known at runtime through its [`MessageInfo`].
+///
+/// Fields are accessed by name or by [`FieldNumber`]. Singular fields hold a scalar, enum or
+/// message [`Value`], repeated fields a [`Value::List`] and map fields a [`Value::Map`].
+#[derive(Debug, Clone)]
+pub struct DynamicMessage {
+    message_info: &'static MessageInfo,
+    // one slot per field descriptor, in the same order
+    fields: Vec<Option<Value>>,
+    unknown_fields: UnknownFields,
+}
+
+/// Identifies a field of a [`DynamicMessage`].
+pub trait FieldKey {
+    fn field_index(&self, message_info: &MessageInfo) -> Result<usize, Error>;
+}
+
+impl FieldKey for &str {
+    fn field_index(&self, message_info: &MessageInfo) -> Result<usize, Error> {
+        message_info
+            .fields
+            .iter()
+            .position(|f| f.name == *self)
+            .ok_or_else(|| Error::FieldNameNotFound(self.to_string()))
+    }
+}
+
+impl FieldKey for FieldNumber {
+    fn field_index(&self, message_info: &MessageInfo) -> Result<usize, Error> {
+        message_info
+            .field_index(*self)
+            .ok_or(Error::FieldNotFound(*self))
+    }
+}
+
+impl DynamicMessage {
+    pub fn new(message_info: &'static MessageInfo) -> Self {
+        DynamicMessage {
+            message_info,
+            fields: vec![None; message_info.fields.len()],
+            unknown_fields: UnknownFields::new(),
+        }
+    }
+
+    pub fn message_info(&self) -> &'static MessageInfo {
+        self.message_info
+    }
+
+    pub fn get(&self, field: impl FieldKey) -> Option<&Value> {
+        let index = field.field_index(self.message_info).ok()?;
+        self.fields[index].as_ref()
+    }
+
+    pub fn get_mut(&mut self, field: impl FieldKey) -> Option<&mut Value> {
+        let index = field.field_index(self.message_info).ok()?;
+        self.fields[index].as_mut()
+    }
+
+    pub fn has(&self, field: impl FieldKey) -> bool {
+        self.get(field).is_some()
+    }
+
+    /// Sets a field, returning the value it held before. The value must match the field
+    /// descriptor, for example a repeated field only accepts a [`Value::List`] of its type.
+    /// Setting a member of a oneof clears the other members.
+    pub fn set(
+        &mut self,
+        field: impl FieldKey,
+        value: impl Into<Value>,
+    ) -> Result<Option<Value>, Error> {
+        let index = field.field_index(self.message_info)?;
+        let field_info = &self.message_info.fields[index];
+        let value = value.into();
+        if !matches_field(field_info, &value) {
+            return Err(Error::ValueMismatch(field_info.name));
+        }
+        if let Some(oneof_index) = field_info.oneof_index {
+            for (i, f) in self.message_info.fields.iter().enumerate() {
+                if i != index && f.oneof_index == Some(oneof_index) {
+                    self.fields[i] = None;
+                }
+            }
+        }
+        Ok(self.fields[index].replace(value))
+    }
+
+    /// Returns the value of a field, or the value it takes on when it is not set: the declared
+    /// default of a proto2 field, an empty message, list or map, or the zero value of its type.
+    pub fn get_or_default(&self, field: impl FieldKey) -> Result<Value, Error> {
+        let index = field.field_index(self.message_info)?;
+        match &self.fields[index] {
+            Some(value) => Ok(value.clone()),
+            None => default_value(&self.message_info.fields[index]),
+        }
+    }
+
+    pub fn clear(&mut self, field: impl FieldKey) -> Option<Value> {
+        let index = field.field_index(self.message_info).ok()?;
+        self.fields[index].take()
+    }
+
+    /// Iterates over the fields that are set, in field descriptor order.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static FieldInfo, &Value)> {
+        self.message_info
+            .fields
+            .iter()
+            .zip(&self.fields)
+            .filter_map(|(field_info, value)| value.as_ref().map(|value| (field_info, value)))
+    }
+
+    /// The fields read from the wire that the message descriptor does not know about.
+    pub fn unknown_fields(&self) -> &UnknownFields {
+        &self.unknown_fields
+    }
+
+    pub fn unknown_fields_mut(&mut self) -> &mut UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    pub fn encode(&self) -> Result<Vec<u8>, Error> {
+        crate::to_vec(self, self.message_info)
+    }
+
+    pub fn decode(buf: Bytes, message_info: &'static MessageInfo) -> Result<Self, Error> {
+        DynamicMessage::decode_with_options(buf, message_info, crate::de::Options::default())
+    }
+
+    pub fn decode_with_options(
+        buf: Bytes,
+        message_info: &'static MessageInfo,
+        options: crate::de::Options,
+    ) -> Result<Self, Error> {
+        DynamicMessage::deserialize(
+            message_info,
+            &mut crate::de::Deserializer::with_options(buf, message_info, options),
+        )
+    }
+
+    /// Deserializes a message described by `message_info` from any self-describing format.
+    pub fn deserialize<'de, D>(
+        message_info: &'static MessageInfo,
+        deserializer: D,
+    ) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        MessageSeed(message_info).deserialize(deserializer)
+    }
+}
+
+impl PartialEq for DynamicMessage {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.message_info, other.message_info)
+            && self.fields == other.fields
+            && self.unknown_fields == other.unknown_fields
+    }
+}
+
+impl Eq for DynamicMessage {}
+
+impl Hash for DynamicMessage {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.message_info.name.hash(state);
+        self.fields.hash(state);
+    }
+}
+
+// The value a field takes on when it is not set.
+pub(crate) fn default_value(field_info: &FieldInfo) -> Result<Value, Error> {
+    if let Cardinality::Repeated = field_info.cardinality {
+        return Ok(match map_entry(field_info) {
+            Some(_) => Value::Map(Default::default()),
+            None => Value::List(Vec::new()),
+        });
+    }
+
+    Ok(match (field_info.default_value, field_info.ty) {
+        (Some(DefaultValue::Bool(v)), _) => Value::Bool(v),
+        (Some(DefaultValue::Int(v)), _) => Value::from(v),
+        (Some(DefaultValue::Uint(v)), _) => Value::from(v),
+        (Some(DefaultValue::Float(v)), _) => Value::from(v),
+        (Some(DefaultValue::String(v)), _) => Value::String(v.to_string()),
+        (Some(DefaultValue::Bytes(v)), _) => Value::Bytes(v.to_vec()),
+        (Some(DefaultValue::Enum(v)), _) => Value::Enum(v),
+        (None, Type::Bool) => Value::Bool(false),
+        (None, Type::String) => Value::String(String::new()),
+        (None, Type::Bytes) => Value::Bytes(Vec::new()),
+        // the first value of an enum is its default
+        (None, Type::Enum) => Value::Enum(
+            field_info
+                .enum_info
+                .and_then(|e| e.value.first())
+                .map_or(0, |v| v.number),
+        ),
+        (None, Type::Float | Type::Double) => Value::from(0.0),
+        (None, Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64) => Value::from(0u64),
+        (None, Type::Message | Type::Group) => {
+            let message_info = field_info
+                .message_info()
+                .ok_or_else(|| Error::Message("message descriptor not found".to_string()))?;
+            Value::Message(DynamicMessage::new(message_info))
+        }
+        (None, _) => Value::from(0i64),
+    })
+}
+
+fn matches_field(field_info: &FieldInfo, value: &Value) -> bool {
+    match (field_info.cardinality, value) {
+        (Cardinality::Repeated, Value::Map(map)) => map_entry(field_info).is_some_and(|(k, v)| {
+            map.iter()
+                .all(|(key, value)| matches_key(k, key) && matches_value(v, value))
+        }),
+        (Cardinality::Repeated, Value::List(list)) => {
+            map_entry(field_info).is_none() && list.iter().all(|v| matches_value(field_info, v))
+        }
+        (Cardinality::Repeated, _) => false,
+        _ => matches_value(field_info, value),
+    }
+}
+
+fn matches_value(field_info: &FieldInfo, value: &Value) -> bool {
+    match (field_info.ty, value) {
+        (Type::Bool, Value::Bool(_))
+        | (Type::String, Value::String(_))
+        | (Type::Bytes, Value::Bytes(_))
+        | (Type::Enum, Value::Enum(_))
+        | (Type::Double | Type::Float, Value::Number(_)) => true,
+        (
+            Type::Int32
+            | Type::Int64
+            | Type::Uint32
+            | Type::Uint64
+            | Type::SInt32
+            | Type::SInt64
+            | Type::Fixed32
+            | Type::Fixed64
+            | Type::SFixed32
+            | Type::SFixed64,
+            Value::Number(n),
+        ) => !n.is_f64(),
+        (Type::Message | Type::Group, Value::Message(m)) => field_info
+            .message_info()
+            .is_some_and(|info| ptr::eq(info, m.message_info)),
+        _ => false,
+    }
+}
+
+fn matches_key(field_info: &FieldInfo, key: &MapKey) -> bool {
+    matches!(
+        (field_info.ty, key),
+        (Type::Bool, MapKey::Bool(_))
+            | (Type::String, MapKey::String(_))
+            | (
+                Type::Int32
+                    | Type::Int64
+                    | Type::SInt32
+                    | Type::SInt64
+                    | Type::SFixed32
+                    | Type::SFixed64,
+                MapKey::Int(_)
+            )
+            | (
+                Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64,
+                MapKey::Uint(_)
+            )
+    )
+}
+
+// The key and value field descriptors of a map field.
+fn map_entry(field_info: &FieldInfo) -> Option<(&'static FieldInfo, &'static FieldInfo)> {
+    let entry_info = field_info.message_info().filter(|m| m.is_map)?;
+    let (key_index, value_index) = entry_info.map_entry().ok()?;
+    Some((
+        &entry_info.fields[key_index],
+        &entry_info.fields[value_index],
+    ))
+}
+
+impl Serialize for DynamicMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = self.fields.iter().filter(|v| v.is_some()).count()
+            + usize::from(!self.unknown_fields.is_empty());
+        let mut message = serializer.serialize_struct(self.message_info.name, len)?;
+        let fields = self.message_info.fields;
+        let mut index = 0;
+        while index < fields.len() {
+            let next_index = self.message_info.next_field_index(index);
+            let field_info = &fields[index];
+            match field_info.oneof_index {
+                // a oneof is a single field holding whichever member is set
+                Some(oneof_index) => {
+                    let name = self
+                        .message_info
+                        .oneofs
+                        .get(oneof_index as usize)
+                        .ok_or_else(|| ser::Error::custom("oneof descriptor not found"))?
+                        .name;
+                    let member = (index..next_index).find_map(|i| {
+                        let value = self.fields[i].as_ref()?;
+                        Some(Oneof {
+                            name,
+                            position: (i - index) as u32,
+                            field: Field {
+                                field_info: &fields[i],
+                                value,
+                            },
+                        })
+                    });
+                    match member {
+                        Some(member) => message.serialize_field(name, &member)?,
+                        None => message.skip_field(name)?,
+                    }
+                }
+                None => match &self.fields[index] {
+                    Some(value) => {
+                        message.serialize_field(field_info.name, &Field { field_info, value })?
+                    }
+                    None => message.skip_field(field_info.name)?,
+                },
+            }
+            index = next_index;
+        }
+        if !self.unknown_fields.is_empty() {
+            message.serialize_field(UNKNOWN_FIELDS, &self.unknown_fields)?;
+        }
+        message.end()
+    }
+}
+
+// The member of a oneof that is set, serialized as the variant of an enum.
+struct Oneof<'a> {
+    name: &'static str,
+    position: u32,
+    field: Field<'a>,
+}
+
+impl<'a> Serialize for Oneof<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_variant(
+            self.name,
+            self.position,
+            self.field.field_info.name,
+            &self.field,
+        )
+    }
+}
+
+// A value serialized as the type its field descriptor calls for.
+struct Field<'a> {
+    field_info: &'static FieldInfo,
+    value: &'a Value,
+}
+
+impl<'a> Serialize for Field<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let field_info = self.field_info;
+        match self.value {
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::Number(n) => serialize_number(field_info, n, serializer),
+            Value::Enum(v) => serializer.serialize_i32(*v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Message(v) => v.serialize(serializer),
+            Value::List(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for value in list {
+                    seq.serialize_element(&Field { field_info, value })?;
+                }
+                seq.end()
+            }
+            Value::Map(map) => {
+                let (key_info, value_info) = map_entry(field_info)
+                    .ok_or_else(|| ser::Error::custom("map entry descriptor expected"))?;
+                let mut entries = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    entries.serialize_key(&Key {
+                        field_info: key_info,
+                        key,
+                    })?;
+                    entries.serialize_value(&Field {
+                        field_info: value_info,
+                        value,
+                    })?;
+                }
+                entries.end()
+            }
+        }
+    }
+}
+
+fn serialize_number<S>(field_info: &FieldInfo, n: &Number, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let out_of_range =
+        || ser::Error::custom(format!("{} out of range for field {}", n, field_info.name));
+    match field_info.ty {
+        Type::Double => serializer.serialize_f64(n.as_f64().ok_or_else(out_of_range)?),
+        Type::Float => serializer.serialize_f32(n.as_f64().ok_or_else(out_of_range)? as f32),
+        Type::Int32 | Type::SInt32 | Type::SFixed32 => serializer.serialize_i32(
+            n.as_i64()
+                .and_then(|n| i32::try_from(n).ok())
+                .ok_or_else(out_of_range)?,
+        ),
+        Type::Int64 | Type::SInt64 | Type::SFixed64 => {
+            serializer.serialize_i64(n.as_i64().ok_or_else(out_of_range)?)
+        }
+        Type::Uint32 | Type::Fixed32 => serializer.serialize_u32(
+            n.as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .ok_or_else(out_of_range)?,
+        ),
+        Type::Uint64 | Type::Fixed64 => {
+            serializer.serialize_u64(n.as_u64().ok_or_else(out_of_range)?)
+        }
+        _ => Err(ser::Error::custom(Error::ValueMismatch(field_info.name))),
+    }
+}
+
+// A map key serialized as the type its field descriptor calls for.
+struct Key<'a> {
+    field_info: &'static FieldInfo,
+    key: &'a MapKey,
+}
+
+impl<'a> Serialize for Key<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let out_of_range = || {
+            ser::Error::custom(format!(
+                "{:?} out of range for field {}",
+                self.key, self.field_info.name
+            ))
+        };
+        match (self.field_info.ty, self.key) {
+            (Type::Int32 | Type::SInt32 | Type::SFixed32, MapKey::Int(k)) => {
+                serializer.serialize_i32(i32::try_from(*k).map_err(|_| out_of_range())?)
+            }
+            (Type::Uint32 | Type::Fixed32, MapKey::Uint(k)) => {
+                serializer.serialize_u32(u32::try_from(*k).map_err(|_| out_of_range())?)
+            }
+            (_, MapKey::Bool(k)) => serializer.serialize_bool(*k),
+            (_, MapKey::Int(k)) => serializer.serialize_i64(*k),
+            (_, MapKey::Uint(k)) => serializer.serialize_u64(*k),
+            (_, MapKey::String(k)) => serializer.serialize_str(k),
+        }
+    }
+}
+
+struct MessageSeed(&'static MessageInfo);
+
+impl<'de> DeserializeSeed<'de> for MessageSeed {
+    type Value = DynamicMessage;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(self.0.name, &[UNKNOWN_FIELDS], MessageVisitor(self.0))
+    }
+}
+
+struct MessageVisitor(&'static MessageInfo);
+
+impl<'de> Visitor<'de> for MessageVisitor {
+    type Value = DynamicMessage;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "message {}", self.0.name)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut message = DynamicMessage::new(self.0);
+        while let Some(name) = map.next_key::<String>()? {
+            if name == UNKNOWN_FIELDS {
+                message.unknown_fields = map.next_value()?;
+                continue;
+            }
+            if let Ok(index) = name.as_str().field_index(self.0) {
+                let field_info = &self.0.fields[index];
+                message.fields[index] = map.next_value_seed(FieldSeed(field_info))?;
+                continue;
+            }
+            let oneof_index = self.0.oneofs.iter().position(|o| o.name == name);
+            let first = oneof_index.and_then(|oneof_index| {
+                self.0
+                    .fields
+                    .iter()
+                    .position(|f| f.oneof_index == Some(oneof_index as i32))
+            });
+            match first {
+                Some(first) => {
+                    if let Some((index, value)) = map.next_value_seed(OneofSeed(self.0, first))? {
+                        message.fields[index] = value;
+                    }
+                }
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(message)
+    }
+}
+
+// The member of a oneof that is set, if any, along with its index. The oneof starts at the given
+// field index.
+struct OneofSeed(&'static MessageInfo, usize);
+
+impl<'de> DeserializeSeed<'de> for OneofSeed {
+    type Value = Option<(usize, Option<Value>)>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_option(self)
+    }
+}
+
+impl<'de> Visitor<'de> for OneofSeed {
+    type Value = Option<(usize, Option<Value>)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("oneof")
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_enum("", &[], self)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        let (index, member) = data.variant_seed(MemberSeed(self.0, self.1))?;
+        let value = member.newtype_variant_seed(FieldSeed(&self.0.fields[index]))?;
+        Ok(Some((index, value)))
+    }
+}
+
+// Identifies a member of the oneof starting at the given field index, by its position within the
+// oneof or by its name.
+struct MemberSeed(&'static MessageInfo, usize);
+
+impl MemberSeed {
+    fn member<E: de::Error>(&self, index: Option<usize>) -> Result<usize, E> {
+        let oneof_index = self.0.fields[self.1].oneof_index;
+        match index {
+            Some(index) if self.0.fields.get(index).map(|f| f.oneof_index) == Some(oneof_index) => {
+                Ok(index)
+            }
+            _ => Err(de::Error::custom("oneof member not found")),
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for MemberSeed {
+    type Value = usize;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for MemberSeed {
+    type Value = usize;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("oneof member")
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.member(Some(self.1 + v as usize))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.member(v.field_index(self.0).ok())
+    }
+}
+
+// All of the values of a field, or None when the field is absent.
+struct FieldSeed(&'static FieldInfo);
+
+impl<'de> DeserializeSeed<'de> for FieldSeed {
+    type Value = Option<Value>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = match (self.0.cardinality, map_entry(self.0)) {
+            (Cardinality::Repeated, Some((key_info, value_info))) => {
+                deserializer.deserialize_map(MapVisitor(key_info, value_info))?
+            }
+            (Cardinality::Repeated, None) => deserializer.deserialize_seq(ListVisitor(self.0))?,
+            _ => return deserializer.deserialize_option(OptionVisitor(self.0)),
+        };
+        // an empty repeated field is indistinguishable from an absent one
+        Ok(match value {
+            Value::List(list) if list.is_empty() => None,
+            Value::Map(map) if map.is_empty() => None,
+            value => Some(value),
+        })
+    }
+}
+
+struct OptionVisitor(&'static FieldInfo);
+
+impl<'de> Visitor<'de> for OptionVisitor {
+    type Value = Option<Value>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "field {}", self.0.name)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(None)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        ValueSeed(self.0).deserialize(deserializer).map(Some)
+    }
+}
+
+struct ListVisitor(&'static FieldInfo);
+
+impl<'de> Visitor<'de> for ListVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "repeated field {}", self.0.name)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element_seed(ValueSeed(self.0))? {
+            list.push(value);
+        }
+        Ok(Value::List(list))
+    }
+}
+
+struct MapVisitor(&'static FieldInfo, &'static FieldInfo);
+
+impl<'de> Visitor<'de> for MapVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("map field")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut map = super::Map::new();
+        while let Some(key) = access.next_key_seed(KeySeed(self.0))? {
+            let value = access.next_value_seed(ValueSeed(self.1))?;
+            map.insert(key, value);
+        }
+        Ok(Value::Map(map))
+    }
+}
+
+// A single value of a field.
+struct ValueSeed(&'static FieldInfo);
+
+impl<'de> DeserializeSeed<'de> for ValueSeed {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match self.0.ty {
+            Type::Message | Type::Group => {
+                let message_info = self
+                    .0
+                    .message_info()
+                    .ok_or_else(|| de::Error::custom("message descriptor not found"))?;
+                MessageSeed(message_info)
+                    .deserialize(deserializer)
+                    .map(Value::Message)
+            }
+            _ => deserializer.deserialize_any(ValueVisitor(self.0)),
+        }
+    }
+}
+
+struct ValueVisitor(&'static FieldInfo);
+
+impl ValueVisitor {
+    fn number<E>(self, n: Number) -> Result<Value, E>
+    where
+        E: de::Error,
+    {
+        match self.0.ty {
+            Type::Enum => n
+                .as_i64()
+                .and_then(|n| i32::try_from(n).ok())
+                .map(Value::Enum)
+                .ok_or_else(|| E::custom(format!("invalid enum value {}", n))),
+            _ => Ok(Value::Number(n)),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "value of field {}", self.0.name)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.number(v.into())
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.number(v.into())
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.number(v.into())
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.number(v.into())
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(Value::Bytes(v))
+    }
+}
+
+struct KeySeed(&'static FieldInfo);
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = MapKey;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(KeyVisitor(self.0))
+    }
+}
+
+struct KeyVisitor(&'static FieldInfo);
+
+impl<'de> Visitor<'de> for KeyVisitor {
+    type Value = MapKey;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer, bool or string map key")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(MapKey::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match self.0.ty {
+            Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64 => u64::try_from(v)
+                .map(MapKey::Uint)
+                .map_err(|_| E::custom(format!("map key {} out of range", v))),
+            _ => Ok(MapKey::Int(v)),
+        }
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match self.0.ty {
+            Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64 => Ok(MapKey::Uint(v)),
+            _ => i64::try_from(v)
+                .map(MapKey::Int)
+                .map_err(|_| E::custom(format!("map key {} out of range", v))),
+        }
+    }
+
+    // formats such as JSON only have string keys, so non-string keys are parsed from them
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        let invalid = || E::custom(format!("invalid map key {}", v));
+        match self.0.ty {
+            Type::String => Ok(MapKey::String(v.to_string())),
+            Type::Bool => v.parse().map(MapKey::Bool).map_err(|_| invalid()),
+            Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64 => {
+                v.parse().map(MapKey::Uint).map_err(|_| invalid())
+            }
+            _ => v.parse().map(MapKey::Int).map_err(|_| invalid()),
+        }
+    }
+}
@@ -1,10 +1,22 @@
+pub mod message;
 pub mod number;
 
-use std::collections::HashMap;
+use std::{collections::BTreeMap, fmt};
 
+use serde::{
+    de::{self, Visitor},
+    ser::{SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+pub use self::message::DynamicMessage;
 use self::number::Number;
 
-#[derive(Debug, Clone)]
+/// Any protobuf value.
+///
+/// A `Value` serializes without a schema, so messages read by tobu deserialize into a
+/// [`Value::Map`] keyed by field name. Such a map serializes back into the message it came from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Value {
     Bool(bool),
     Number(Number),
@@ -18,6 +30,293 @@ pub enum Value {
 
 pub type Bytes = Vec<u8>;
 pub type Enum = i32;
-pub type Message = Vec<Value>;
+pub type Message = DynamicMessage;
 pub type List = Vec<Value>;
-pub type Map = HashMap<Value, Value>;
+pub type Map = BTreeMap<MapKey, Value>;
+
+// Protobuf only allows integral, bool and string map keys. Keeping floats and messages out of keys
+// gives them a total order, so maps iterate the same way every time.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapKey {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    String(String),
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Value::Bool(v)
+    }
+}
+
+macro_rules! from_number {
+    ($($number_ty:ty)*) => {
+        $(
+            impl From<$number_ty> for Value {
+                fn from(v: $number_ty) -> Self {
+                    Value::Number(v.into())
+                }
+            }
+        )*
+    };
+}
+
+from_number!(i32 i64 u32 u64 f32 f64);
+
+impl From<Number> for Value {
+    fn from(v: Number) -> Self {
+        Value::Number(v)
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::String(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::String(v.to_string())
+    }
+}
+
+impl From<Bytes> for Value {
+    fn from(v: Bytes) -> Self {
+        Value::Bytes(v)
+    }
+}
+
+impl From<DynamicMessage> for Value {
+    fn from(v: DynamicMessage) -> Self {
+        Value::Message(v)
+    }
+}
+
+impl From<List> for Value {
+    fn from(v: List) -> Self {
+        Value::List(v)
+    }
+}
+
+impl From<Map> for Value {
+    fn from(v: Map) -> Self {
+        Value::Map(v)
+    }
+}
+
+impl From<bool> for MapKey {
+    fn from(v: bool) -> Self {
+        MapKey::Bool(v)
+    }
+}
+
+impl From<i32> for MapKey {
+    fn from(v: i32) -> Self {
+        MapKey::Int(v as i64)
+    }
+}
+
+impl From<i64> for MapKey {
+    fn from(v: i64) -> Self {
+        MapKey::Int(v)
+    }
+}
+
+impl From<u32> for MapKey {
+    fn from(v: u32) -> Self {
+        MapKey::Uint(v as u64)
+    }
+}
+
+impl From<u64> for MapKey {
+    fn from(v: u64) -> Self {
+        MapKey::Uint(v)
+    }
+}
+
+impl From<String> for MapKey {
+    fn from(v: String) -> Self {
+        MapKey::String(v)
+    }
+}
+
+impl From<&str> for MapKey {
+    fn from(v: &str) -> Self {
+        MapKey::String(v.to_string())
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Value::Bool(v) => serializer.serialize_bool(*v),
+            Value::Number(v) => v.serialize(serializer),
+            Value::Enum(v) => serializer.serialize_i32(*v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Message(v) => v.serialize(serializer),
+            Value::List(list) => {
+                let mut seq = serializer.serialize_seq(Some(list.len()))?;
+                for value in list {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Map(map) => {
+                let mut entries = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map {
+                    entries.serialize_entry(key, value)?;
+                }
+                entries.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a protobuf value")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+                Ok(Value::Bool(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+                Ok(Value::Number(v.into()))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+                Ok(Value::Number(v.into()))
+            }
+
+            fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+                Ok(Value::Number(v.into()))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+                Ok(Value::String(v.to_string()))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<Value, E> {
+                Ok(Value::String(v))
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Value, E> {
+                Ok(Value::Bytes(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Value, E> {
+                Ok(Value::Bytes(v))
+            }
+
+            fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Deserialize::deserialize(deserializer)
+            }
+
+            fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Deserialize::deserialize(deserializer)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(value) = seq.next_element()? {
+                    list.push(value);
+                }
+                Ok(Value::List(list))
+            }
+
+            fn visit_map<A>(self, mut access: A) -> Result<Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                // absent fields of a message come out as None and are left out of the map
+                let mut map = Map::new();
+                while let Some(key) = access.next_key()? {
+                    if let Some(value) = access.next_value::<Option<Value>>()? {
+                        map.insert(key, value);
+                    }
+                }
+                Ok(Value::Map(map))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+impl Serialize for MapKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            MapKey::Bool(k) => serializer.serialize_bool(*k),
+            MapKey::Int(k) => serializer.serialize_i64(*k),
+            MapKey::Uint(k) => serializer.serialize_u64(*k),
+            MapKey::String(k) => serializer.serialize_str(k),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MapKey {
+    fn deserialize<D>(deserializer: D) -> Result<MapKey, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct MapKeyVisitor;
+
+        impl<'de> Visitor<'de> for MapKeyVisitor {
+            type Value = MapKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer, bool or string map key")
+            }
+
+            fn visit_bool<E>(self, v: bool) -> Result<MapKey, E> {
+                Ok(MapKey::Bool(v))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<MapKey, E> {
+                Ok(MapKey::Int(v))
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<MapKey, E> {
+                Ok(MapKey::Uint(v))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<MapKey, E> {
+                Ok(MapKey::String(v.to_string()))
+            }
+
+            fn visit_string<E>(self, v: String) -> Result<MapKey, E> {
+                Ok(MapKey::String(v))
+            }
+        }
+
+        deserializer.deserialize_any(MapKeyVisitor)
+    }
+}
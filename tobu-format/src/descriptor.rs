@@ -0,0 +1,66 @@
+//! Values protoc writes into descriptors in its own notation, read the same way by the plugin and
+//! by a descriptor pool at runtime.
+
+// Undoes the C escapes protoc uses for bytes defaults.
+pub fn unescape(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut chars = s.bytes().peekable();
+    while let Some(c) = chars.next() {
+        if c != b'\\' {
+            bytes.push(c);
+            continue;
+        }
+        let c = chars.next()?;
+        bytes.push(match c {
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'v' => 0x0b,
+            b'\\' | b'\'' | b'"' | b'?' => c,
+            b'x' => {
+                let mut v = 0u8;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|d| (*d as char).to_digit(16)) {
+                        Some(d) => v = v.wrapping_mul(16).wrapping_add(d as u8),
+                        None => break,
+                    }
+                    chars.next();
+                }
+                v
+            }
+            b'0'..=b'7' => {
+                let mut v = c - b'0';
+                for _ in 0..2 {
+                    match chars.peek().and_then(|d| (*d as char).to_digit(8)) {
+                        Some(d) => v = v.wrapping_mul(8).wrapping_add(d as u8),
+                        None => break,
+                    }
+                    chars.next();
+                }
+                v
+            }
+            _ => return None,
+        });
+    }
+    Some(bytes)
+}
+
+// The lowerCamelCase name protoc uses when the descriptor does not record one.
+pub fn json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut capitalize = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
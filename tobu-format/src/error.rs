@@ -1,3 +1,5 @@
+use crate::field::FieldNumber;
+
 #[derive(thiserror::Error, Debug)]
 pub enum DecodeError {
     #[error("invalid field number {0}")]
@@ -11,4 +13,13 @@ pub enum DecodeError {
 
     #[error("variable length integer overflow")]
     Overflow,
+
+    #[error("end group {1} does not match start group {0}")]
+    MismatchedEndGroup(FieldNumber, FieldNumber),
+
+    #[error("end group {0} without a start group")]
+    UnexpectedEndGroup(FieldNumber),
+
+    #[error("groups or messages nested too deeply")]
+    RecursionLimitExceeded,
 }
@@ -12,8 +12,7 @@ const LAST_RESERVED_NUMBER: i32 = 19999;
 const MAX_VALID_NUMBER: i32 = (1 << 29) - 1;
 
 impl FieldNumber {
-    // TODO: someday we can make this a const fn
-    pub fn new(n: i32) -> Self {
+    pub const fn new(n: i32) -> Self {
         assert!(FieldNumber::valid(n));
         Self(n)
     }
@@ -1,4 +1,6 @@
 #[cfg(test)]
+mod test_descriptor;
+#[cfg(test)]
 mod test_field;
 #[cfg(test)]
 mod test_wire;
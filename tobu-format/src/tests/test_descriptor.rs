@@ -0,0 +1,25 @@
+use crate::descriptor::{json_name, unescape};
+
+#[test]
+fn json_names() {
+    assert_eq!(json_name("snake_case_name"), "snakeCaseName");
+    assert_eq!(json_name("name"), "name");
+    assert_eq!(json_name("field_1"), "field1");
+}
+
+#[test]
+fn unescaped() {
+    assert_eq!(unescape("plain").unwrap(), b"plain");
+    assert_eq!(
+        unescape(r#"\n\r\t\a\b\f\v\\\'\"\?"#).unwrap(),
+        b"\n\r\t\x07\x08\x0c\x0b\\'\"?"
+    );
+    assert_eq!(unescape(r"\0\101\3770").unwrap(), b"\0A\xff0");
+    assert_eq!(unescape(r"\x41\xfg").unwrap(), b"A\x0fg");
+}
+
+#[test]
+fn unescape_invalid() {
+    assert_eq!(unescape(r"\q"), None);
+    assert_eq!(unescape("trailing\\"), None);
+}
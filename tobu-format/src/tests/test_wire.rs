@@ -66,21 +66,31 @@ fn group() {
     let group_num = FieldNumber::try_from(5000).unwrap();
     put_tag(&mut buf, group_num, WireType::StartGroup);
 
+    let mut body = BytesMut::new();
     let num = FieldNumber::try_from(1).unwrap();
-    put_tag(&mut buf, num, WireType::Varint);
+    put_tag(&mut body, num, WireType::Varint);
     let val = 0x123456789;
-    put_varint(&mut buf, val);
+    put_varint(&mut body, val);
+    buf.put_slice(&body);
 
     put_tag(&mut buf, group_num, WireType::EndGroup);
 
     let mut parser = Parser::new(buf.freeze());
+    let group = parser.next().unwrap().unwrap();
     assert_eq!(
-        parser.next().unwrap().unwrap(),
+        group,
         WireField {
             num: group_num,
-            val: FieldValue::StartGroup
+            val: FieldValue::Group(body.freeze())
         }
     );
+    assert!(parser.next().is_none());
+
+    let body = match group.val {
+        FieldValue::Group(body) => body,
+        _ => unreachable!(),
+    };
+    let mut parser = Parser::new(body);
     assert_eq!(
         parser.next().unwrap().unwrap(),
         WireField {
@@ -88,13 +98,7 @@ fn group() {
             val: FieldValue::Varint(val)
         }
     );
-    assert_eq!(
-        parser.next().unwrap().unwrap(),
-        WireField {
-            num: group_num,
-            val: FieldValue::EndGroup
-        }
-    );
+    assert!(parser.next().is_none());
 }
 
 #[test]
@@ -104,33 +108,23 @@ fn group_nested() {
     let num = FieldNumber::try_from(1).unwrap();
     put_tag(&mut buf, num, WireType::StartGroup);
 
+    let mut body = BytesMut::new();
     let nested_num = FieldNumber::try_from(2).unwrap();
-    put_tag(&mut buf, nested_num, WireType::StartGroup);
+    put_tag(&mut body, nested_num, WireType::StartGroup);
+    put_tag(&mut body, nested_num, WireType::EndGroup);
+    buf.put_slice(&body);
 
-    put_tag(&mut buf, nested_num, WireType::EndGroup);
+    put_tag(&mut buf, num, WireType::EndGroup);
 
     let mut parser = Parser::new(buf.freeze());
     assert_eq!(
         parser.next().unwrap().unwrap(),
         WireField {
             num,
-            val: FieldValue::StartGroup
-        }
-    );
-    assert_eq!(
-        parser.next().unwrap().unwrap(),
-        WireField {
-            num: nested_num,
-            val: FieldValue::StartGroup
-        }
-    );
-    assert_eq!(
-        parser.next().unwrap().unwrap(),
-        WireField {
-            num: nested_num,
-            val: FieldValue::EndGroup
+            val: FieldValue::Group(body.freeze())
         }
     );
+    assert!(parser.next().is_none());
 }
 
 #[test]
@@ -147,16 +141,10 @@ fn group_empty() {
         parser.next().unwrap().unwrap(),
         WireField {
             num,
-            val: FieldValue::StartGroup
-        }
-    );
-    assert_eq!(
-        parser.next().unwrap().unwrap(),
-        WireField {
-            num,
-            val: FieldValue::EndGroup
+            val: FieldValue::Group(Bytes::new())
         }
     );
+    assert!(parser.next().is_none());
 }
 
 #[test]
@@ -164,10 +152,13 @@ fn group_denormalized() {
     let mut buf = BytesMut::new();
 
     let num = FieldNumber::try_from(5).unwrap();
-    put_tag(&mut buf, num, WireType::Fixed32);
+    put_tag(&mut buf, num, WireType::StartGroup);
 
+    let mut body = BytesMut::new();
+    put_tag(&mut body, num, WireType::Fixed32);
     let val = 0xf0e1d2c3;
-    put_fixed32(&mut buf, val);
+    put_fixed32(&mut body, val);
+    buf.put_slice(&body);
 
     // manually end group
     buf.put_slice(b"\xac\x80\x80\x00");
@@ -177,16 +168,112 @@ fn group_denormalized() {
         parser.next().unwrap().unwrap(),
         WireField {
             num,
-            val: FieldValue::Fixed32(val)
+            val: FieldValue::Group(body.freeze())
         }
     );
-    assert_eq!(
-        parser.next().unwrap().unwrap(),
-        WireField {
-            num,
-            val: FieldValue::EndGroup
-        }
+    assert!(parser.next().is_none());
+}
+
+#[test]
+fn group_eof() {
+    let mut buf = BytesMut::new();
+
+    let num = FieldNumber::try_from(1).unwrap();
+    put_tag(&mut buf, num, WireType::StartGroup);
+    put_tag(&mut buf, num, WireType::Varint);
+    put_varint(&mut buf, 1);
+
+    assert!(matches!(
+        Parser::new(buf.freeze()).next().unwrap(),
+        Err(DecodeError::Eof)
+    ));
+}
+
+#[test]
+fn group_mismatched_end() {
+    let mut buf = BytesMut::new();
+
+    let num = FieldNumber::try_from(1).unwrap();
+    put_tag(&mut buf, num, WireType::StartGroup);
+
+    let end_num = FieldNumber::try_from(2).unwrap();
+    put_tag(&mut buf, end_num, WireType::EndGroup);
+
+    assert!(matches!(
+        Parser::new(buf.freeze()).next().unwrap(),
+        Err(DecodeError::MismatchedEndGroup(start, end)) if start == num && end == end_num
+    ));
+}
+
+#[test]
+fn group_unexpected_end() {
+    let mut buf = BytesMut::new();
+
+    let num = FieldNumber::try_from(1).unwrap();
+    put_tag(&mut buf, num, WireType::EndGroup);
+
+    assert!(matches!(
+        Parser::new(buf.freeze()).next().unwrap(),
+        Err(DecodeError::UnexpectedEndGroup(n)) if n == num
+    ));
+}
+
+#[test]
+fn group_nested_to_limit() {
+    let mut buf = BytesMut::new();
+
+    let num = FieldNumber::try_from(1).unwrap();
+    for _ in 0..RECURSION_LIMIT {
+        put_tag(&mut buf, num, WireType::StartGroup);
+    }
+    for _ in 0..RECURSION_LIMIT {
+        put_tag(&mut buf, num, WireType::EndGroup);
+    }
+
+    assert!(Parser::new(buf.freeze()).next().unwrap().is_ok());
+}
+
+#[test]
+fn group_nested_too_deeply() {
+    // start group tags for field 1 and nothing else
+    let buf = Bytes::from(vec![0x0b; 1_000_000]);
+
+    assert!(matches!(
+        Parser::new(buf).next().unwrap(),
+        Err(DecodeError::RecursionLimitExceeded)
+    ));
+}
+
+#[test]
+fn field_roundtrip() {
+    let mut buf = BytesMut::new();
+
+    let num = FieldNumber::try_from(3).unwrap();
+    put_tag(&mut buf, num, WireType::Varint);
+    put_varint(&mut buf, 300);
+    put_tag(&mut buf, num, WireType::StartGroup);
+    put_tag(
+        &mut buf,
+        FieldNumber::try_from(1).unwrap(),
+        WireType::Fixed32,
     );
+    put_fixed32(&mut buf, 7);
+    put_tag(&mut buf, num, WireType::EndGroup);
+    put_tag(&mut buf, num, WireType::Bytes);
+    put_bytes(&mut buf, b"hello");
+    put_tag(&mut buf, num, WireType::Fixed64);
+    put_fixed64(&mut buf, 8);
+    let buf = buf.freeze();
+
+    let mut out = BytesMut::new();
+    let mut size = 0;
+    for field in Parser::new(buf.clone()) {
+        let field = field.unwrap();
+        put_field(&mut out, &field);
+        size += size_field(&field);
+    }
+    assert_eq!(out.freeze(), buf);
+    assert_eq!(size, buf.len());
 }
 
 #[test]
@@ -194,11 +281,52 @@ fn group_size() {
     let mut buf = BytesMut::new();
 
     let num = FieldNumber::try_from(5000).unwrap();
+    put_tag(&mut buf, num, WireType::StartGroup);
     put_tag(&mut buf, num, WireType::EndGroup);
 
     assert_eq!(size_group(num, 3), buf.len() + 3);
 }
 
+#[test]
+fn packed() {
+    let mut buf = BytesMut::new();
+    put_varint(&mut buf, 1);
+    put_varint(&mut buf, 300);
+    put_varint(&mut buf, u64::MAX);
+    assert_eq!(
+        parse_packed(buf.freeze(), WireType::Varint).unwrap(),
+        vec![
+            FieldValue::Varint(1),
+            FieldValue::Varint(300),
+            FieldValue::Varint(u64::MAX)
+        ]
+    );
+
+    let mut buf = BytesMut::new();
+    put_fixed32(&mut buf, 7);
+    put_fixed32(&mut buf, u32::MAX);
+    assert_eq!(
+        parse_packed(buf.freeze(), WireType::Fixed32).unwrap(),
+        vec![FieldValue::Fixed32(7), FieldValue::Fixed32(u32::MAX)]
+    );
+
+    assert!(parse_packed(Bytes::new(), WireType::Fixed64)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn packed_eof() {
+    assert!(matches!(
+        parse_packed(Bytes::from_static(&[1, 2, 3]), WireType::Fixed32),
+        Err(DecodeError::Eof)
+    ));
+    assert!(matches!(
+        parse_packed(Bytes::from_static(&[1, 0x80]), WireType::Varint),
+        Err(DecodeError::Eof)
+    ));
+}
+
 #[test]
 fn varint_eof() {
     let values = vec![
@@ -4,6 +4,9 @@ use bytes::{Buf, BufMut, Bytes};
 
 use super::{error::DecodeError, field::FieldNumber};
 
+// How deeply groups and messages may nest before decoding gives up rather than running out of stack.
+pub const RECURSION_LIMIT: usize = 100;
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum WireType {
     Varint = 0,
@@ -52,8 +55,8 @@ pub enum FieldValue {
     Fixed32(u32),
     Fixed64(u64),
     Bytes(Bytes),
-    StartGroup,
-    EndGroup,
+    // The fields between a start group and its matching end group, excluding both tags.
+    Group(Bytes),
 }
 
 pub struct Parser {
@@ -80,18 +83,102 @@ impl Iterator for Parser {
 
 fn parse_next(buf: &mut Bytes) -> Result<WireField, DecodeError> {
     let (num, typ) = parse_tag(buf)?;
-    let val = parse_wire_value(buf, typ)?;
+    let val = parse_wire_value(buf, num, typ, 0)?;
     Ok(WireField { num, val })
 }
 
-fn parse_wire_value(buf: &mut Bytes, typ: WireType) -> Result<FieldValue, DecodeError> {
+// Writes a field back out as it was parsed, which for a group means wrapping its fields in start
+// and end group tags.
+pub fn put_field(buf: &mut impl BufMut, field: &WireField) {
+    match &field.val {
+        FieldValue::Varint(v) => {
+            put_tag(buf, field.num, WireType::Varint);
+            put_varint(buf, *v);
+        }
+        FieldValue::Fixed32(v) => {
+            put_tag(buf, field.num, WireType::Fixed32);
+            put_fixed32(buf, *v);
+        }
+        FieldValue::Fixed64(v) => {
+            put_tag(buf, field.num, WireType::Fixed64);
+            put_fixed64(buf, *v);
+        }
+        FieldValue::Bytes(v) => {
+            put_tag(buf, field.num, WireType::Bytes);
+            put_bytes(buf, v);
+        }
+        FieldValue::Group(v) => {
+            put_tag(buf, field.num, WireType::StartGroup);
+            buf.put_slice(v);
+            put_tag(buf, field.num, WireType::EndGroup);
+        }
+    }
+}
+
+pub fn size_field(field: &WireField) -> usize {
+    match &field.val {
+        FieldValue::Varint(v) => size_tag(field.num) + size_varint(*v),
+        FieldValue::Fixed32(_) => size_tag(field.num) + size_fixed32(),
+        FieldValue::Fixed64(_) => size_tag(field.num) + size_fixed64(),
+        FieldValue::Bytes(v) => size_tag(field.num) + size_bytes(v.len()),
+        FieldValue::Group(v) => size_group(field.num, v.len()),
+    }
+}
+
+// The depth is the number of groups the value is in.
+fn parse_wire_value(
+    buf: &mut Bytes,
+    num: FieldNumber,
+    typ: WireType,
+    depth: usize,
+) -> Result<FieldValue, DecodeError> {
     match typ {
         WireType::Varint => Ok(FieldValue::Varint(parse_varint(buf)?)),
         WireType::Fixed32 => Ok(FieldValue::Fixed32(parse_fixed32(buf)?)),
         WireType::Fixed64 => Ok(FieldValue::Fixed64(parse_fixed64(buf)?)),
         WireType::Bytes => Ok(FieldValue::Bytes(parse_bytes(buf)?)),
-        WireType::StartGroup => Ok(FieldValue::StartGroup),
-        WireType::EndGroup => Ok(FieldValue::EndGroup),
+        WireType::StartGroup => Ok(FieldValue::Group(parse_group(buf, num, depth + 1)?)),
+        WireType::EndGroup => Err(DecodeError::UnexpectedEndGroup(num)),
+    }
+}
+
+// Parses the elements of a packed repeated field, which are written back to back without tags.
+pub fn parse_packed(mut buf: Bytes, typ: WireType) -> Result<Vec<FieldValue>, DecodeError> {
+    let mut values = Vec::new();
+    while !buf.is_empty() {
+        let val = match typ {
+            WireType::Varint => FieldValue::Varint(parse_varint(&mut buf)?),
+            WireType::Fixed32 => FieldValue::Fixed32(parse_fixed32(&mut buf)?),
+            WireType::Fixed64 => FieldValue::Fixed64(parse_fixed64(&mut buf)?),
+            _ => return Err(DecodeError::InvalidWireType(typ.get())),
+        };
+        values.push(val);
+    }
+    Ok(values)
+}
+
+// Groups are not length delimited, so the fields of the group are parsed, including any nested
+// groups, until the matching end group is found.
+fn parse_group(buf: &mut Bytes, num: FieldNumber, depth: usize) -> Result<Bytes, DecodeError> {
+    if depth > RECURSION_LIMIT {
+        return Err(DecodeError::RecursionLimitExceeded);
+    }
+
+    let group = buf.clone();
+    loop {
+        if buf.is_empty() {
+            return Err(DecodeError::Eof);
+        }
+
+        let len = group.len() - buf.len();
+        let (field_num, typ) = parse_tag(buf)?;
+        if typ == WireType::EndGroup {
+            if field_num != num {
+                return Err(DecodeError::MismatchedEndGroup(num, field_num));
+            }
+            return Ok(group.slice(..len));
+        }
+        parse_wire_value(buf, field_num, typ, depth)?;
     }
 }
 
@@ -190,7 +277,7 @@ fn parse_bytes(buf: &mut Bytes) -> Result<Bytes, DecodeError> {
     if len > buf.len() {
         Err(DecodeError::Eof)
     } else {
-        Ok(buf.split_to(len as usize))
+        Ok(buf.split_to(len))
     }
 }
 
@@ -199,7 +286,8 @@ pub fn size_bytes(num: usize) -> usize {
 }
 
 pub fn size_group(num: FieldNumber, len: usize) -> usize {
-    size_tag(num) + len
+    // start group tag + len + end group tag
+    2 * size_tag(num) + len
 }
 
 fn decode_tag(varint: u64) -> Result<(FieldNumber, WireType), DecodeError> {
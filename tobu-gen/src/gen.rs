@@ -1,44 +1,436 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Literal, TokenStream};
 use quote::{format_ident, quote};
 
-use crate::process::{Cardinality, Enum, Field, FieldType, File, Message};
+use crate::{
+    params::Params,
+    process::{
+        Cardinality, DefaultValue, Enum, Extension, Field, FieldType, File, Message, Oneof, TypeRef,
+    },
+};
 
-pub fn gen_file(file: &File) -> TokenStream {
-    let use_crates = file.dependencies.iter().map(|mods| {
-        let mods = mods.iter().map(|m| format_ident!("{}", m));
-        quote! { use crate::#(#mods)::*::*; }
-    });
-    let messages = file.messages.iter().map(gen_message);
+// Types refer to each other by their full path, so nothing is imported.
+pub fn gen_file(file: &File, params: &Params) -> TokenStream {
+    let messages = file.messages.iter().map(|m| gen_message(m, params));
+    let enums = file.enums.iter().map(|e| gen_enum(e, params));
+    let extensions = file.extensions.iter().map(|e| gen_extension(e, params));
 
     quote! {
         #![allow(dead_code)]
         #![allow(clippy::enum_variant_names)]
-        #(#use_crates)*
 
         #(#messages)*
+
+        #(#enums)*
+
+        #(#extensions)*
     }
 }
 
-fn gen_message(message: &Message) -> TokenStream {
+// The name of the static descriptor of a message or enum.
+fn info_ident(name: &str) -> Ident {
+    use heck::ShoutySnakeCase;
+    format_ident!("{}", name.to_shouty_snake_case())
+}
+
+fn gen_type_path(type_ref: &TypeRef) -> TokenStream {
+    gen_path(&type_ref.module, format_ident!("{}", type_ref.name))
+}
+
+fn gen_info_path(type_ref: &TypeRef) -> TokenStream {
+    gen_path(&type_ref.module, info_ident(&type_ref.name))
+}
+
+// An empty first segment of the module stands for a leading ::.
+fn gen_path(module: &[String], name: Ident) -> TokenStream {
+    let module = module
+        .iter()
+        .map(|m| (!m.is_empty()).then(|| format_ident!("{}", m)));
+    quote! { #(#module::)* #name }
+}
+
+fn gen_message(message: &Message, params: &Params) -> TokenStream {
+    let info = gen_message_info(message, params);
+    // map entries are held in a HashMap, so they only need a descriptor
+    if message.is_map_entry {
+        return info;
+    }
+
     let name = format_ident!("{}", message.name);
-    let fields = message.fields.iter().map(gen_field);
-    let nested = message.nested.iter().map(gen_message);
-    let enums = message.enums.iter().map(gen_enum);
+    // A oneof takes the place of its first member, the rest of its members go in its enum.
+    let fields =
+        message
+            .fields
+            .iter()
+            .enumerate()
+            .filter_map(|(i, field)| match field.oneof_index {
+                Some(index) => {
+                    let first = message.fields[..i]
+                        .iter()
+                        .all(|f| f.oneof_index != Some(index));
+                    first.then(|| gen_oneof_field(&message.oneofs[index], &message.module))
+                }
+                None => Some(gen_field(field, message.is_proto3, params)),
+            });
+    let oneofs = message.oneofs.iter().enumerate().map(|(index, oneof)| {
+        let members = message
+            .fields
+            .iter()
+            .filter(|f| f.oneof_index == Some(index));
+        gen_oneof(oneof, members, params)
+    });
+    let nested = message.nested.iter().map(|m| gen_message(m, params));
+    let enums = message.enums.iter().map(|e| gen_enum(e, params));
+    let extensions = message.extensions.iter().map(|e| gen_extension(e, params));
+    let info_name = info_ident(&message.name);
+    let getters = message
+        .fields
+        .iter()
+        .filter_map(|field| gen_getter(field, message.is_proto3, params))
+        .collect::<Vec<_>>();
+    let getters = (!getters.is_empty()).then(|| {
+        quote! {
+            impl #name {
+                #(#getters)*
+            }
+        }
+    });
+
+    // the types that belong to the message go in a module of its own
+    let scoped = oneofs
+        .chain(nested)
+        .chain(enums)
+        .chain(extensions)
+        .collect::<Vec<_>>();
+    let module = (!scoped.is_empty()).then(|| {
+        let module = format_ident!("{}", message.module);
+        quote! {
+            pub mod #module {
+                #(#scoped)*
+            }
+        }
+    });
 
+    let derive = gen_derive(quote! { Debug, Clone, Default, PartialEq }, params);
     quote! {
-        #[derive(Debug, Clone, Default, PartialEq)]
+        #derive
         pub struct #name {
-            #(pub #fields),*
+            #(pub #fields,)*
+            #[serde(rename = "_unknown_fields", default)]
+            pub unknown_fields: tobu::unknown::UnknownFields,
         }
 
-        #(#nested)*
+        #getters
 
-        #(#enums)*
+        #info
+
+        impl tobu::Message for #name {
+            fn info() -> &'static tobu::info::MessageInfo {
+                &#info_name
+            }
+        }
+
+        #module
     }
 }
 
-fn gen_enum(num: &Enum) -> TokenStream {
+fn gen_message_info(message: &Message, params: &Params) -> TokenStream {
+    let info = info_ident(&message.name);
+    let full_name = &message.full_name;
+    let fields = message.fields.iter().map(|f| gen_field_info(f, params));
+    let oneofs = message.oneofs.iter().map(|oneof| {
+        let name = &oneof.name;
+        quote! { tobu::info::OneofInfo { name: #name } }
+    });
+    let syntax = gen_syntax(message.is_proto3);
+    let is_map = message.is_map_entry;
+
+    quote! {
+        pub static #info: tobu::info::MessageInfo = tobu::info::MessageInfo {
+            name: #full_name,
+            fields: &[#(#fields),*],
+            oneofs: &[#(#oneofs),*],
+            syntax: #syntax,
+            is_map: #is_map,
+        };
+    }
+}
+
+fn gen_field_info(field: &Field, params: &Params) -> TokenStream {
+    let name = &field.proto_name;
+    let number = field.number;
+    let cardinality = match field.cardinality {
+        Cardinality::Optional => quote! { Optional },
+        Cardinality::Required => quote! { Required },
+        Cardinality::Repeated => quote! { Repeated },
+    };
+    let ty = match field.ty {
+        FieldType::Double => quote! { Double },
+        FieldType::Float => quote! { Float },
+        FieldType::Int64 => quote! { Int64 },
+        FieldType::UInt64 => quote! { Uint64 },
+        FieldType::Int32 => quote! { Int32 },
+        FieldType::Fixed64 => quote! { Fixed64 },
+        FieldType::Fixed32 => quote! { Fixed32 },
+        FieldType::Bool => quote! { Bool },
+        FieldType::String => quote! { String },
+        FieldType::Group(_) => quote! { Group },
+        FieldType::Message(_) => quote! { Message },
+        FieldType::Bytes => quote! { Bytes },
+        FieldType::UInt32 => quote! { Uint32 },
+        FieldType::Enum(_) => quote! { Enum },
+        FieldType::SFixed32 => quote! { SFixed32 },
+        FieldType::SFixed64 => quote! { SFixed64 },
+        FieldType::SInt32 => quote! { SInt32 },
+        FieldType::SInt64 => quote! { SInt64 },
+    };
+    let type_name = field.type_name.as_deref().unwrap_or_default();
+    let json_name = &field.json_name;
+    let packed = field.packed;
+    let proto3_optional = field.proto3_optional;
+    let default_value = match &field.default_value {
+        Some(default) => {
+            let default = gen_default_info(default, &field.ty, params);
+            quote! { Some(tobu::info::DefaultValue::#default) }
+        }
+        None => quote! { None },
+    };
+    let oneof_index = match field.oneof_index {
+        Some(index) => {
+            let index = index as i32;
+            quote! { Some(#index) }
+        }
+        None => quote! { None },
+    };
+    let (message_info, enum_info) = match &field.ty {
+        FieldType::Group(type_ref) | FieldType::Message(type_ref) => {
+            let info = gen_info_path(type_ref);
+            (
+                quote! { Some(tobu::info::MessageRef::Static(&#info)) },
+                quote! { None },
+            )
+        }
+        FieldType::Enum(type_ref) => {
+            let info = gen_info_path(type_ref);
+            (quote! { None }, quote! { Some(&#info) })
+        }
+        _ => (quote! { None }, quote! { None }),
+    };
+
+    quote! {
+        tobu::info::FieldInfo {
+            name: #name,
+            number: tobu::info::FieldNumber::new(#number),
+            cardinality: tobu::info::Cardinality::#cardinality,
+            ty: tobu::info::Type::#ty,
+            type_name: #type_name,
+            json_name: #json_name,
+            packed: #packed,
+            oneof_index: #oneof_index,
+            proto3_optional: #proto3_optional,
+            default_value: #default_value,
+            message_info: #message_info,
+            enum_info: #enum_info,
+        }
+    }
+}
+
+fn gen_extension(ext: &Extension, params: &Params) -> TokenStream {
+    // kept apart from the descriptor of a message or enum of the same name
+    let info = info_ident(&format!("{}Extension", ext.name));
+    let extendee = &ext.extendee;
+    let field = gen_field_info(&ext.field, params);
+
+    quote! {
+        pub static #info: tobu::info::ExtensionInfo = tobu::info::ExtensionInfo {
+            extendee: #extendee,
+            field: #field,
+        };
+    }
+}
+
+// Singular scalar fields held in an Option get a getter that returns the default when they are not
+// set.
+fn gen_getter(field: &Field, is_proto3: bool, params: &Params) -> Option<TokenStream> {
+    if field.cardinality == Cardinality::Repeated
+        || field.oneof_index.is_some()
+        || (is_proto3 && !field.proto3_optional)
+        || matches!(field.ty, FieldType::Message(_) | FieldType::Group(_))
+    {
+        return None;
+    }
+
+    let name = format_ident!("{}", field.name);
+    let ty = gen_field_type(&Cardinality::Required, &field.ty, field.boxed, params);
+    let (ty, value) = match field.ty {
+        FieldType::String => (quote! { &str }, quote! { self.#name.as_deref() }),
+        FieldType::Bytes => (quote! { &[u8] }, quote! { self.#name.as_deref() }),
+        FieldType::Enum(_) => (ty, quote! { self.#name.clone() }),
+        _ => (ty, quote! { self.#name }),
+    };
+    let default = match &field.default_value {
+        Some(default) => {
+            let default = gen_default(default, &field.ty, params);
+            quote! { unwrap_or(#default) }
+        }
+        None => quote! { unwrap_or_default() },
+    };
+
+    Some(quote! {
+        pub fn #name(&self) -> #ty {
+            #value.#default
+        }
+    })
+}
+
+// A default as a value of the type of the field.
+fn gen_default(default: &DefaultValue, ty: &FieldType, params: &Params) -> TokenStream {
+    match (default, ty) {
+        (DefaultValue::Bool(v), _) => quote! { #v },
+        (DefaultValue::Int(v), FieldType::Int32 | FieldType::SInt32 | FieldType::SFixed32) => {
+            let v = Literal::i32_suffixed(*v as i32);
+            quote! { #v }
+        }
+        (DefaultValue::Int(v), _) => quote! { #v },
+        (DefaultValue::Uint(v), FieldType::UInt32 | FieldType::Fixed32) => {
+            let v = Literal::u32_suffixed(*v as u32);
+            quote! { #v }
+        }
+        (DefaultValue::Uint(v), _) => quote! { #v },
+        (DefaultValue::Float(v), FieldType::Float) => gen_float(*v, true),
+        (DefaultValue::Float(v), _) => gen_float(*v, false),
+        (DefaultValue::String(v), _) => quote! { #v },
+        (DefaultValue::Bytes(v), _) => {
+            let v = Literal::byte_string(v);
+            quote! { &#v[..] }
+        }
+        (DefaultValue::Enum(v), _) => {
+            let ty = gen_field_type(&Cardinality::Required, ty, false, params);
+            let v = format_ident!("{}", v);
+            quote! { #ty::#v }
+        }
+    }
+}
+
+fn gen_default_info(default: &DefaultValue, ty: &FieldType, params: &Params) -> TokenStream {
+    match default {
+        DefaultValue::Bool(v) => quote! { Bool(#v) },
+        DefaultValue::Int(v) => quote! { Int(#v) },
+        DefaultValue::Uint(v) => quote! { Uint(#v) },
+        DefaultValue::Float(v) => {
+            let v = gen_float(*v, false);
+            quote! { Float(#v) }
+        }
+        DefaultValue::String(v) => quote! { String(#v) },
+        DefaultValue::Bytes(v) => {
+            let v = Literal::byte_string(v);
+            quote! { Bytes(#v) }
+        }
+        // closed enums have explicit discriminants
+        DefaultValue::Enum(v) => {
+            let ty = gen_field_type(&Cardinality::Required, ty, false, params);
+            let v = format_ident!("{}", v);
+            quote! { Enum(#ty::#v as i32) }
+        }
+    }
+}
+
+// Literals cannot spell out infinities or NaN.
+fn gen_float(v: f64, is_f32: bool) -> TokenStream {
+    let ty = if is_f32 {
+        quote! { f32 }
+    } else {
+        quote! { f64 }
+    };
+    if v.is_nan() {
+        quote! { #ty::NAN }
+    } else if v == f64::INFINITY {
+        quote! { #ty::INFINITY }
+    } else if v == f64::NEG_INFINITY {
+        quote! { #ty::NEG_INFINITY }
+    } else if is_f32 {
+        let v = Literal::f32_suffixed(v as f32);
+        quote! { #v }
+    } else {
+        let v = Literal::f64_suffixed(v);
+        quote! { #v }
+    }
+}
+
+// Derives the traits along with the serde traits, which have to be told where serde is when it is
+// not a dependency of its own.
+fn gen_derive(traits: TokenStream, params: &Params) -> TokenStream {
+    match &params.serde_path {
+        Some(serde) => {
+            let path = serde.to_string();
+            quote! {
+                #[derive(#traits, #serde::Serialize, #serde::Deserialize)]
+                #[serde(crate = #path)]
+            }
+        }
+        None => quote! { #[derive(#traits, serde::Serialize, serde::Deserialize)] },
+    }
+}
+
+fn gen_syntax(is_proto3: bool) -> TokenStream {
+    if is_proto3 {
+        quote! { tobu::info::Syntax::Proto3 }
+    } else {
+        quote! { tobu::info::Syntax::Proto2 }
+    }
+}
+
+fn gen_oneof_field(oneof: &Oneof, module: &str) -> TokenStream {
+    let name = format_ident!("{}", oneof.name);
+    let module = format_ident!("{}", module);
+    let enum_name = format_ident!("{}", oneof.enum_name);
+    quote! {
+        #name: Option<#module::#enum_name>
+    }
+}
+
+fn gen_oneof<'a>(
+    oneof: &Oneof,
+    members: impl Iterator<Item = &'a Field>,
+    params: &Params,
+) -> TokenStream {
+    let name = format_ident!("{}", oneof.enum_name);
+    let variants = members.map(|field| {
+        use heck::CamelCase;
+        let name = format_ident!("{}", field.name.trim_start_matches("r#").to_camel_case());
+        let ty = gen_field_type(&Cardinality::Required, &field.ty, field.boxed, params);
+        quote! { #name(#ty) }
+    });
+
+    let derive = gen_derive(quote! { Debug, Clone, PartialEq }, params);
+    quote! {
+        #derive
+        pub enum #name {
+            #(#variants),*
+        }
+    }
+}
+
+fn gen_enum(num: &Enum, params: &Params) -> TokenStream {
     let name = format_ident!("{}", num.name);
+    let derive = gen_derive(quote! { Debug, Clone, Default, PartialEq }, params);
+    let info = gen_enum_info(num);
+    // the first value is the default
+    let default = (0..num.values.len()).map(|i| (i == 0).then(|| quote! { #[default] }));
+    if num.is_open {
+        // An open enum holds values it does not know in a variant after the known values, which
+        // rules out explicit discriminants.
+        let values = num.values.iter().map(|v| format_ident!("{}", v.name));
+        return quote! {
+            #derive
+            pub enum #name {
+                #(#default #values,)*
+                Unrecognized(i32)
+            }
+
+            #info
+        };
+    }
+
     let values = num.values.iter().map(|v| {
         let name = format_ident!("{}", v.name);
         let number = v.number;
@@ -46,65 +438,98 @@ fn gen_enum(num: &Enum) -> TokenStream {
     });
 
     quote! {
-        #[derive(Debug, Clone, PartialEq)]
+        #derive
         #[repr(i32)]
         pub enum #name {
-            #(#values),*
+            #(#default #values),*
         }
+
+        #info
     }
 }
 
-fn gen_field(field: &Field) -> TokenStream {
-    let name = format_ident!("{}", field.name);
-    let ty = gen_field_type(&field.cardinality, &field.ty);
+fn gen_enum_info(num: &Enum) -> TokenStream {
+    let info = info_ident(&num.name);
+    let full_name = &num.full_name;
+    let values = num.values.iter().map(|v| {
+        let name = &v.proto_name;
+        let number = v.number;
+        quote! { tobu::info::EnumValue { name: #name, number: #number } }
+    });
+    let syntax = gen_syntax(num.is_open);
+
     quote! {
-        #name: #ty
+        pub static #info: tobu::info::EnumInfo = tobu::info::EnumInfo {
+            name: #full_name,
+            value: &[#(#values),*],
+            syntax: #syntax,
+        };
     }
 }
 
-fn gen_field_type(cardinality: &Cardinality, ty: &FieldType) -> TokenStream {
-    let mut tokens = TokenStream::new();
-    match cardinality {
-        Cardinality::Optional => {
-            if *ty != FieldType::Bytes {
-                tokens.extend(quote! { Option< });
+fn gen_field(field: &Field, is_proto3: bool, params: &Params) -> TokenStream {
+    let name = format_ident!("{}", field.name);
+    // A proto3 scalar field that is not declared optional cannot tell being unset from holding
+    // its default, so it is held without an Option. A required field is held in an Option, so
+    // that a message missing it fails to encode unless partial.
+    let cardinality = match (&field.cardinality, &field.ty) {
+        (Cardinality::Required, _) => &Cardinality::Optional,
+        (Cardinality::Optional, FieldType::Message(_) | FieldType::Group(_)) => &field.cardinality,
+        (Cardinality::Optional, _) if is_proto3 && !field.proto3_optional => &Cardinality::Required,
+        (cardinality, _) => cardinality,
+    };
+    let ty = match &field.map {
+        Some((key, value)) => {
+            let key = gen_field_type(&Cardinality::Required, key, false, params);
+            let value = gen_field_type(&Cardinality::Required, value, false, params);
+            if params.btree_map {
+                quote! { std::collections::BTreeMap<#key, #value> }
+            } else {
+                quote! { std::collections::HashMap<#key, #value> }
             }
         }
-        Cardinality::Required => {}
-        Cardinality::Repeated => tokens.extend(quote! { Vec< }),
+        None => gen_field_type(cardinality, &field.ty, field.boxed, params),
     };
-
-    match ty {
-        FieldType::Group(name) | FieldType::Message(name) | FieldType::Enum(name) => {
-            let name = format_ident!("{}", name);
-            tokens.extend(quote! { #name });
-        }
-        FieldType::Double => tokens.extend(quote! { f64 }),
-        FieldType::Float => tokens.extend(quote! { f32 }),
-        FieldType::Int64 => tokens.extend(quote! { i64 }),
-        FieldType::UInt64 => tokens.extend(quote! { u64 }),
-        FieldType::Int32 => tokens.extend(quote! { i32 }),
-        FieldType::Fixed64 => tokens.extend(quote! { u64 }),
-        FieldType::Fixed32 => tokens.extend(quote! { i32 }),
-        FieldType::Bool => tokens.extend(quote! { bool}),
-        FieldType::String => tokens.extend(quote! { String }),
-        FieldType::Bytes => tokens.extend(quote! { Vec<u8> }),
-        FieldType::UInt32 => tokens.extend(quote! { u32 }),
-        FieldType::SFixed32 => tokens.extend(quote! { i32 }),
-        FieldType::SFixed64 => tokens.extend(quote! { i64 }),
-        FieldType::SInt32 => tokens.extend(quote! { i32 }),
-        FieldType::SInt64 => tokens.extend(quote! { i64 }),
+    quote! {
+        #name: #ty
     }
+}
 
-    match cardinality {
-        Cardinality::Optional => {
-            if *ty != FieldType::Bytes {
-                tokens.extend(quote! { > });
-            }
+fn gen_field_type(
+    cardinality: &Cardinality,
+    ty: &FieldType,
+    boxed: bool,
+    params: &Params,
+) -> TokenStream {
+    let ty = match ty {
+        FieldType::Group(type_ref) | FieldType::Message(type_ref) | FieldType::Enum(type_ref) => {
+            gen_type_path(type_ref)
         }
-        Cardinality::Repeated => tokens.extend(quote! { > }),
-        Cardinality::Required => {}
-    }
+        FieldType::Double => quote! { f64 },
+        FieldType::Float => quote! { f32 },
+        FieldType::Int64 => quote! { i64 },
+        FieldType::UInt64 => quote! { u64 },
+        FieldType::Int32 => quote! { i32 },
+        FieldType::Fixed64 => quote! { u64 },
+        FieldType::Fixed32 => quote! { u32 },
+        FieldType::Bool => quote! { bool },
+        FieldType::String => quote! { String },
+        FieldType::Bytes => params.bytes.clone(),
+        FieldType::UInt32 => quote! { u32 },
+        FieldType::SFixed32 => quote! { i32 },
+        FieldType::SFixed64 => quote! { i64 },
+        FieldType::SInt32 => quote! { i32 },
+        FieldType::SInt64 => quote! { i64 },
+    };
+    let ty = if boxed {
+        quote! { Box<#ty> }
+    } else {
+        ty
+    };
 
-    tokens
+    match cardinality {
+        Cardinality::Optional => quote! { Option<#ty> },
+        Cardinality::Required => ty,
+        Cardinality::Repeated => quote! { Vec<#ty> },
+    }
 }
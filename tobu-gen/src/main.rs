@@ -1,6 +1,8 @@
 mod gen;
+mod params;
 mod parse;
 mod process;
+mod response;
 mod google {
     mod protobuf {
         mod descriptor;
@@ -10,30 +12,56 @@ mod google {
     }
 }
 
-use std::{
-    fs,
-    io::{self, Read},
-};
+#[cfg(test)]
+mod tests;
+
+use std::io::{self, Read, Write};
 
 use anyhow::Result;
 use bytes::Bytes;
 use clap::App;
+use params::parse_params;
 use parse::parse_request;
 use process::process_files;
+use response::{
+    encode_response, CodeGeneratorResponse, CodeGeneratorResponseFile, FEATURE_PROTO3_OPTIONAL,
+};
 
 use crate::gen::gen_file;
 
+// Reads a CodeGeneratorRequest from stdin and writes a CodeGeneratorResponse to stdout, as protoc
+// expects of a plugin. Errors in the request are reported to protoc through the response.
 fn main() -> Result<()> {
     App::new("tobu-gen-rust").version("0.1.0").get_matches();
     let mut buf = Vec::new();
     io::stdin().read_to_end(&mut buf)?;
-    let req = parse_request(Bytes::from(buf))?;
-    let files = process_files(&req.proto_file)?;
-    for file in &files {
-        if let Some(parent) = file.path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        fs::write(&file.path, format!("{}", gen_file(file)))?;
-    }
+    let res = match generate(Bytes::from(buf)) {
+        Ok(file) => CodeGeneratorResponse {
+            file,
+            ..Default::default()
+        },
+        Err(err) => CodeGeneratorResponse {
+            error: Some(format!("{:#}", err)),
+            ..Default::default()
+        },
+    };
+    io::stdout().write_all(&encode_response(&CodeGeneratorResponse {
+        supported_features: Some(FEATURE_PROTO3_OPTIONAL),
+        ..res
+    }))?;
     Ok(())
 }
+
+fn generate(buf: Bytes) -> Result<Vec<CodeGeneratorResponseFile>> {
+    let req = parse_request(buf)?;
+    let params = parse_params(req.parameter.as_deref().unwrap_or_default())?;
+    let files = process_files(&req.proto_file, &req.file_to_generate, &params)?;
+    Ok(files
+        .iter()
+        .map(|file| CodeGeneratorResponseFile {
+            name: Some(file.name.clone()),
+            content: Some(gen_file(file, &params).to_string()),
+            ..Default::default()
+        })
+        .collect())
+}
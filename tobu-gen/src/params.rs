@@ -0,0 +1,102 @@
+use anyhow::{anyhow, bail, Result};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+// The options passed to the plugin as a comma separated list of key=value pairs, such as
+// --tobu_opt=bytes=bytes::Bytes,map=btree. extern_path may be given more than once.
+#[derive(Debug)]
+pub struct Params {
+    // the type bytes fields are held in
+    pub bytes: TokenStream,
+    // whether map fields are held in a BTreeMap rather than a HashMap
+    pub btree_map: bool,
+    // the path serde is reachable at, when it is not a dependency of its own
+    pub serde_path: Option<TokenStream>,
+    // the module path generated files are placed under, starting with the crate that holds them
+    pub module_root: Vec<String>,
+    // protobuf packages or types, with a leading dot, that are not generated but found at a path
+    // of their own, such as .google.protobuf=::tobu_types
+    pub extern_paths: Vec<(String, Vec<String>)>,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            bytes: quote! { Vec<u8> },
+            btree_map: false,
+            serde_path: None,
+            module_root: vec!["crate".to_owned()],
+            // the well-known types come prebuilt in tobu-types
+            extern_paths: vec![(
+                ".google.protobuf".to_owned(),
+                vec![String::new(), "tobu_types".to_owned()],
+            )],
+        }
+    }
+}
+
+pub fn parse_params(parameter: &str) -> Result<Params> {
+    let mut params = Params::default();
+    for param in parameter.split(',').filter(|p| !p.is_empty()) {
+        let (key, value) = param
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Parameter {} expected to be key=value", param))?;
+        match key.trim() {
+            "bytes" => params.bytes = parse_path(key, value)?,
+            "map" => {
+                params.btree_map = match value.trim() {
+                    "btree" => true,
+                    "hash" => false,
+                    _ => bail!("Parameter map expected to be btree or hash: {}", value),
+                }
+            }
+            "serde_path" => params.serde_path = Some(parse_path(key, value)?),
+            "module_root" => {
+                params.module_root = value.trim().split("::").map(|m| m.to_owned()).collect();
+                if !params.module_root.iter().all(|m| is_ident(m)) {
+                    bail!(
+                        "Parameter module_root expected to be a module path: {}",
+                        value
+                    )
+                }
+            }
+            "extern_path" => {
+                let (proto, rust) = value.split_once('=').ok_or_else(|| {
+                    anyhow!(
+                        "Parameter extern_path expected to be .proto=rust: {}",
+                        value
+                    )
+                })?;
+                let proto = proto.trim();
+                let rust: Vec<_> = rust.trim().split("::").map(|m| m.to_owned()).collect();
+                // a path may start with ::
+                let absolute = rust.len() > 1 && rust[0].is_empty();
+                if !proto.starts_with('.') || !rust[absolute as usize..].iter().all(|m| is_ident(m))
+                {
+                    bail!(
+                        "Parameter extern_path expected to be .proto=rust: {}",
+                        value
+                    )
+                }
+                params.extern_paths.push((proto.to_owned(), rust));
+            }
+            _ => bail!("Unknown parameter {}", key),
+        }
+    }
+    Ok(params)
+}
+
+fn parse_path(key: &str, value: &str) -> Result<TokenStream> {
+    match value.parse::<TokenStream>() {
+        Ok(path) if !path.is_empty() => Ok(path),
+        _ => bail!("Parameter {} expected to be a Rust path: {}", key, value),
+    }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
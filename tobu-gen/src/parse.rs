@@ -18,8 +18,11 @@ pub struct FileDescriptorProto {
     pub package: Option<String>,
     pub dependency: Vec<String>,
     pub message_type: Vec<DescriptorProto>,
+    pub enum_type: Vec<EnumDescriptorProto>,
+    pub extension: Vec<FieldDescriptorProto>,
     pub options: Option<FileOptions>,
     pub source_code_info: Option<SourceCodeInfo>,
+    pub syntax: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -47,20 +50,15 @@ pub struct SourceCodeInfoLocation {
     pub leading_detached_comments: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 #[repr(i32)]
 pub enum FileDescriptorOptimizeMode {
+    #[default]
     Speed = 1,
     CodeSize = 2,
     LiteRuntime = 3,
 }
 
-impl Default for FileDescriptorOptimizeMode {
-    fn default() -> Self {
-        FileDescriptorOptimizeMode::Speed
-    }
-}
-
 impl TryFrom<i32> for FileDescriptorOptimizeMode {
     type Error = i32;
 
@@ -88,16 +86,32 @@ pub struct DescriptorProto {
     pub field: Vec<FieldDescriptorProto>,
     pub nested_type: Vec<DescriptorProto>,
     pub enum_type: Vec<EnumDescriptorProto>,
+    pub extension: Vec<FieldDescriptorProto>,
     pub extension_range: Vec<DescriptorProtoExtensionRange>,
+    pub oneof_decl: Vec<OneofDescriptorProto>,
+    pub options: Option<MessageOptions>,
     pub reserved_range: Vec<DescriptorProtoReservedRange>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MessageOptions {
+    pub message_set_wire_format: Option<bool>,
+    pub no_standard_descriptor_accessor: Option<bool>,
+    pub deprecated: Option<bool>,
+    pub map_entry: Option<bool>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct DescriptorProtoReservedRange {
     pub start: Option<i32>,
     pub end: Option<i32>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OneofDescriptorProto {
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct EnumDescriptorProto {
     pub name: Option<String>,
@@ -119,6 +133,7 @@ pub struct DescriptorProtoExtensionRange {
 #[derive(Debug, Clone, Default, PartialEq)]
 pub struct FieldDescriptorProto {
     pub name: Option<String>,
+    pub extendee: Option<String>,
     pub number: Option<i32>,
     pub label: Option<FieldDescriptorProtoLabel>,
     pub r#type: Option<FieldDescriptorProtoType>,
@@ -127,6 +142,7 @@ pub struct FieldDescriptorProto {
     pub oneof_index: Option<i32>,
     pub json_name: Option<String>,
     pub options: Option<FieldOptions>,
+    pub proto3_optional: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -135,20 +151,15 @@ pub struct FieldOptions {
     pub packed: Option<bool>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 #[repr(i32)]
 pub enum FieldDescriptorProtoLabel {
+    #[default]
     Optional = 1,
     Required = 2,
     Repeated = 3,
 }
 
-impl Default for FieldDescriptorProtoLabel {
-    fn default() -> Self {
-        FieldDescriptorProtoLabel::Optional
-    }
-}
-
 impl TryFrom<i32> for FieldDescriptorProtoLabel {
     type Error = i32;
 
@@ -162,9 +173,10 @@ impl TryFrom<i32> for FieldDescriptorProtoLabel {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 #[repr(i32)]
 pub enum FieldDescriptorProtoType {
+    #[default]
     Double = 1,
     Float = 2,
     Int64 = 3,
@@ -185,12 +197,6 @@ pub enum FieldDescriptorProtoType {
     SInt64 = 18,
 }
 
-impl Default for FieldDescriptorProtoType {
-    fn default() -> Self {
-        FieldDescriptorProtoType::Double
-    }
-}
-
 impl TryFrom<i32> for FieldDescriptorProtoType {
     type Error = i32;
 
@@ -224,6 +230,7 @@ pub fn parse_request(b: Bytes) -> Result<CodeGeneratorRequest> {
     for r in Parser::new(b) {
         match r.map(|f| (f.num.get(), f.val))? {
             (1, FieldValue::Bytes(b)) => req.file_to_generate.push(String::from_utf8(b.to_vec())?),
+            (2, FieldValue::Bytes(b)) => req.parameter = Some(String::from_utf8(b.to_vec())?),
             (3, FieldValue::Bytes(b)) => req.compiler_version = Some(parse_version(b)?),
             (15, FieldValue::Bytes(b)) => req.proto_file.push(parse_file(b)?),
             (num, val) => bail!("parse_req {:?}, {:?}", num, val),
@@ -255,8 +262,11 @@ fn parse_file(b: Bytes) -> Result<FileDescriptorProto> {
             (2, FieldValue::Bytes(b)) => file.package = Some(String::from_utf8(b.to_vec())?),
             (3, FieldValue::Bytes(b)) => file.dependency.push(String::from_utf8(b.to_vec())?),
             (4, FieldValue::Bytes(b)) => file.message_type.push(parse_message(b)?),
+            (5, FieldValue::Bytes(b)) => file.enum_type.push(parse_enum(b)?),
+            (7, FieldValue::Bytes(b)) => file.extension.push(parse_field(b)?),
             (8, FieldValue::Bytes(b)) => file.options = Some(parse_file_options(b)?),
             (9, FieldValue::Bytes(b)) => file.source_code_info = Some(parse_source_code_info(b)?),
+            (12, FieldValue::Bytes(b)) => file.syntax = Some(String::from_utf8(b.to_vec())?),
             (num, val) => bail!("parse_file {:?}, {:?}", num, val),
         };
     }
@@ -272,6 +282,9 @@ fn parse_message(b: Bytes) -> Result<DescriptorProto> {
             (3, FieldValue::Bytes(b)) => message.nested_type.push(parse_message(b)?),
             (4, FieldValue::Bytes(b)) => message.enum_type.push(parse_enum(b)?),
             (5, FieldValue::Bytes(b)) => message.extension_range.push(parse_extension_range(b)?),
+            (6, FieldValue::Bytes(b)) => message.extension.push(parse_field(b)?),
+            (7, FieldValue::Bytes(b)) => message.options = Some(parse_message_options(b)?),
+            (8, FieldValue::Bytes(b)) => message.oneof_decl.push(parse_oneof(b)?),
             (9, FieldValue::Bytes(b)) => message.reserved_range.push(parse_reserved_range(b)?),
             (num, val) => bail!("parse_message {:?}, {:?}", num, val),
         };
@@ -284,6 +297,7 @@ fn parse_field(b: Bytes) -> Result<FieldDescriptorProto> {
     for r in Parser::new(b) {
         match r.map(|f| (f.num.get(), f.val))? {
             (1, FieldValue::Bytes(b)) => field.name = Some(String::from_utf8(b.to_vec())?),
+            (2, FieldValue::Bytes(b)) => field.extendee = Some(String::from_utf8(b.to_vec())?),
             (3, FieldValue::Varint(i)) => field.number = Some(i32::try_from(i)?),
             (4, FieldValue::Varint(i)) => {
                 field.label = Some(
@@ -300,7 +314,9 @@ fn parse_field(b: Bytes) -> Result<FieldDescriptorProto> {
             (6, FieldValue::Bytes(b)) => field.type_name = Some(String::from_utf8(b.to_vec())?),
             (7, FieldValue::Bytes(b)) => field.default_value = Some(String::from_utf8(b.to_vec())?),
             (8, FieldValue::Bytes(b)) => field.options = Some(parse_field_options(b)?),
+            (9, FieldValue::Varint(i)) => field.oneof_index = Some(i32::try_from(i)?),
             (10, FieldValue::Bytes(b)) => field.json_name = Some(String::from_utf8(b.to_vec())?),
+            (17, FieldValue::Varint(i)) => field.proto3_optional = Some(i != 0),
             (num, val) => bail!("parse_field {:?}, {:?}", num, val),
         };
     }
@@ -319,6 +335,17 @@ fn parse_extension_range(b: Bytes) -> Result<DescriptorProtoExtensionRange> {
     Ok(range)
 }
 
+fn parse_oneof(b: Bytes) -> Result<OneofDescriptorProto> {
+    let mut oneof = OneofDescriptorProto::default();
+    for r in Parser::new(b) {
+        match r.map(|f| (f.num.get(), f.val))? {
+            (1, FieldValue::Bytes(b)) => oneof.name = Some(String::from_utf8(b.to_vec())?),
+            (num, val) => bail!("parse_oneof {:?}, {:?}", num, val),
+        };
+    }
+    Ok(oneof)
+}
+
 fn parse_enum(b: Bytes) -> Result<EnumDescriptorProto> {
     let mut enumeration = EnumDescriptorProto::default();
     for r in Parser::new(b) {
@@ -343,6 +370,20 @@ fn parse_enum_value(b: Bytes) -> Result<EnumValueDescriptorProto> {
     Ok(value)
 }
 
+fn parse_message_options(b: Bytes) -> Result<MessageOptions> {
+    let mut options = MessageOptions::default();
+    for r in Parser::new(b) {
+        match r.map(|f| (f.num.get(), f.val))? {
+            (1, FieldValue::Varint(i)) => options.message_set_wire_format = Some(i != 0),
+            (2, FieldValue::Varint(i)) => options.no_standard_descriptor_accessor = Some(i != 0),
+            (3, FieldValue::Varint(i)) => options.deprecated = Some(i != 0),
+            (7, FieldValue::Varint(i)) => options.map_entry = Some(i != 0),
+            (num, val) => bail!("parse_message_options {:?}, {:?}", num, val),
+        };
+    }
+    Ok(options)
+}
+
 fn parse_field_options(b: Bytes) -> Result<FieldOptions> {
     let mut options = FieldOptions::default();
     for r in Parser::new(b) {
@@ -1,47 +1,108 @@
-use std::path::PathBuf;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::{anyhow, bail, Result};
+use tobu_format::descriptor::{json_name, unescape};
 
-use crate::parse::{
-    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FieldDescriptorProtoLabel,
-    FieldDescriptorProtoType, FileDescriptorProto,
+use crate::{
+    params::Params,
+    parse::{
+        DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FieldDescriptorProtoLabel,
+        FieldDescriptorProtoType, FileDescriptorProto,
+    },
 };
 
 #[derive(Debug)]
 pub struct File {
-    pub path: PathBuf,
-    pub dependencies: Vec<Vec<String>>,
+    // the name of the generated file, relative to the output directory, which holds every file of
+    // a package
+    pub name: String,
     pub messages: Vec<Message>,
+    pub enums: Vec<Enum>,
+    pub extensions: Vec<Extension>,
 }
 
 #[derive(Debug)]
 pub struct Message {
     pub name: String,
+    // the snake_case module that holds the nested types and oneofs of the message
+    pub module: String,
+    // the fully qualified protobuf name, without a leading dot
+    pub full_name: String,
     pub nested: Vec<Message>,
     pub enums: Vec<Enum>,
     pub fields: Vec<Field>,
+    pub extensions: Vec<Extension>,
+    pub oneofs: Vec<Oneof>,
+    pub is_map_entry: bool,
+    pub is_proto3: bool,
+}
+
+#[derive(Debug)]
+pub struct Oneof {
+    pub name: String,
+    // the enum holding the members, declared in the module of the message
+    pub enum_name: String,
 }
+
 #[derive(Debug)]
 pub struct Enum {
     pub name: String,
+    pub full_name: String,
     pub values: Vec<EnumValue>,
+    // proto3 enums are open and keep values they do not know
+    pub is_open: bool,
 }
 
 #[derive(Debug)]
 pub struct EnumValue {
     pub name: String,
+    pub proto_name: String,
     pub number: i32,
 }
 
 #[derive(Debug)]
 pub struct Field {
     pub name: String,
+    pub proto_name: String,
+    pub json_name: String,
+    pub number: i32,
     pub cardinality: Cardinality,
     pub ty: FieldType,
-    pub default_value: Option<String>,
+    // the type of a message, group or enum field as the descriptor names it
+    pub type_name: Option<String>,
+    pub default_value: Option<DefaultValue>,
+    pub oneof_index: Option<usize>,
+    // a proto3 field declared optional, held as an Option like a proto2 field
+    pub proto3_optional: bool,
+    pub packed: bool,
+    // the key and value types of a map field
+    pub map: Option<(FieldType, FieldType)>,
+    // a message field that leads back to the message it is in, which is boxed so the message has
+    // a size
+    pub boxed: bool,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
+pub struct Extension {
+    pub name: String,
+    // the fully qualified protobuf name of the extended message, without a leading dot
+    pub extendee: String,
+    pub field: Field,
+}
+
+// Where the Rust type generated for a message or enum is found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeRef {
+    // the module path, starting with the crate that holds it or an empty segment for a leading ::
+    pub module: Vec<String>,
+    pub name: String,
+}
+
+// The Rust type of every message and enum in a request, by fully qualified protobuf name with a
+// leading dot, as field descriptors name them.
+type Types = HashMap<String, TypeRef>;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum FieldType {
     Double,
     Float,
@@ -52,17 +113,40 @@ pub enum FieldType {
     Fixed32,
     Bool,
     String,
-    Group(String),
-    Message(String),
+    Group(TypeRef),
+    Message(TypeRef),
     Bytes,
     UInt32,
-    Enum(String),
+    Enum(TypeRef),
     SFixed32,
     SFixed64,
     SInt32,
     SInt64,
 }
 
+impl FieldType {
+    // Only scalar numeric types may be packed.
+    fn is_packable(&self) -> bool {
+        !matches!(
+            self,
+            FieldType::String | FieldType::Bytes | FieldType::Group(_) | FieldType::Message(_)
+        )
+    }
+}
+
+// The value declared with [default = ...], parsed according to the type of the field.
+#[derive(Debug, PartialEq)]
+pub enum DefaultValue {
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    // the name of the variant
+    Enum(String),
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Cardinality {
     Optional,
@@ -70,91 +154,340 @@ pub enum Cardinality {
     Repeated,
 }
 
-pub fn process_files(files: &[FileDescriptorProto]) -> Result<Vec<File>> {
-    files.iter().map(process_file).collect()
+// Only the files protoc asks for are generated, but the types they refer to may be declared in any
+// of the files.
+pub fn process_files(
+    files: &[FileDescriptorProto],
+    file_to_generate: &[String],
+    params: &Params,
+) -> Result<Vec<File>> {
+    let types = collect_types(files, params);
+    let mut generated: Vec<File> = Vec::new();
+    for file in files {
+        if !file
+            .name
+            .as_ref()
+            .is_some_and(|name| file_to_generate.contains(name))
+        {
+            continue;
+        }
+        let file = process_file(file, &types)?;
+        match generated.iter_mut().find(|f| f.name == file.name) {
+            Some(package) => {
+                package.messages.extend(file.messages);
+                package.enums.extend(file.enums);
+                package.extensions.extend(file.extensions);
+            }
+            None => generated.push(file),
+        }
+    }
+    Ok(generated)
 }
 
-fn process_file(file: &FileDescriptorProto) -> Result<File> {
-    let name = file
-        .name
-        .as_ref()
-        .ok_or_else(|| anyhow!("File name expected: {:?}", file))?;
-
+fn process_file(file: &FileDescriptorProto, types: &Types) -> Result<File> {
     let package = file
         .package
         .as_ref()
         .ok_or_else(|| anyhow!("File package expected: {:?}", file))?;
+    let is_proto3 = file.syntax.as_deref() == Some("proto3");
+    let enums = file
+        .enum_type
+        .iter()
+        .map(|num| process_enum(num, package, is_proto3))
+        .collect::<Result<Vec<_>>>()?;
+    let extensions = file
+        .extension
+        .iter()
+        .map(|ext| process_extension(ext, &[], is_proto3, types))
+        .collect::<Result<Vec<_>>>()?;
+    let mut messages = process_messages(&file.message_type, package, is_proto3, types)?;
+    box_recursive_fields(&mut messages);
     Ok(File {
-        path: process_path(name, package),
-        dependencies: process_dependencies(&file.dependency),
-        messages: process_messages(&file.message_type)?,
+        name: process_name(package),
+        messages,
+        enums,
+        extensions,
     })
 }
 
-fn process_path(name: &str, package: &str) -> PathBuf {
-    let mut p = PathBuf::new();
-    p.push("src");
-    for dir in package.split('.') {
-        p.push(dir);
+// The message types each message holds in its singular fields and oneofs, by fully qualified
+// protobuf name with a leading dot. Repeated and map fields are on the heap already.
+type Held = HashMap<String, Vec<String>>;
+
+// Files cannot import each other both ways, so a message can only lead back to itself through
+// messages of its own file.
+fn box_recursive_fields(messages: &mut [Message]) {
+    let mut held = Held::new();
+    collect_held(messages, &mut held);
+    set_boxed(messages, &held);
+}
+
+fn collect_held(messages: &[Message], held: &mut Held) {
+    for msg in messages {
+        let types = msg
+            .fields
+            .iter()
+            .filter(|f| is_held(f))
+            .filter_map(|f| f.type_name.clone())
+            .collect();
+        held.insert(format!(".{}", msg.full_name), types);
+        collect_held(&msg.nested, held);
+    }
+}
+
+fn set_boxed(messages: &mut [Message], held: &Held) {
+    for msg in messages {
+        let full_name = format!(".{}", msg.full_name);
+        for field in &mut msg.fields {
+            field.boxed = is_held(field)
+                && field
+                    .type_name
+                    .as_deref()
+                    .is_some_and(|ty| leads_to(ty, &full_name, held));
+        }
+        set_boxed(&mut msg.nested, held);
+    }
+}
+
+fn is_held(field: &Field) -> bool {
+    field.cardinality != Cardinality::Repeated
+        && matches!(field.ty, FieldType::Message(_) | FieldType::Group(_))
+}
+
+// Whether a message holds another, itself included, through any number of messages in between.
+fn leads_to(from: &str, to: &str, held: &Held) -> bool {
+    let mut seen = HashSet::new();
+    let mut pending = vec![from];
+    while let Some(name) = pending.pop() {
+        if name == to {
+            return true;
+        }
+        if seen.insert(name) {
+            pending.extend(held.get(name).into_iter().flatten().map(String::as_str));
+        }
+    }
+    false
+}
+
+// protoc expects file names with / as the separator whatever the platform.
+fn process_name(package: &str) -> String {
+    format!("{}.rs", package.replace('.', "/"))
+}
+
+fn collect_types(files: &[FileDescriptorProto], params: &Params) -> Types {
+    let mut types = Types::new();
+    for file in files {
+        let package = file.package.as_deref().unwrap_or_default();
+        let mut scope: Vec<&str> = package.split('.').filter(|p| !p.is_empty()).collect();
+        let package_len = scope.len();
+        for msg in &file.message_type {
+            collect_message_types(msg, &mut scope, package_len, params, &mut types);
+        }
+        for num in &file.enum_type {
+            collect_type(num.name.as_deref(), &scope, package_len, params, &mut types);
+        }
+    }
+    types
+}
+
+// The scope starts with the package, followed by the messages the message is nested in.
+fn collect_message_types<'a>(
+    msg: &'a DescriptorProto,
+    scope: &mut Vec<&'a str>,
+    package_len: usize,
+    params: &Params,
+    types: &mut Types,
+) {
+    collect_type(msg.name.as_deref(), scope, package_len, params, types);
+    let Some(name) = msg.name.as_deref() else {
+        return;
+    };
+    scope.push(name);
+    for nested in &msg.nested_type {
+        collect_message_types(nested, scope, package_len, params, types);
+    }
+    for num in &msg.enum_type {
+        collect_type(num.name.as_deref(), scope, package_len, params, types);
     }
-    // split/rsplit will always return an iterator with something in it
-    p.push(
-        name.rsplit('/')
-            .next()
-            .unwrap_or_default()
-            .split('.')
-            .next()
-            .unwrap_or_default(),
-    );
-    p.set_extension("rs");
-    p
-}
-
-fn process_dependencies(deps: &[String]) -> Vec<Vec<String>> {
-    deps.iter()
-        .map(|dep| {
-            dep.trim_end_matches(".proto")
-                .split('/')
-                .map(|s| s.to_owned())
-                .collect()
+    scope.pop();
+}
+
+// A type is found under the module root by its package, then in the module of each message it is
+// nested in, unless an extern path takes its place.
+fn collect_type(
+    name: Option<&str>,
+    scope: &[&str],
+    package_len: usize,
+    params: &Params,
+    types: &mut Types,
+) {
+    use heck::SnakeCase;
+    let Some(name) = name else {
+        return;
+    };
+    let full_name = format!(".{}", qualify(&scope.join("."), name));
+    // the longest extern path wins
+    let extern_path = params
+        .extern_paths
+        .iter()
+        .filter(|(proto, _)| {
+            full_name == *proto
+                || full_name
+                    .strip_prefix(proto.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
         })
-        .collect()
+        .max_by_key(|(proto, _)| proto.len());
+    let type_ref = match extern_path {
+        // the type itself
+        Some((proto, rust)) if *proto == full_name => TypeRef {
+            module: rust[..rust.len() - 1].to_vec(),
+            name: rust[rust.len() - 1].clone(),
+        },
+        Some((proto, rust)) => {
+            let rest: Vec<&str> = full_name[proto.len() + 1..].split('.').collect();
+            let (name, modules) = rest.split_last().unwrap_or((&"", &[]));
+            // past the package the extern path names a message, whose nested types are in a module
+            // of its own
+            let package = scope[..package_len].join(".");
+            let mut module = rust.clone();
+            if proto.len() > package.len() + 1 {
+                if let Some(last) = module.last_mut() {
+                    *last = rust_ident(&last.to_snake_case());
+                }
+            }
+            TypeRef {
+                module: module
+                    .into_iter()
+                    .chain(modules.iter().map(|m| rust_ident(&m.to_snake_case())))
+                    .collect(),
+                name: name.to_string(),
+            }
+        }
+        None => TypeRef {
+            module: params
+                .module_root
+                .iter()
+                .cloned()
+                .chain(scope[..package_len].iter().map(|p| rust_ident(p)))
+                .chain(
+                    scope[package_len..]
+                        .iter()
+                        .map(|m| rust_ident(&m.to_snake_case())),
+                )
+                .collect(),
+            name: name.to_string(),
+        },
+    };
+    types.insert(full_name, type_ref);
 }
 
-fn process_messages(msgs: &[DescriptorProto]) -> Result<Vec<Message>> {
-    msgs.iter().map(|msg| process_message(msg, "")).collect()
+// Rust keywords are used as raw identifiers, save for those that cannot be, which get an
+// underscore.
+fn rust_ident(name: &str) -> String {
+    match name {
+        "crate" | "self" | "super" | "Self" => format!("{}_", name),
+        "as" | "async" | "await" | "break" | "const" | "continue" | "dyn" | "else" | "enum"
+        | "extern" | "false" | "fn" | "for" | "if" | "impl" | "in" | "let" | "loop" | "match"
+        | "mod" | "move" | "mut" | "pub" | "ref" | "return" | "static" | "struct" | "trait"
+        | "true" | "type" | "unsafe" | "use" | "where" | "while" | "abstract" | "become"
+        | "box" | "do" | "final" | "gen" | "macro" | "override" | "priv" | "try" | "typeof"
+        | "unsized" | "virtual" | "yield" => format!("r#{}", name),
+        _ => name.to_string(),
+    }
 }
 
-fn process_message(msg: &DescriptorProto, base_name: &str) -> Result<Message> {
-    let name = base_name.to_string()
-        + msg
-            .name
-            .as_ref()
-            .ok_or_else(|| anyhow!("message name required {:#?}", msg))?;
+fn process_messages(
+    msgs: &[DescriptorProto],
+    package: &str,
+    is_proto3: bool,
+    types: &Types,
+) -> Result<Vec<Message>> {
+    msgs.iter()
+        .map(|msg| process_message(msg, package, is_proto3, types))
+        .collect()
+}
+
+fn process_message(
+    msg: &DescriptorProto,
+    scope: &str,
+    is_proto3: bool,
+    types: &Types,
+) -> Result<Message> {
+    use heck::SnakeCase;
+    let name = msg
+        .name
+        .as_ref()
+        .ok_or_else(|| anyhow!("message name required {:#?}", msg))?
+        .clone();
+    let module = rust_ident(&name.to_snake_case());
+    let full_name = qualify(scope, &name);
     let nested = msg
         .nested_type
         .iter()
-        .map(|n| process_message(n, &name))
+        .map(|n| process_message(n, &full_name, is_proto3, types))
         .collect::<Result<Vec<_>>>()?;
     let enums = msg
         .enum_type
         .iter()
-        .map(|num| process_enum(num, &name))
+        .map(|num| process_enum(num, &full_name, is_proto3))
         .collect::<Result<Vec<_>>>()?;
     let fields = msg
         .field
         .iter()
-        .map(|f| process_field(f, &name, &nested, &enums))
+        .map(|f| process_field(f, &nested, is_proto3, types))
+        .collect::<Result<Vec<_>>>()?;
+    let extensions = msg
+        .extension
+        .iter()
+        .map(|ext| process_extension(ext, &nested, is_proto3, types))
+        .collect::<Result<Vec<_>>>()?;
+    // protoc declares synthetic oneofs after every other oneof, so leaving them out does not
+    // disturb the indexes of the rest
+    let oneofs = msg
+        .oneof_decl
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| fields.iter().any(|f| f.oneof_index == Some(*index)))
+        .map(|(_, oneof)| {
+            use heck::CamelCase;
+            let oneof_name = oneof
+                .name
+                .as_ref()
+                .ok_or_else(|| anyhow!("oneof name required {:#?}", msg))?;
+            Ok(Oneof {
+                name: oneof_name.clone(),
+                enum_name: oneof_name.to_camel_case(),
+            })
+        })
         .collect::<Result<Vec<_>>>()?;
+    let is_map_entry = msg
+        .options
+        .as_ref()
+        .and_then(|o| o.map_entry)
+        .unwrap_or(false);
     Ok(Message {
         name,
+        module,
+        full_name,
         nested,
         enums,
         fields,
+        extensions,
+        oneofs,
+        is_map_entry,
+        is_proto3,
     })
 }
 
-fn process_enum(num: &EnumDescriptorProto, base_name: &str) -> Result<Enum> {
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+fn process_enum(num: &EnumDescriptorProto, scope: &str, is_open: bool) -> Result<Enum> {
     let name = num
         .name
         .as_ref()
@@ -163,37 +496,82 @@ fn process_enum(num: &EnumDescriptorProto, base_name: &str) -> Result<Enum> {
         .value
         .iter()
         .map(|val| {
-            use heck::CamelCase;
-            let value_name = val
+            let proto_name = val
                 .name
                 .as_ref()
                 .ok_or_else(|| anyhow!("enum value name required {:#?}", num))?
-                .to_camel_case();
-            let name = value_name.trim_start_matches(name).to_string();
+                .clone();
+            let name = variant_name(name, &proto_name);
             let number = val
                 .number
                 .ok_or_else(|| anyhow!("enum value number required {:#?}", num))?;
-            Ok(EnumValue { name, number })
+            Ok(EnumValue {
+                name,
+                proto_name,
+                number,
+            })
         })
         .collect::<Result<Vec<_>>>()?;
-    let name = base_name.to_string() + name;
-    Ok(Enum { name, values })
+    Ok(Enum {
+        name: name.clone(),
+        full_name: qualify(scope, name),
+        values,
+        is_open,
+    })
+}
+
+// The name of an enum value in CamelCase without the name of the enum in front, unless that leaves
+// nothing that could start a name, as for NULL_VALUE in enum NullValue.
+fn variant_name(enum_name: &str, value_name: &str) -> String {
+    use heck::CamelCase;
+    let name = value_name.to_camel_case();
+    match name.strip_prefix(enum_name) {
+        Some(rest) if rest.starts_with(|c: char| c.is_ascii_alphabetic()) => rest.to_string(),
+        _ => name,
+    }
+}
+
+// An extension declared in a message goes in the module of the message, like a nested message.
+fn process_extension(
+    ext: &FieldDescriptorProto,
+    nested: &[Message],
+    is_proto3: bool,
+    types: &Types,
+) -> Result<Extension> {
+    use heck::CamelCase;
+    let field = process_field(ext, nested, is_proto3, types)?;
+    let extendee = ext
+        .extendee
+        .as_ref()
+        .ok_or_else(|| anyhow!("extendee required {:#?}", ext))?
+        .trim_start_matches('.')
+        .to_string();
+    Ok(Extension {
+        name: field.proto_name.to_camel_case(),
+        extendee,
+        field,
+    })
 }
 
 fn process_field(
     field: &FieldDescriptorProto,
-    base_name: &str,
     nested: &[Message],
-    enums: &[Enum],
+    is_proto3: bool,
+    types: &Types,
 ) -> Result<Field> {
-    let mut name = field
+    let proto_name = field
         .name
         .as_ref()
         .ok_or_else(|| anyhow!("field name required {:#?}", field))?
         .clone();
-    if name == "type" {
-        name = "r#type".to_string()
-    }
+    let name = rust_ident(&proto_name);
+    let number = field
+        .number
+        .ok_or_else(|| anyhow!("field number required {:#?}", field))?;
+    let json_name = field
+        .json_name
+        .clone()
+        .unwrap_or_else(|| json_name(&proto_name));
     let cardinality = match field
         .label
         .as_ref()
@@ -203,75 +581,120 @@ fn process_field(
         FieldDescriptorProtoLabel::Required => Cardinality::Required,
         FieldDescriptorProtoLabel::Repeated => Cardinality::Repeated,
     };
-    let type_name = field
-        .type_name
-        .as_ref()
-        .map(|name| base_name.to_string() + name.rsplit('.').next().unwrap_or_default());
-
-    let nested_names = nested.iter().map(|m| &m.name);
-    let enum_names = enums.iter().map(|e| &e.name);
-    let ty = if nested_names
-        .chain(enum_names)
-        .any(|name| type_name == Some(name.clone()))
-    {
-        process_field_type(field, base_name)?
-    } else {
-        process_field_type(field, "")?
-    };
+    let ty = process_field_type(field, types)?;
 
     let default_value = field
         .default_value
         .as_ref()
-        .map(|v| process_default_value(v, &ty))
+        .map(|v| process_default_value(v, &ty, field.type_name.as_deref()))
+        .transpose()?;
+
+    // proto3 optional fields are members of a synthetic oneof, which is left out
+    let proto3_optional = field.proto3_optional.unwrap_or(false);
+    let oneof_index = field
+        .oneof_index
+        .filter(|_| !proto3_optional)
+        .map(usize::try_from)
         .transpose()?;
 
+    // repeated scalar numeric fields are packed by default in proto3
+    let packed = cardinality == Cardinality::Repeated
+        && ty.is_packable()
+        && field
+            .options
+            .as_ref()
+            .and_then(|o| o.packed)
+            .unwrap_or(is_proto3);
+
+    // a map field is a repeated field of a nested map entry message
+    let map = match &ty {
+        FieldType::Message(_) if cardinality == Cardinality::Repeated => nested
+            .iter()
+            .find(|m| {
+                m.is_map_entry
+                    && field
+                        .type_name
+                        .as_deref()
+                        .map(|n| n.trim_start_matches('.'))
+                        == Some(m.full_name.as_str())
+            })
+            .map(|entry| match entry.fields.as_slice() {
+                [key, value] => Ok((key.ty.clone(), value.ty.clone())),
+                _ => bail!("map entry {} must have a key and a value", entry.full_name),
+            })
+            .transpose()?,
+        _ => None,
+    };
+
     Ok(Field {
         name,
+        proto_name,
+        json_name,
+        number,
         cardinality,
         ty,
+        type_name: field.type_name.clone(),
         default_value,
+        oneof_index,
+        proto3_optional,
+        packed,
+        map,
+        boxed: false,
     })
 }
 
-fn process_default_value(default: &str, ty: &FieldType) -> Result<String> {
-    match (default, ty) {
-        (default, FieldType::Enum(name)) => {
-            use heck::CamelCase;
-            Ok(format!(
-                "{}::{}",
-                default,
-                default.to_camel_case().trim_start_matches(name)
-            ))
+fn process_default_value(
+    default: &str,
+    ty: &FieldType,
+    type_name: Option<&str>,
+) -> Result<DefaultValue> {
+    let value = match ty {
+        FieldType::Bool => default.parse().map(DefaultValue::Bool).ok(),
+        FieldType::Int32 | FieldType::SInt32 | FieldType::SFixed32 => default
+            .parse::<i32>()
+            .map(|v| DefaultValue::Int(v.into()))
+            .ok(),
+        FieldType::Int64 | FieldType::SInt64 | FieldType::SFixed64 => {
+            default.parse().map(DefaultValue::Int).ok()
         }
-        (default, FieldType::Group(group)) => bail!(
-            "Default value({}) for FieldType::Group({}) not supported.",
-            default,
-            group
-        ),
-        (default, FieldType::Message(message)) => bail!(
-            "Default value({}) for FieldType::Message({}) not supported.",
+        FieldType::UInt32 | FieldType::Fixed32 => default
+            .parse::<u32>()
+            .map(|v| DefaultValue::Uint(v.into()))
+            .ok(),
+        FieldType::UInt64 | FieldType::Fixed64 => default.parse().map(DefaultValue::Uint).ok(),
+        FieldType::Float => default
+            .parse::<f32>()
+            .map(|v| DefaultValue::Float(v.into()))
+            .ok(),
+        FieldType::Double => default.parse().map(DefaultValue::Float).ok(),
+        FieldType::String => Some(DefaultValue::String(default.to_string())),
+        FieldType::Bytes => unescape(default).map(DefaultValue::Bytes),
+        FieldType::Enum(_) => {
+            let enum_name = type_name
+                .and_then(|name| name.rsplit('.').next())
+                .unwrap_or_default();
+            Some(DefaultValue::Enum(variant_name(enum_name, default)))
+        }
+        FieldType::Group(_) | FieldType::Message(_) => bail!(
+            "Default value({}) for {} not supported.",
             default,
-            message
+            type_name.unwrap_or_default()
         ),
-        (default, _) => Ok(default.to_string()),
-    }
+    };
+    value.ok_or_else(|| anyhow!("Invalid default value({}) for {:?}", default, ty))
 }
 
-fn process_field_type(field: &FieldDescriptorProto, base_name: &str) -> Result<FieldType> {
+fn process_field_type(field: &FieldDescriptorProto, types: &Types) -> Result<FieldType> {
     let ty = match field
         .r#type
         .as_ref()
         .ok_or_else(|| anyhow!("field type required {:#?}", field))?
     {
-        FieldDescriptorProtoType::Group => {
-            FieldType::Group(process_field_type_name(field, base_name)?)
-        }
+        FieldDescriptorProtoType::Group => FieldType::Group(process_field_type_ref(field, types)?),
         FieldDescriptorProtoType::Message => {
-            FieldType::Message(process_field_type_name(field, base_name)?)
-        }
-        FieldDescriptorProtoType::Enum => {
-            FieldType::Enum(process_field_type_name(field, base_name)?)
+            FieldType::Message(process_field_type_ref(field, types)?)
         }
+        FieldDescriptorProtoType::Enum => FieldType::Enum(process_field_type_ref(field, types)?),
         FieldDescriptorProtoType::Double => FieldType::Double,
         FieldDescriptorProtoType::Float => FieldType::Float,
         FieldDescriptorProtoType::Int64 => FieldType::Int64,
@@ -291,10 +714,13 @@ fn process_field_type(field: &FieldDescriptorProto, base_name: &str) -> Result<F
     Ok(ty)
 }
 
-fn process_field_type_name(field: &FieldDescriptorProto, base_name: &str) -> Result<String> {
+fn process_field_type_ref(field: &FieldDescriptorProto, types: &Types) -> Result<TypeRef> {
     let type_name = field
         .type_name
         .as_ref()
         .ok_or_else(|| anyhow!("field type name required {:#?}", field))?;
-    Ok(base_name.to_string() + type_name.rsplit('.').next().unwrap_or_default())
+    types
+        .get(type_name)
+        .cloned()
+        .ok_or_else(|| anyhow!("type {} not found", type_name))
 }
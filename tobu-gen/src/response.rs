@@ -0,0 +1,57 @@
+use bytes::BufMut;
+use tobu_format::{
+    field::FieldNumber,
+    wire::{put_bytes, put_tag, put_varint, WireType},
+};
+
+// CodeGeneratorResponse.Feature
+pub const FEATURE_PROTO3_OPTIONAL: u64 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeGeneratorResponse {
+    pub error: Option<String>,
+    pub supported_features: Option<u64>,
+    pub file: Vec<CodeGeneratorResponseFile>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CodeGeneratorResponseFile {
+    pub name: Option<String>,
+    pub insertion_point: Option<String>,
+    pub content: Option<String>,
+}
+
+pub fn encode_response(res: &CodeGeneratorResponse) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Some(error) = &res.error {
+        put_string(&mut buf, 1, error);
+    }
+    if let Some(features) = res.supported_features {
+        put_tag(&mut buf, FieldNumber::new(2), WireType::Varint);
+        put_varint(&mut buf, features);
+    }
+    for file in &res.file {
+        put_tag(&mut buf, FieldNumber::new(15), WireType::Bytes);
+        put_bytes(&mut buf, &encode_file(file));
+    }
+    buf
+}
+
+fn encode_file(file: &CodeGeneratorResponseFile) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if let Some(name) = &file.name {
+        put_string(&mut buf, 1, name);
+    }
+    if let Some(insertion_point) = &file.insertion_point {
+        put_string(&mut buf, 2, insertion_point);
+    }
+    if let Some(content) = &file.content {
+        put_string(&mut buf, 15, content);
+    }
+    buf
+}
+
+fn put_string(buf: &mut impl BufMut, num: i32, s: &str) {
+    put_tag(buf, FieldNumber::new(num), WireType::Bytes);
+    put_bytes(buf, s.as_bytes());
+}
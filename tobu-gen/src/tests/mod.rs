@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod test_gen;
+#[cfg(test)]
+mod test_params;
+#[cfg(test)]
+mod test_process;
+#[cfg(test)]
+mod test_response;
+
+use crate::parse::{
+    DescriptorProto, FieldDescriptorProto, FieldDescriptorProtoLabel, FieldDescriptorProtoType,
+    FileDescriptorProto,
+};
+
+fn file(name: &str, package: &str, message_type: Vec<DescriptorProto>) -> FileDescriptorProto {
+    FileDescriptorProto {
+        name: Some(name.to_string()),
+        package: Some(package.to_string()),
+        message_type,
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    }
+}
+
+fn message(name: &str, field: Vec<FieldDescriptorProto>) -> DescriptorProto {
+    DescriptorProto {
+        name: Some(name.to_string()),
+        field,
+        ..Default::default()
+    }
+}
+
+fn field(
+    name: &str,
+    number: i32,
+    label: FieldDescriptorProtoLabel,
+    ty: FieldDescriptorProtoType,
+) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        label: Some(label),
+        r#type: Some(ty),
+        ..Default::default()
+    }
+}
+
+fn message_field(
+    name: &str,
+    number: i32,
+    label: FieldDescriptorProtoLabel,
+    type_name: &str,
+) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        type_name: Some(type_name.to_string()),
+        ..field(name, number, label, FieldDescriptorProtoType::Message)
+    }
+}
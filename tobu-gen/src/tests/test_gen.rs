@@ -0,0 +1,455 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::{
+    gen::gen_file,
+    params::parse_params,
+    parse::{
+        DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+        FieldDescriptorProtoLabel::*, FieldDescriptorProtoType, FileDescriptorProto,
+        MessageOptions, OneofDescriptorProto,
+    },
+    process::process_files,
+};
+
+use super::{field, file, message, message_field};
+
+// The generated code without whitespace, which token streams do not keep the same way.
+fn generate(file: FileDescriptorProto) -> String {
+    generate_with(file, "")
+}
+
+fn generate_with(file: FileDescriptorProto, parameter: &str) -> String {
+    let params = parse_params(parameter).unwrap();
+    let name = file.name.clone().unwrap();
+    let files = process_files(&[file], &[name], &params).unwrap();
+    unspaced(gen_file(&files[0], &params))
+}
+
+fn unspaced(tokens: TokenStream) -> String {
+    tokens.to_string().replace(' ', "")
+}
+
+fn assert_contains(code: &str, tokens: TokenStream) {
+    assert!(
+        code.contains(&unspaced(tokens.clone())),
+        "{} not in {}",
+        tokens,
+        code
+    );
+}
+
+// The static descriptor of the message or enum with the given full name.
+fn info<'a>(code: &'a str, full_name: &str) -> &'a str {
+    let name = format!("name:\"{}\"", full_name);
+    code.split("pubstatic")
+        .map(|s| &s[..s.find("};").unwrap_or(s.len())])
+        .find(|s| s.contains(&name))
+        .unwrap_or_else(|| panic!("{} not in {}", full_name, code))
+}
+
+// The FieldInfo of the field with the given name in a static descriptor.
+fn field_info<'a>(info: &'a str, name: &str) -> &'a str {
+    let name = format!("name:\"{}\"", name);
+    info.split("tobu::info::FieldInfo{")
+        .find(|s| s.starts_with(&name))
+        .unwrap_or_else(|| panic!("{} not in {}", name, info))
+}
+
+#[test]
+fn message_info() {
+    let mut numbers = field("numbers", 1, Repeated, FieldDescriptorProtoType::Int32);
+    numbers.json_name = Some("numbers".to_string());
+    let mut text = field("text", 2, Optional, FieldDescriptorProtoType::String);
+    text.oneof_index = Some(0);
+    let entry = DescriptorProto {
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        }),
+        ..message(
+            "CountsEntry",
+            vec![
+                field("key", 1, Optional, FieldDescriptorProtoType::String),
+                field("value", 2, Optional, FieldDescriptorProtoType::Int32),
+            ],
+        )
+    };
+    let msg = DescriptorProto {
+        nested_type: vec![entry],
+        oneof_decl: vec![OneofDescriptorProto {
+            name: Some("choice".to_string()),
+        }],
+        ..message(
+            "Msg",
+            vec![
+                numbers,
+                text,
+                message_field("counts", 3, Repeated, ".test.Msg.CountsEntry"),
+            ],
+        )
+    };
+    let code = generate(file("test.proto", "test", vec![msg]));
+
+    assert_contains(
+        &code,
+        quote! {
+            #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+            pub struct Msg {
+                pub numbers: Vec<i32>,
+                pub choice: Option<msg::Choice>,
+                pub counts: std::collections::HashMap<String, i32>,
+                #[serde(rename = "_unknown_fields", default)]
+                pub unknown_fields: tobu::unknown::UnknownFields,
+            }
+        },
+    );
+    assert_contains(
+        &code,
+        quote! {
+            #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+            pub enum Choice {
+                Text(String)
+            }
+        },
+    );
+
+    assert_contains(
+        &code,
+        quote! {
+            impl tobu::Message for Msg {
+                fn info() -> &'static tobu::info::MessageInfo {
+                    &MSG
+                }
+            }
+        },
+    );
+
+    let msg = info(&code, "test.Msg");
+    assert!(msg.contains("syntax:tobu::info::Syntax::Proto3,is_map:false"));
+    assert!(msg.contains("oneofs:&[tobu::info::OneofInfo{name:\"choice\"}]"));
+    // repeated scalars are packed in proto3
+    let numbers = field_info(msg, "numbers");
+    assert!(numbers.contains("packed:true,oneof_index:None"));
+    let text = field_info(msg, "text");
+    assert!(text.contains("packed:false,oneof_index:Some(0i32)"));
+    let counts = field_info(msg, "counts");
+    assert!(counts.contains(
+        "message_info:Some(tobu::info::MessageRef::Static(&crate::test::msg::COUNTS_ENTRY))"
+    ));
+
+    // the map entry only gets a descriptor
+    assert!(!code.contains("pubstructCountsEntry"));
+    assert!(info(&code, "test.Msg.CountsEntry").contains("is_map:true"));
+}
+
+#[test]
+fn nested() {
+    let outer = DescriptorProto {
+        nested_type: vec![message("Inner", vec![])],
+        enum_type: vec![EnumDescriptorProto {
+            name: Some("Kind".to_string()),
+            value: vec![EnumValueDescriptorProto {
+                name: Some("KIND_A".to_string()),
+                number: Some(0),
+            }],
+        }],
+        ..message(
+            "Outer",
+            vec![
+                message_field("inner", 1, Optional, ".test.Outer.Inner"),
+                FieldDescriptorProto {
+                    r#type: Some(FieldDescriptorProtoType::Enum),
+                    ..message_field("kind", 2, Optional, ".test.Outer.Kind")
+                },
+            ],
+        )
+    };
+    let code = generate(file("test.proto", "test", vec![outer]));
+
+    assert_contains(
+        &code,
+        quote! {
+            pub inner: Option<crate::test::outer::Inner>,
+            pub kind: crate::test::outer::Kind,
+        },
+    );
+    // nested types are in a module named after the message they are in
+    let outer = &code[code.find("pubmodouter{").unwrap()..];
+    assert!(outer.contains("pubstructInner{"));
+    assert!(outer.contains("pubenumKind{"));
+    assert!(info(outer, "test.Outer.Inner").starts_with("INNER:"));
+    assert!(info(outer, "test.Outer.Kind").starts_with("KIND:"));
+}
+
+#[test]
+fn recursive_messages_boxed() {
+    let mut node = message(
+        "Node",
+        vec![
+            message_field("child", 1, Optional, ".test.Node"),
+            message_field("children", 2, Repeated, ".test.Node"),
+            message_field("leaf", 3, Optional, ".test.Leaf"),
+            message_field("other", 4, Optional, ".test.Other"),
+            message_field("node", 5, Optional, ".test.Node"),
+        ],
+    );
+    node.field[4].oneof_index = Some(0);
+    node.oneof_decl = vec![OneofDescriptorProto {
+        name: Some("kind".to_string()),
+    }];
+    let code = generate(file(
+        "test.proto",
+        "test",
+        vec![
+            node,
+            message("Leaf", vec![]),
+            // holds Node through Node.other
+            message(
+                "Other",
+                vec![message_field("node", 1, Optional, ".test.Node")],
+            ),
+        ],
+    ));
+
+    for field in [
+        quote! { pub child: Option<Box<crate::test::Node>>, },
+        // a repeated field is on the heap already
+        quote! { pub children: Vec<crate::test::Node>, },
+        quote! { pub leaf: Option<crate::test::Leaf>, },
+        quote! { pub other: Option<Box<crate::test::Other>>, },
+        quote! { Node(Box<crate::test::Node>) },
+        quote! { pub node: Option<Box<crate::test::Node>>, },
+    ] {
+        assert_contains(&code, field);
+    }
+}
+
+#[test]
+fn proto3_optional() {
+    let mut maybe = field("maybe", 1, Optional, FieldDescriptorProtoType::Int32);
+    maybe.oneof_index = Some(0);
+    maybe.proto3_optional = Some(true);
+    let msg = DescriptorProto {
+        oneof_decl: vec![OneofDescriptorProto {
+            name: Some("_maybe".to_string()),
+        }],
+        ..message(
+            "Msg",
+            vec![
+                maybe,
+                field("plain", 2, Optional, FieldDescriptorProtoType::Int32),
+            ],
+        )
+    };
+    let code = generate(file("test.proto", "test", vec![msg]));
+
+    assert_contains(&code, quote! { pub maybe: Option<i32>, });
+    assert_contains(&code, quote! { pub plain: i32, });
+    // the synthetic oneof is left out
+    assert!(!code.contains("enumMsgMaybe"));
+    let msg = info(&code, "test.Msg");
+    assert!(msg.contains("oneofs:&[]"));
+    assert!(field_info(msg, "maybe").contains("oneof_index:None,proto3_optional:true"));
+    assert!(field_info(msg, "plain").contains("oneof_index:None,proto3_optional:false"));
+}
+
+#[test]
+fn proto2_defaults() {
+    let mut count = field("count", 1, Optional, FieldDescriptorProtoType::Int32);
+    count.default_value = Some("5".to_string());
+    let mut name = field("name", 2, Optional, FieldDescriptorProtoType::String);
+    name.default_value = Some("hi".to_string());
+    let msg = message(
+        "Msg",
+        vec![
+            count,
+            name,
+            field("plain", 3, Optional, FieldDescriptorProtoType::Int32),
+        ],
+    );
+    let code = generate(FileDescriptorProto {
+        syntax: None,
+        ..file("test.proto", "test", vec![msg])
+    });
+
+    assert_contains(
+        &code,
+        quote! {
+            impl Msg {
+                pub fn count(&self) -> i32 {
+                    self.count.unwrap_or(5i32)
+                }
+                pub fn name(&self) -> &str {
+                    self.name.as_deref().unwrap_or("hi")
+                }
+                pub fn plain(&self) -> i32 {
+                    self.plain.unwrap_or_default()
+                }
+            }
+        },
+    );
+    let msg = info(&code, "test.Msg");
+    assert!(field_info(msg, "count")
+        .contains("default_value:Some(tobu::info::DefaultValue::Int(5i64))"));
+    assert!(field_info(msg, "name")
+        .contains("default_value:Some(tobu::info::DefaultValue::String(\"hi\"))"));
+    assert!(field_info(msg, "plain").contains("default_value:None"));
+}
+
+#[test]
+fn params() {
+    let msg = message(
+        "Msg",
+        vec![
+            field("data", 1, Optional, FieldDescriptorProtoType::Bytes),
+            message_field("counts", 2, Repeated, ".test.Msg.CountsEntry"),
+        ],
+    );
+    let msg = DescriptorProto {
+        nested_type: vec![DescriptorProto {
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..message(
+                "CountsEntry",
+                vec![
+                    field("key", 1, Optional, FieldDescriptorProtoType::String),
+                    field("value", 2, Optional, FieldDescriptorProtoType::Int32),
+                ],
+            )
+        }],
+        ..msg
+    };
+    let code = generate_with(
+        file("test.proto", "test", vec![msg]),
+        "bytes=bytes::Bytes,map=btree,serde_path=::tobu::serde,module_root=crate::proto",
+    );
+
+    assert_contains(
+        &code,
+        quote! {
+            #[derive(Debug, Clone, Default, PartialEq, ::tobu::serde::Serialize, ::tobu::serde::Deserialize)]
+            #[serde(crate = ":: tobu :: serde")]
+            pub struct Msg {
+                pub data: bytes::Bytes,
+                pub counts: std::collections::BTreeMap<String, i32>,
+                #[serde(rename = "_unknown_fields", default)]
+                pub unknown_fields: tobu::unknown::UnknownFields,
+            }
+        },
+    );
+    assert!(field_info(info(&code, "test.Msg"), "counts")
+        .contains("MessageRef::Static(&crate::proto::test::msg::COUNTS_ENTRY)"));
+}
+
+#[test]
+fn top_level_enums() {
+    let code = generate(FileDescriptorProto {
+        enum_type: vec![EnumDescriptorProto {
+            name: Some("Color".to_string()),
+            value: vec![
+                EnumValueDescriptorProto {
+                    name: Some("COLOR_RED".to_string()),
+                    number: Some(0),
+                },
+                EnumValueDescriptorProto {
+                    name: Some("COLOR_GREEN".to_string()),
+                    number: Some(2),
+                },
+            ],
+        }],
+        syntax: None,
+        ..file("test.proto", "test", vec![])
+    });
+
+    assert_contains(
+        &code,
+        quote! {
+            #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+            #[repr(i32)]
+            pub enum Color {
+                #[default]
+                Red = 0i32,
+                Green = 2i32
+            }
+
+            pub static COLOR: tobu::info::EnumInfo = tobu::info::EnumInfo {
+                name: "test.Color",
+                value: &[
+                    tobu::info::EnumValue { name: "COLOR_RED", number: 0i32 },
+                    tobu::info::EnumValue { name: "COLOR_GREEN", number: 2i32 }
+                ],
+                syntax: tobu::info::Syntax::Proto2,
+            };
+        },
+    );
+}
+
+#[test]
+fn top_level_extension() {
+    let mut extra = field("extra", 100, Optional, FieldDescriptorProtoType::Int32);
+    extra.extendee = Some(".test.Base".to_string());
+    let code = generate(FileDescriptorProto {
+        extension: vec![extra],
+        syntax: None,
+        ..file("test.proto", "test", vec![message("Base", vec![])])
+    });
+
+    let extra = info(&code, "extra");
+    assert!(extra.starts_with(
+        "EXTRA_EXTENSION:tobu::info::ExtensionInfo=tobu::info::ExtensionInfo{\
+         extendee:\"test.Base\",field:tobu::info::FieldInfo{name:\"extra\","
+    ));
+    assert!(field_info(extra, "extra").contains("ty:tobu::info::Type::Int32"));
+    // extensions are never held in a struct
+    assert!(!code.contains("pubextra"));
+}
+
+#[test]
+fn proto2_required() {
+    let code = generate(FileDescriptorProto {
+        syntax: None,
+        ..file(
+            "test.proto",
+            "test",
+            vec![
+                message(
+                    "Msg",
+                    vec![
+                        field("id", 1, Required, FieldDescriptorProtoType::Int32),
+                        message_field("leaf", 2, Required, ".test.Leaf"),
+                    ],
+                ),
+                message("Leaf", vec![]),
+            ],
+        )
+    });
+
+    // held in an Option so that a message missing them fails to encode
+    assert_contains(
+        &code,
+        quote! {
+            pub struct Msg {
+                pub id: Option<i32>,
+                pub leaf: Option<crate::test::Leaf>,
+                #[serde(rename = "_unknown_fields", default)]
+                pub unknown_fields: tobu::unknown::UnknownFields,
+            }
+        },
+    );
+    assert_contains(
+        &code,
+        quote! {
+            impl Msg {
+                pub fn id(&self) -> i32 {
+                    self.id.unwrap_or_default()
+                }
+            }
+        },
+    );
+    let msg = info(&code, "test.Msg");
+    assert!(field_info(msg, "id").contains("cardinality:tobu::info::Cardinality::Required"));
+    assert!(field_info(msg, "leaf").contains("cardinality:tobu::info::Cardinality::Required"));
+}
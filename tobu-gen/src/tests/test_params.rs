@@ -0,0 +1,91 @@
+use quote::quote;
+
+use crate::params::parse_params;
+
+fn error(parameter: &str) -> String {
+    parse_params(parameter).unwrap_err().to_string()
+}
+
+#[test]
+fn empty() {
+    let params = parse_params("").unwrap();
+    assert_eq!(params.bytes.to_string(), quote! { Vec<u8> }.to_string());
+    assert!(!params.btree_map);
+    assert!(params.serde_path.is_none());
+    assert_eq!(params.module_root, ["crate"]);
+    assert_eq!(
+        params.extern_paths,
+        [(
+            ".google.protobuf".to_string(),
+            vec![String::new(), "tobu_types".to_string()]
+        )]
+    );
+}
+
+#[test]
+fn several() {
+    let params =
+        parse_params("bytes=bytes::Bytes,map=btree,serde_path=::tobu::serde,module_root=a::b")
+            .unwrap();
+    assert_eq!(
+        params.bytes.to_string(),
+        quote! { bytes::Bytes }.to_string()
+    );
+    assert!(params.btree_map);
+    assert_eq!(
+        params.serde_path.unwrap().to_string(),
+        quote! { ::tobu::serde }.to_string()
+    );
+    assert_eq!(params.module_root, ["a", "b"]);
+}
+
+#[test]
+fn extern_path_repeated() {
+    let params = parse_params("extern_path=.a=::x::a,map=hash,extern_path=.b.C=crate::C").unwrap();
+    assert!(!params.btree_map);
+    assert_eq!(
+        params.extern_paths[1..],
+        [
+            (
+                ".a".to_string(),
+                vec![String::new(), "x".into(), "a".into()]
+            ),
+            (".b.C".to_string(), vec!["crate".into(), "C".into()]),
+        ]
+    );
+}
+
+#[test]
+fn malformed() {
+    assert_eq!(error("map"), "Parameter map expected to be key=value");
+    assert_eq!(
+        error("map=btree,,bytes"),
+        "Parameter bytes expected to be key=value"
+    );
+    assert_eq!(
+        error("map=tree"),
+        "Parameter map expected to be btree or hash: tree"
+    );
+    assert_eq!(
+        error("bytes="),
+        "Parameter bytes expected to be a Rust path: "
+    );
+    assert_eq!(
+        error("module_root=a::1"),
+        "Parameter module_root expected to be a module path: a::1"
+    );
+    for value in ["a=::x", ".a", ".a=x::", ".a=::"] {
+        assert_eq!(
+            error(&format!("extern_path={}", value)),
+            format!(
+                "Parameter extern_path expected to be .proto=rust: {}",
+                value
+            )
+        );
+    }
+}
+
+#[test]
+fn unknown() {
+    assert_eq!(error("map=btree,size=1"), "Unknown parameter size");
+}
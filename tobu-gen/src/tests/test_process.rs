@@ -0,0 +1,186 @@
+use crate::{
+    params::Params,
+    parse::{
+        DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FieldDescriptorProtoLabel::*,
+        FieldDescriptorProtoType, FileDescriptorProto,
+    },
+    process::{process_files, FieldType, TypeRef},
+};
+
+use super::{file, message, message_field};
+
+// The types the fields of the first message of the generated file refer to.
+fn field_types(files: &[FileDescriptorProto], params: &Params) -> Vec<TypeRef> {
+    let name = files[0].name.clone().unwrap();
+    let generated = process_files(files, &[name], params).unwrap();
+    generated[0].messages[0]
+        .fields
+        .iter()
+        .map(|field| match &field.ty {
+            FieldType::Message(type_ref) | FieldType::Enum(type_ref) => type_ref.clone(),
+            ty => panic!("type expected, found {:?}", ty),
+        })
+        .collect()
+}
+
+fn type_ref(module: &[&str], name: &str) -> TypeRef {
+    TypeRef {
+        module: module.iter().map(|m| m.to_string()).collect(),
+        name: name.to_string(),
+    }
+}
+
+fn enum_field(name: &str, number: i32, type_name: &str) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        r#type: Some(FieldDescriptorProtoType::Enum),
+        ..message_field(name, number, Optional, type_name)
+    }
+}
+
+#[test]
+fn nested() {
+    let outer = DescriptorProto {
+        nested_type: vec![DescriptorProto {
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("Kind".to_string()),
+                ..Default::default()
+            }],
+            ..message("Inner", vec![])
+        }],
+        ..message(
+            "Outer",
+            vec![
+                message_field("inner", 1, Optional, ".a.b.Outer.Inner"),
+                enum_field("kind", 2, ".a.b.Outer.Inner.Kind"),
+            ],
+        )
+    };
+    assert_eq!(
+        field_types(&[file("a.proto", "a.b", vec![outer])], &Params::default()),
+        [
+            type_ref(&["crate", "a", "b", "outer"], "Inner"),
+            type_ref(&["crate", "a", "b", "outer", "inner"], "Kind"),
+        ]
+    );
+}
+
+#[test]
+fn other_package() {
+    let files = [
+        file(
+            "y.proto",
+            "y",
+            vec![message(
+                "Y",
+                vec![
+                    message_field("x", 1, Optional, ".x.X"),
+                    message_field("r#type", 2, Optional, ".type.MyType"),
+                ],
+            )],
+        ),
+        file("x.proto", "x", vec![message("X", vec![])]),
+        // keywords are raw identifiers
+        file("type.proto", "type", vec![message("MyType", vec![])]),
+    ];
+    let params = Params {
+        module_root: vec!["crate".to_string(), "proto".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        field_types(&files, &params),
+        [
+            type_ref(&["crate", "proto", "x"], "X"),
+            type_ref(&["crate", "proto", "r#type"], "MyType"),
+        ]
+    );
+}
+
+#[test]
+fn longest_extern_path() {
+    let files = [
+        file(
+            "a.proto",
+            "a",
+            vec![message(
+                "A",
+                vec![
+                    message_field("e", 1, Optional, ".ext.E"),
+                    message_field("s", 2, Optional, ".ext.sub.S"),
+                    message_field("t", 3, Optional, ".ext.subway.T"),
+                    message_field("exact", 4, Optional, ".ext.sub.Exact"),
+                    message_field("nested", 5, Optional, ".ext.sub.Exact.Nested"),
+                ],
+            )],
+        ),
+        file("ext.proto", "ext", vec![message("E", vec![])]),
+        file(
+            "sub.proto",
+            "ext.sub",
+            vec![
+                message("S", vec![]),
+                DescriptorProto {
+                    nested_type: vec![message("Nested", vec![])],
+                    ..message("Exact", vec![])
+                },
+            ],
+        ),
+        file("subway.proto", "ext.subway", vec![message("T", vec![])]),
+    ];
+    let mut params = Params::default();
+    for (proto, rust) in [
+        (".ext", &["", "ext"][..]),
+        (".ext.sub", &["crate", "sub"]),
+        (".ext.sub.Exact", &["", "exact", "Renamed"]),
+    ] {
+        params.extern_paths.push((
+            proto.to_string(),
+            rust.iter().map(|m| m.to_string()).collect(),
+        ));
+    }
+    assert_eq!(
+        field_types(&files, &params),
+        [
+            type_ref(&["", "ext"], "E"),
+            type_ref(&["crate", "sub"], "S"),
+            // .ext.sub is not a prefix of .ext.subway
+            type_ref(&["", "ext", "subway"], "T"),
+            type_ref(&["", "exact"], "Renamed"),
+            type_ref(&["", "exact", "renamed"], "Nested"),
+        ]
+    );
+}
+
+#[test]
+fn well_known_types() {
+    let files = [
+        file(
+            "a.proto",
+            "a",
+            vec![message(
+                "A",
+                vec![
+                    message_field("duration", 1, Optional, ".google.protobuf.Duration"),
+                    enum_field("null", 2, ".google.protobuf.NullValue"),
+                ],
+            )],
+        ),
+        FileDescriptorProto {
+            enum_type: vec![EnumDescriptorProto {
+                name: Some("NullValue".to_string()),
+                ..Default::default()
+            }],
+            ..file(
+                "google/protobuf/wkt.proto",
+                "google.protobuf",
+                vec![message("Duration", vec![])],
+            )
+        },
+    ];
+    assert_eq!(
+        field_types(&files, &Params::default()),
+        [
+            type_ref(&["", "tobu_types"], "Duration"),
+            type_ref(&["", "tobu_types"], "NullValue"),
+        ]
+    );
+}
@@ -0,0 +1,78 @@
+use bytes::Bytes;
+use tobu_format::wire::{FieldValue, Parser, WireField};
+
+use crate::response::{
+    encode_response, CodeGeneratorResponse, CodeGeneratorResponseFile, FEATURE_PROTO3_OPTIONAL,
+};
+
+fn parse(buf: impl Into<Bytes>) -> Vec<(i32, FieldValue)> {
+    Parser::new(buf.into())
+        .map(|field| {
+            let WireField { num, val } = field.unwrap();
+            (num.get(), val)
+        })
+        .collect()
+}
+
+fn string(s: &str) -> FieldValue {
+    FieldValue::Bytes(Bytes::copy_from_slice(s.as_bytes()))
+}
+
+#[test]
+fn empty() {
+    assert!(encode_response(&CodeGeneratorResponse::default()).is_empty());
+}
+
+#[test]
+fn files() {
+    let res = CodeGeneratorResponse {
+        supported_features: Some(FEATURE_PROTO3_OPTIONAL),
+        file: vec![
+            CodeGeneratorResponseFile {
+                name: Some("a/b.rs".to_string()),
+                content: Some("pub struct B {}".to_string()),
+                ..Default::default()
+            },
+            CodeGeneratorResponseFile {
+                name: Some("c.rs".to_string()),
+                insertion_point: Some("here".to_string()),
+                content: Some(String::new()),
+            },
+        ],
+        ..Default::default()
+    };
+    let fields = parse(encode_response(&res));
+    assert_eq!(fields.len(), 3);
+    assert_eq!(fields[0], (2, FieldValue::Varint(FEATURE_PROTO3_OPTIONAL)));
+
+    let files: Vec<_> = fields[1..]
+        .iter()
+        .map(|(num, val)| match val {
+            FieldValue::Bytes(file) if *num == 15 => parse(file.clone()),
+            _ => panic!("file expected, found {} {:?}", num, val),
+        })
+        .collect();
+    assert_eq!(
+        files,
+        [
+            vec![(1, string("a/b.rs")), (15, string("pub struct B {}"))],
+            vec![(1, string("c.rs")), (2, string("here")), (15, string(""))],
+        ]
+    );
+}
+
+#[test]
+fn error() {
+    let res = CodeGeneratorResponse {
+        error: Some("type .a.B not found".to_string()),
+        supported_features: Some(FEATURE_PROTO3_OPTIONAL),
+        ..Default::default()
+    };
+    assert_eq!(
+        parse(encode_response(&res)),
+        [
+            (1, string("type .a.B not found")),
+            (2, FieldValue::Varint(FEATURE_PROTO3_OPTIONAL)),
+        ]
+    );
+}
@@ -0,0 +1,96 @@
+use bytes::Bytes;
+
+use crate::Error;
+
+/// A message of any type along with a URL naming its type, such as
+/// `type.googleapis.com/google.protobuf.Duration`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Any {
+    pub type_url: String,
+    pub value: Vec<u8>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static ANY: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Any",
+    fields: &[
+        tobu::info::FieldInfo {
+            name: "type_url",
+            number: tobu::info::FieldNumber::new(1i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::String,
+            type_name: "",
+            json_name: "typeUrl",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "value",
+            number: tobu::info::FieldNumber::new(2i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Bytes,
+            type_name: "",
+            json_name: "value",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+    ],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Any {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &ANY
+    }
+}
+
+impl Any {
+    /// Packs a message under its type URL, such as `type.googleapis.com/google.protobuf.Duration`.
+    pub fn pack<M: tobu::Message>(message: &M) -> Result<Self, Error> {
+        Ok(Any {
+            type_url: tobu::registry::type_url(M::info()),
+            value: message.encode()?,
+            ..Default::default()
+        })
+    }
+
+    /// Unpacks the message, which has to be of type `M`.
+    pub fn unpack<M: tobu::Message>(&self) -> Result<M, Error> {
+        if !self.is::<M>() {
+            return Err(Error::TypeMismatch {
+                expected: M::info().name.to_string(),
+                found: self.type_name().to_string(),
+            });
+        }
+        Ok(M::decode(Bytes::copy_from_slice(&self.value))?)
+    }
+
+    /// Unpacks the message with whichever descriptor the registry has for its type URL.
+    pub fn unpack_dynamic(
+        &self,
+        registry: &tobu::registry::TypeRegistry,
+    ) -> Result<tobu::value::DynamicMessage, Error> {
+        Ok(registry.decode(&self.type_url, Bytes::copy_from_slice(&self.value))?)
+    }
+
+    /// Whether the message packed is of type `M`.
+    pub fn is<M: tobu::Message>(&self) -> bool {
+        self.type_name() == M::info().name
+    }
+
+    /// The full name of the type of the message packed.
+    pub fn type_name(&self) -> &str {
+        tobu::registry::type_name(&self.type_url)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::Error;
+
+/// A signed span of time as seconds and nanoseconds, which have the same sign. Written in JSON as
+/// seconds with a suffix of `s`, such as `1.5s`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Duration {
+    pub seconds: i64,
+    pub nanos: i32,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static DURATION: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Duration",
+    fields: &[
+        tobu::info::FieldInfo {
+            name: "seconds",
+            number: tobu::info::FieldNumber::new(1i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Int64,
+            type_name: "",
+            json_name: "seconds",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "nanos",
+            number: tobu::info::FieldNumber::new(2i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Int32,
+            type_name: "",
+            json_name: "nanos",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+    ],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Duration {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &DURATION
+    }
+}
+
+const NANOS_PER_SECOND: i32 = 1_000_000_000;
+
+impl TryFrom<std::time::Duration> for Duration {
+    type Error = Error;
+
+    fn try_from(duration: std::time::Duration) -> Result<Self, Self::Error> {
+        Ok(Duration {
+            seconds: i64::try_from(duration.as_secs()).map_err(|_| Error::DurationOutOfRange)?,
+            nanos: duration.subsec_nanos() as i32,
+            ..Default::default()
+        })
+    }
+}
+
+// std::time::Duration cannot be negative.
+impl TryFrom<Duration> for std::time::Duration {
+    type Error = Error;
+
+    fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+        let seconds = u64::try_from(duration.seconds).map_err(|_| Error::DurationOutOfRange)?;
+        if !(0..NANOS_PER_SECOND).contains(&duration.nanos) {
+            return Err(Error::DurationOutOfRange);
+        }
+        Ok(std::time::Duration::new(seconds, duration.nanos as u32))
+    }
+}
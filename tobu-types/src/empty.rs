@@ -0,0 +1,20 @@
+/// A message with no fields, such as the request or response of a method that needs none.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Empty {
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static EMPTY: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Empty",
+    fields: &[],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Empty {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &EMPTY
+    }
+}
@@ -0,0 +1,35 @@
+/// A set of paths to fields, such as `user.display_name`. Written in JSON as a comma separated
+/// string of lowerCamelCase paths.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldMask {
+    pub paths: Vec<String>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static FIELD_MASK: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.FieldMask",
+    fields: &[tobu::info::FieldInfo {
+        name: "paths",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Repeated,
+        ty: tobu::info::Type::String,
+        type_name: "",
+        json_name: "paths",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for FieldMask {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &FIELD_MASK
+    }
+}
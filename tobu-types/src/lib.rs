@@ -0,0 +1,66 @@
+//! The protobuf [well-known types] in `google.protobuf`, along with their descriptors.
+//!
+//! The types are written the same way `tobu-gen` writes generated code, so generated code that
+//! refers to a well-known type uses the ones here. The JSON mapping gives `Timestamp`,
+//! `Duration`, `FieldMask`, `Struct`, `Value`, `ListValue` and the wrappers a form of their own,
+//! such as an RFC 3339 string for a `Timestamp`, which [`tobu::json`] takes care of.
+//!
+//! [well-known types]: https://protobuf.dev/reference/protobuf/google.protobuf/
+
+mod any;
+mod duration;
+mod empty;
+mod field_mask;
+mod r#struct;
+mod timestamp;
+mod wrappers;
+
+pub use any::*;
+pub use duration::*;
+pub use empty::*;
+pub use field_mask::*;
+pub use r#struct::*;
+pub use timestamp::*;
+pub use wrappers::*;
+
+#[cfg(test)]
+mod tests;
+
+/// An error converting a well-known type to or from its counterpart in `std`, or packing a message
+/// into an [`Any`] and back.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("timestamp is out of range")]
+    TimestampOutOfRange,
+
+    #[error("duration is out of range")]
+    DurationOutOfRange,
+
+    #[error("expected a packed {expected}, found {found}")]
+    TypeMismatch { expected: String, found: String },
+
+    #[error(transparent)]
+    Tobu(#[from] tobu::error::Error),
+}
+
+// A tobu error may hold an io::Error, which cannot be compared, so tobu errors are equal when they
+// read the same.
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Error::TimestampOutOfRange, Error::TimestampOutOfRange)
+            | (Error::DurationOutOfRange, Error::DurationOutOfRange) => true,
+            (
+                Error::TypeMismatch { expected, found },
+                Error::TypeMismatch {
+                    expected: other_expected,
+                    found: other_found,
+                },
+            ) => expected == other_expected && found == other_found,
+            (Error::Tobu(error), Error::Tobu(other)) => error.to_string() == other.to_string(),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
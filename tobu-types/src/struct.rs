@@ -0,0 +1,240 @@
+/// A JSON object. Written in JSON as the object itself.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Struct {
+    pub fields: std::collections::HashMap<String, Value>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static STRUCT: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Struct",
+    fields: &[tobu::info::FieldInfo {
+        name: "fields",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Repeated,
+        ty: tobu::info::Type::Message,
+        type_name: ".google.protobuf.Struct.FieldsEntry",
+        json_name: "fields",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: Some(tobu::info::MessageRef::Static(&STRUCT_FIELDS_ENTRY)),
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Struct {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &STRUCT
+    }
+}
+
+pub static STRUCT_FIELDS_ENTRY: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Struct.FieldsEntry",
+    fields: &[
+        tobu::info::FieldInfo {
+            name: "key",
+            number: tobu::info::FieldNumber::new(1i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::String,
+            type_name: "",
+            json_name: "key",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "value",
+            number: tobu::info::FieldNumber::new(2i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Message,
+            type_name: ".google.protobuf.Value",
+            json_name: "value",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: Some(tobu::info::MessageRef::Static(&VALUE)),
+            enum_info: None,
+        },
+    ],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: true,
+};
+
+/// A JSON value. Written in JSON as the value itself.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Value {
+    pub kind: Option<ValueKind>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Value",
+    fields: &[
+        tobu::info::FieldInfo {
+            name: "null_value",
+            number: tobu::info::FieldNumber::new(1i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Enum,
+            type_name: ".google.protobuf.NullValue",
+            json_name: "nullValue",
+            packed: false,
+            oneof_index: Some(0i32),
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: Some(&NULL_VALUE),
+        },
+        tobu::info::FieldInfo {
+            name: "number_value",
+            number: tobu::info::FieldNumber::new(2i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Double,
+            type_name: "",
+            json_name: "numberValue",
+            packed: false,
+            oneof_index: Some(0i32),
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "string_value",
+            number: tobu::info::FieldNumber::new(3i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::String,
+            type_name: "",
+            json_name: "stringValue",
+            packed: false,
+            oneof_index: Some(0i32),
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "bool_value",
+            number: tobu::info::FieldNumber::new(4i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Bool,
+            type_name: "",
+            json_name: "boolValue",
+            packed: false,
+            oneof_index: Some(0i32),
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "struct_value",
+            number: tobu::info::FieldNumber::new(5i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Message,
+            type_name: ".google.protobuf.Struct",
+            json_name: "structValue",
+            packed: false,
+            oneof_index: Some(0i32),
+            proto3_optional: false,
+            default_value: None,
+            message_info: Some(tobu::info::MessageRef::Static(&STRUCT)),
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "list_value",
+            number: tobu::info::FieldNumber::new(6i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Message,
+            type_name: ".google.protobuf.ListValue",
+            json_name: "listValue",
+            packed: false,
+            oneof_index: Some(0i32),
+            proto3_optional: false,
+            default_value: None,
+            message_info: Some(tobu::info::MessageRef::Static(&LIST_VALUE)),
+            enum_info: None,
+        },
+    ],
+    oneofs: &[tobu::info::OneofInfo { name: "kind" }],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Value {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &VALUE
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ValueKind {
+    NullValue(NullValue),
+    NumberValue(f64),
+    StringValue(String),
+    BoolValue(bool),
+    StructValue(Struct),
+    ListValue(ListValue),
+}
+
+/// The null of a JSON [`Value`].
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum NullValue {
+    #[default]
+    NullValue,
+    Unrecognized(i32),
+}
+
+pub static NULL_VALUE: tobu::info::EnumInfo = tobu::info::EnumInfo {
+    name: "google.protobuf.NullValue",
+    value: &[tobu::info::EnumValue {
+        name: "NULL_VALUE",
+        number: 0i32,
+    }],
+    syntax: tobu::info::Syntax::Proto3,
+};
+
+/// A JSON array. Written in JSON as the array itself.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ListValue {
+    pub values: Vec<Value>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static LIST_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.ListValue",
+    fields: &[tobu::info::FieldInfo {
+        name: "values",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Repeated,
+        ty: tobu::info::Type::Message,
+        type_name: ".google.protobuf.Value",
+        json_name: "values",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: Some(tobu::info::MessageRef::Static(&VALUE)),
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for ListValue {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &LIST_VALUE
+    }
+}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod test_any;
+#[cfg(test)]
+mod test_duration;
+#[cfg(test)]
+mod test_json;
+#[cfg(test)]
+mod test_timestamp;
+
+use crate::{Duration, Timestamp};
+
+fn duration(seconds: i64, nanos: i32) -> Duration {
+    Duration {
+        seconds,
+        nanos,
+        ..Default::default()
+    }
+}
+
+fn timestamp(seconds: i64, nanos: i32) -> Timestamp {
+    Timestamp {
+        seconds,
+        nanos,
+        ..Default::default()
+    }
+}
@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tobu::{
+    error::Error as TobuError,
+    info::{Cardinality, FieldInfo, FieldNumber, MessageInfo, Syntax, Type},
+    json::{from_value, to_value, Options},
+    registry::TypeRegistry,
+    text, Message,
+};
+
+use super::duration;
+use crate::{Any, Duration, Error, Timestamp, ANY, DURATION};
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+const FIELD: FieldInfo = FieldInfo {
+    name: "",
+    number: FieldNumber::new(1),
+    cardinality: Cardinality::Optional,
+    ty: Type::Int32,
+    type_name: "",
+    json_name: "",
+    packed: false,
+    oneof_index: None,
+    proto3_optional: false,
+    default_value: None,
+    message_info: None,
+    enum_info: None,
+};
+
+static POINT: MessageInfo = MessageInfo {
+    name: "test.Point",
+    fields: &[
+        FieldInfo {
+            name: "x",
+            json_name: "x",
+            ..FIELD
+        },
+        FieldInfo {
+            name: "y",
+            number: FieldNumber::new(2),
+            json_name: "y",
+            ..FIELD
+        },
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+impl Message for Point {
+    fn info() -> &'static MessageInfo {
+        &POINT
+    }
+}
+
+fn registry() -> Arc<TypeRegistry> {
+    let mut registry = TypeRegistry::new();
+    registry.add(&POINT);
+    registry.add(&DURATION);
+    Arc::new(registry)
+}
+
+#[test]
+fn pack_unpack() {
+    let point = Point { x: 1, y: -2 };
+    let any = Any::pack(&point).unwrap();
+    assert_eq!(any.type_url, "type.googleapis.com/test.Point");
+    assert_eq!(any.type_name(), "test.Point");
+    assert!(any.is::<Point>());
+    assert!(!any.is::<Duration>());
+    assert_eq!(any.unpack::<Point>().unwrap(), point);
+    assert!(matches!(
+        any.unpack::<Timestamp>(),
+        Err(Error::TypeMismatch { expected, found })
+            if expected == "google.protobuf.Timestamp" && found == "test.Point"
+    ));
+
+    let message = any.unpack_dynamic(&registry()).unwrap();
+    assert_eq!(message.message_info().name, "test.Point");
+    assert_eq!(message.get("y"), Some(&(-2).into()));
+    assert!(matches!(
+        any.unpack_dynamic(&TypeRegistry::new()),
+        Err(Error::Tobu(TobuError::TypeNotFound(_)))
+    ));
+}
+
+#[test]
+fn json() {
+    let options = Options {
+        type_registry: Some(registry()),
+        ..Default::default()
+    };
+    for (any, json) in [
+        (
+            Any::pack(&Point { x: 1, y: 2 }).unwrap(),
+            json!({ "@type": "type.googleapis.com/test.Point", "x": 1, "y": 2 }),
+        ),
+        // a type with a form of its own is held under "value"
+        (
+            Any::pack(&duration(1, 0)).unwrap(),
+            json!({ "@type": "type.googleapis.com/google.protobuf.Duration", "value": "1s" }),
+        ),
+        (Any::default(), json!({})),
+    ] {
+        assert_eq!(to_value(&any, &ANY, &options).unwrap(), json);
+        assert_eq!(from_value::<Any>(json, &ANY, &options).unwrap(), any);
+    }
+
+    // the packed type has to be known
+    let any = Any::pack(&Point::default()).unwrap();
+    assert!(matches!(
+        to_value(&any, &ANY, &Options::default()),
+        Err(TobuError::TypeNotFound(_))
+    ));
+    let json = json!({ "@type": "type.googleapis.com/test.Unknown" });
+    assert!(matches!(
+        from_value::<Any>(json, &ANY, &options),
+        Err(TobuError::TypeNotFound(_))
+    ));
+}
+
+#[test]
+fn text() {
+    let options = text::Options {
+        type_registry: Some(registry()),
+    };
+    let any = Any::pack(&Point { x: 1, y: 2 }).unwrap();
+    let s = "[type.googleapis.com/test.Point] {\n  x: 1\n  y: 2\n}\n";
+    assert_eq!(
+        text::to_string_with_options(&any, &ANY, &options).unwrap(),
+        s
+    );
+    assert_eq!(
+        text::from_str_with_options::<Any>(s, &ANY, &options).unwrap(),
+        any
+    );
+    assert_eq!(
+        text::from_str_with_options::<Any>(
+            "[ type.googleapis.com/test.Point ]: < y: 2 x: 1 >",
+            &ANY,
+            &options
+        )
+        .unwrap(),
+        any
+    );
+
+    // without the type the bytes are printed as they are
+    let s = text::to_string(&any, &ANY).unwrap();
+    assert!(s.starts_with("type_url: \"type.googleapis.com/test.Point\"\nvalue: "));
+    assert!(text::from_str::<Any>("[type.googleapis.com/test.Point] {}", &ANY).is_err());
+}
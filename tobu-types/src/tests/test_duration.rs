@@ -0,0 +1,28 @@
+use super::duration;
+use crate::{Duration, Error};
+
+#[test]
+fn from_std() {
+    let std = std::time::Duration::new(1, 500_000_000);
+    assert_eq!(Duration::try_from(std), Ok(duration(1, 500_000_000)));
+    assert_eq!(
+        Duration::try_from(std::time::Duration::MAX),
+        Err(Error::DurationOutOfRange)
+    );
+}
+
+#[test]
+fn into_std() {
+    assert_eq!(
+        std::time::Duration::try_from(duration(1, 500_000_000)),
+        Ok(std::time::Duration::new(1, 500_000_000))
+    );
+    assert_eq!(
+        std::time::Duration::try_from(duration(-1, 0)),
+        Err(Error::DurationOutOfRange)
+    );
+    assert_eq!(
+        std::time::Duration::try_from(duration(0, -500)),
+        Err(Error::DurationOutOfRange)
+    );
+}
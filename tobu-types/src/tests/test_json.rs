@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tobu::{
+    info::{Cardinality, FieldInfo, FieldNumber, MessageInfo, MessageRef, Syntax, Type},
+    json::{from_value, to_value, Options},
+};
+
+use super::{duration, timestamp};
+use crate::{
+    BoolValue, BytesValue, DoubleValue, Duration, FieldMask, Int64Value, ListValue, NullValue,
+    StringValue, Struct, Timestamp, UInt32Value, Value, ValueKind, BOOL_VALUE, BYTES_VALUE,
+    DOUBLE_VALUE, DURATION, FIELD_MASK, INT64_VALUE, STRING_VALUE, STRUCT, TIMESTAMP,
+    U_INT32_VALUE, VALUE,
+};
+
+fn to_json<T: Serialize>(value: &T, info: &'static MessageInfo) -> serde_json::Value {
+    to_value(value, info, &Options::default()).unwrap()
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(
+    value: serde_json::Value,
+    info: &'static MessageInfo,
+) -> Result<T, tobu::error::Error> {
+    from_value(value, info, &Options::default())
+}
+
+fn value(kind: ValueKind) -> Value {
+    Value {
+        kind: Some(kind),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn timestamps() {
+    for (seconds, nanos, json) in [
+        (0, 0, "1970-01-01T00:00:00Z"),
+        (63_108_020, 21_000_000, "1972-01-01T10:00:20.021Z"),
+        (1_000_000_000, 1_000, "2001-09-09T01:46:40.000001Z"),
+        (-1, 999_999_999, "1969-12-31T23:59:59.999999999Z"),
+        (-62_135_596_800, 0, "0001-01-01T00:00:00Z"),
+        (253_402_300_799, 0, "9999-12-31T23:59:59Z"),
+        (951_782_400, 0, "2000-02-29T00:00:00Z"),
+    ] {
+        let timestamp = timestamp(seconds, nanos);
+        assert_eq!(to_json(&timestamp, &TIMESTAMP), json!(json));
+        assert_eq!(
+            from_json::<Timestamp>(json!(json), &TIMESTAMP).unwrap(),
+            timestamp
+        );
+    }
+}
+
+#[test]
+fn timestamp_offsets() {
+    let timestamp = timestamp(63_108_020, 21_000_000);
+    for json in [
+        "1972-01-01T15:00:20.021+05:00",
+        "1972-01-01T05:00:20.021-05:00",
+        "1972-01-01T10:00:20.0210Z",
+    ] {
+        assert_eq!(
+            from_json::<Timestamp>(json!(json), &TIMESTAMP).unwrap(),
+            timestamp
+        );
+    }
+}
+
+#[test]
+fn timestamps_invalid() {
+    for json in [
+        json!("1972-01-01T10:00:20"),
+        json!("1972-01-01 10:00:20Z"),
+        json!("1972-02-30T10:00:20Z"),
+        json!("1972-01-01T10:00:20.0000000001Z"),
+        json!("0000-12-31T23:59:59Z"),
+        json!(63_108_020),
+    ] {
+        assert!(from_json::<Timestamp>(json, &TIMESTAMP).is_err());
+    }
+    let options = Options::default();
+    assert!(to_value(&timestamp(0, -1), &TIMESTAMP, &options).is_err());
+    assert!(to_value(&timestamp(253_402_300_800, 0), &TIMESTAMP, &options).is_err());
+}
+
+#[test]
+fn durations() {
+    for (seconds, nanos, json) in [
+        (0, 0, "0s"),
+        (1, 500_000_000, "1.500s"),
+        (-1, -500_000_000, "-1.500s"),
+        (0, -1_000, "-0.000001s"),
+        (3, 1, "3.000000001s"),
+    ] {
+        let duration = duration(seconds, nanos);
+        assert_eq!(to_json(&duration, &DURATION), json!(json));
+        assert_eq!(
+            from_json::<Duration>(json!(json), &DURATION).unwrap(),
+            duration
+        );
+    }
+    assert_eq!(
+        from_json::<Duration>(json!("1.5s"), &DURATION).unwrap(),
+        duration(1, 500_000_000)
+    );
+    for json in ["1.5", "s", "1.s", "-s", "1.0000000001s", "315576000001s"] {
+        assert!(from_json::<Duration>(json!(json), &DURATION).is_err());
+    }
+    let options = Options::default();
+    assert!(to_value(&duration(1, -1), &DURATION, &options).is_err());
+}
+
+#[test]
+fn field_masks() {
+    let mask = FieldMask {
+        paths: vec!["user.display_name".to_string(), "photo".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(to_json(&mask, &FIELD_MASK), json!("user.displayName,photo"));
+    assert_eq!(
+        from_json::<FieldMask>(json!("user.displayName,photo"), &FIELD_MASK).unwrap(),
+        mask
+    );
+    assert_eq!(
+        from_json::<FieldMask>(json!(""), &FIELD_MASK).unwrap(),
+        FieldMask::default()
+    );
+
+    let mask = FieldMask {
+        paths: vec!["displayName".to_string()],
+        ..Default::default()
+    };
+    assert!(to_value(&mask, &FIELD_MASK, &Options::default()).is_err());
+}
+
+#[test]
+fn structs() {
+    let object = Struct {
+        fields: HashMap::from([
+            (
+                "null".to_string(),
+                value(ValueKind::NullValue(NullValue::NullValue)),
+            ),
+            ("number".to_string(), value(ValueKind::NumberValue(1.5))),
+            (
+                "string".to_string(),
+                value(ValueKind::StringValue("a".to_string())),
+            ),
+            ("bool".to_string(), value(ValueKind::BoolValue(true))),
+            (
+                "list".to_string(),
+                value(ValueKind::ListValue(ListValue {
+                    values: vec![value(ValueKind::NullValue(NullValue::NullValue))],
+                    ..Default::default()
+                })),
+            ),
+            (
+                "struct".to_string(),
+                value(ValueKind::StructValue(Struct::default())),
+            ),
+        ]),
+        ..Default::default()
+    };
+    let json = json!({
+        "null": null,
+        "number": 1.5,
+        "string": "a",
+        "bool": true,
+        "list": [null],
+        "struct": {},
+    });
+    assert_eq!(to_json(&object, &STRUCT), json);
+    assert_eq!(from_json::<Struct>(json, &STRUCT).unwrap(), object);
+}
+
+#[test]
+fn values() {
+    assert_eq!(
+        from_json::<Value>(json!(null), &VALUE).unwrap(),
+        value(ValueKind::NullValue(NullValue::NullValue))
+    );
+    assert_eq!(
+        to_json(&value(ValueKind::NumberValue(2.0)), &VALUE),
+        json!(2.0)
+    );
+    let options = Options::default();
+    assert!(to_value(&Value::default(), &VALUE, &options).is_err());
+    assert!(to_value(&value(ValueKind::NumberValue(f64::NAN)), &VALUE, &options).is_err());
+}
+
+#[test]
+fn wrappers() {
+    assert_eq!(to_json(&DoubleValue::from(1.5), &DOUBLE_VALUE), json!(1.5));
+    assert_eq!(to_json(&Int64Value::from(-2), &INT64_VALUE), json!("-2"));
+    assert_eq!(to_json(&UInt32Value::from(0), &U_INT32_VALUE), json!(0));
+    assert_eq!(to_json(&BoolValue::from(false), &BOOL_VALUE), json!(false));
+    assert_eq!(
+        to_json(&StringValue::from("a".to_string()), &STRING_VALUE),
+        json!("a")
+    );
+    assert_eq!(
+        to_json(&BytesValue::from(b"\xff".to_vec()), &BYTES_VALUE),
+        json!("/w==")
+    );
+
+    assert_eq!(
+        from_json::<Int64Value>(json!("-2"), &INT64_VALUE).unwrap(),
+        Int64Value::from(-2)
+    );
+    assert_eq!(
+        from_json::<StringValue>(json!("a"), &STRING_VALUE).unwrap(),
+        StringValue::from("a".to_string())
+    );
+}
+
+// A message holding well-known types in its fields, where the wrappers and Value tell an unset
+// field apart from the default.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct Holder {
+    time: Option<Timestamp>,
+    count: Option<Int64Value>,
+    value: Option<Value>,
+    values: Vec<Value>,
+}
+
+const FIELD: FieldInfo = FieldInfo {
+    name: "",
+    number: FieldNumber::new(1),
+    cardinality: Cardinality::Optional,
+    ty: Type::Message,
+    type_name: "",
+    json_name: "",
+    packed: false,
+    oneof_index: None,
+    proto3_optional: false,
+    default_value: None,
+    message_info: None,
+    enum_info: None,
+};
+
+static HOLDER: MessageInfo = MessageInfo {
+    name: "Holder",
+    fields: &[
+        FieldInfo {
+            name: "time",
+            number: FieldNumber::new(1),
+            message_info: Some(MessageRef::Static(&TIMESTAMP)),
+            ..FIELD
+        },
+        FieldInfo {
+            name: "count",
+            number: FieldNumber::new(2),
+            message_info: Some(MessageRef::Static(&INT64_VALUE)),
+            ..FIELD
+        },
+        FieldInfo {
+            name: "value",
+            number: FieldNumber::new(3),
+            message_info: Some(MessageRef::Static(&VALUE)),
+            ..FIELD
+        },
+        FieldInfo {
+            name: "values",
+            number: FieldNumber::new(4),
+            cardinality: Cardinality::Repeated,
+            message_info: Some(MessageRef::Static(&VALUE)),
+            ..FIELD
+        },
+    ],
+    oneofs: &[],
+    syntax: Syntax::Proto3,
+    is_map: false,
+};
+
+#[test]
+fn fields() {
+    let holder = Holder {
+        time: Some(timestamp(0, 0)),
+        count: Some(Int64Value::default()),
+        value: Some(value(ValueKind::NullValue(NullValue::NullValue))),
+        values: vec![value(ValueKind::BoolValue(true))],
+    };
+    let json = json!({
+        "time": "1970-01-01T00:00:00Z",
+        "count": "0",
+        "value": null,
+        "values": [true],
+    });
+    assert_eq!(to_json(&holder, &HOLDER), json);
+    assert_eq!(from_json::<Holder>(json, &HOLDER).unwrap(), holder);
+
+    // null leaves any other message unset
+    let json = json!({ "time": null, "count": null });
+    assert_eq!(
+        from_json::<Holder>(json, &HOLDER).unwrap(),
+        Holder::default()
+    );
+    assert_eq!(to_json(&Holder::default(), &HOLDER), json!({}));
+}
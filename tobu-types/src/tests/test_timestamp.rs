@@ -0,0 +1,46 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tobu::Message;
+
+use super::timestamp;
+use crate::{Error, Timestamp};
+
+#[test]
+fn from_system_time() {
+    let time = UNIX_EPOCH + Duration::new(1, 500);
+    assert_eq!(Timestamp::from(time), timestamp(1, 500));
+    assert_eq!(Timestamp::from(UNIX_EPOCH), timestamp(0, 0));
+    // nanos count forward from the second before
+    let time = UNIX_EPOCH - Duration::new(1, 500);
+    assert_eq!(Timestamp::from(time), timestamp(-2, 999_999_500));
+    let time = UNIX_EPOCH - Duration::from_secs(1);
+    assert_eq!(Timestamp::from(time), timestamp(-1, 0));
+}
+
+#[test]
+fn into_system_time() {
+    let time = SystemTime::try_from(timestamp(-2, 999_999_500)).unwrap();
+    assert_eq!(time, UNIX_EPOCH - Duration::new(1, 500));
+    assert_eq!(
+        SystemTime::try_from(timestamp(0, -1)),
+        Err(Error::TimestampOutOfRange)
+    );
+    assert_eq!(
+        SystemTime::try_from(timestamp(0, 1_000_000_000)),
+        Err(Error::TimestampOutOfRange)
+    );
+}
+
+#[test]
+fn round_trip() {
+    let now = SystemTime::now();
+    assert_eq!(SystemTime::try_from(Timestamp::from(now)).unwrap(), now);
+}
+
+#[test]
+fn encode() {
+    let timestamp = timestamp(1, 2);
+    let buf = timestamp.encode().unwrap();
+    assert_eq!(buf, b"\x08\x01\x10\x02");
+    assert_eq!(Timestamp::decode(buf.into()).unwrap(), timestamp);
+}
@@ -0,0 +1,98 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Error;
+
+/// A point in time, independent of any time zone, as seconds and nanoseconds since the Unix epoch.
+/// Written in JSON as an RFC 3339 string such as `1972-01-01T10:00:20.021Z`.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Timestamp {
+    pub seconds: i64,
+    pub nanos: i32,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static TIMESTAMP: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Timestamp",
+    fields: &[
+        tobu::info::FieldInfo {
+            name: "seconds",
+            number: tobu::info::FieldNumber::new(1i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Int64,
+            type_name: "",
+            json_name: "seconds",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+        tobu::info::FieldInfo {
+            name: "nanos",
+            number: tobu::info::FieldNumber::new(2i32),
+            cardinality: tobu::info::Cardinality::Optional,
+            ty: tobu::info::Type::Int32,
+            type_name: "",
+            json_name: "nanos",
+            packed: false,
+            oneof_index: None,
+            proto3_optional: false,
+            default_value: None,
+            message_info: None,
+            enum_info: None,
+        },
+    ],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Timestamp {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &TIMESTAMP
+    }
+}
+
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        let (seconds, nanos) = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            // nanos always count forward, so a time before the epoch borrows a second for them
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, NANOS_PER_SECOND - nanos),
+                }
+            }
+        };
+        Timestamp {
+            seconds,
+            nanos: nanos as i32,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<Timestamp> for SystemTime {
+    type Error = Error;
+
+    fn try_from(timestamp: Timestamp) -> Result<Self, Self::Error> {
+        let nanos = u32::try_from(timestamp.nanos)
+            .ok()
+            .filter(|nanos| *nanos < NANOS_PER_SECOND)
+            .ok_or(Error::TimestampOutOfRange)?;
+        let seconds = Duration::from_secs(timestamp.seconds.unsigned_abs());
+        let time = if timestamp.seconds >= 0 {
+            UNIX_EPOCH.checked_add(seconds)
+        } else {
+            UNIX_EPOCH.checked_sub(seconds)
+        };
+        time.and_then(|time| time.checked_add(Duration::from_nanos(nanos.into())))
+            .ok_or(Error::TimestampOutOfRange)
+    }
+}
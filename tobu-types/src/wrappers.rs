@@ -0,0 +1,337 @@
+// Wrappers hold a single scalar in a message, which tells a field that is not set apart from one
+// that holds the default value. Each is written in JSON as the value it wraps.
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DoubleValue {
+    pub value: f64,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static DOUBLE_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.DoubleValue",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Double,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for DoubleValue {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &DOUBLE_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FloatValue {
+    pub value: f32,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static FLOAT_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.FloatValue",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Float,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for FloatValue {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &FLOAT_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Int64Value {
+    pub value: i64,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static INT64_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Int64Value",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Int64,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Int64Value {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &INT64_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UInt64Value {
+    pub value: u64,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static U_INT64_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.UInt64Value",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Uint64,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for UInt64Value {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &U_INT64_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Int32Value {
+    pub value: i32,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static INT32_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.Int32Value",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Int32,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for Int32Value {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &INT32_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct UInt32Value {
+    pub value: u32,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static U_INT32_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.UInt32Value",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Uint32,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for UInt32Value {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &U_INT32_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BoolValue {
+    pub value: bool,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static BOOL_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.BoolValue",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Bool,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for BoolValue {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &BOOL_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StringValue {
+    pub value: String,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static STRING_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.StringValue",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::String,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for StringValue {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &STRING_VALUE
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BytesValue {
+    pub value: Vec<u8>,
+    #[serde(rename = "_unknown_fields", default)]
+    pub unknown_fields: tobu::unknown::UnknownFields,
+}
+
+pub static BYTES_VALUE: tobu::info::MessageInfo = tobu::info::MessageInfo {
+    name: "google.protobuf.BytesValue",
+    fields: &[tobu::info::FieldInfo {
+        name: "value",
+        number: tobu::info::FieldNumber::new(1i32),
+        cardinality: tobu::info::Cardinality::Optional,
+        ty: tobu::info::Type::Bytes,
+        type_name: "",
+        json_name: "value",
+        packed: false,
+        oneof_index: None,
+        proto3_optional: false,
+        default_value: None,
+        message_info: None,
+        enum_info: None,
+    }],
+    oneofs: &[],
+    syntax: tobu::info::Syntax::Proto3,
+    is_map: false,
+};
+
+impl tobu::Message for BytesValue {
+    fn info() -> &'static tobu::info::MessageInfo {
+        &BYTES_VALUE
+    }
+}
+
+macro_rules! wrapper {
+    ($wrapper:ident, $ty:ty) => {
+        impl From<$ty> for $wrapper {
+            fn from(value: $ty) -> Self {
+                $wrapper {
+                    value,
+                    ..Default::default()
+                }
+            }
+        }
+
+        impl From<$wrapper> for $ty {
+            fn from(wrapper: $wrapper) -> Self {
+                wrapper.value
+            }
+        }
+    };
+}
+
+wrapper!(DoubleValue, f64);
+wrapper!(FloatValue, f32);
+wrapper!(Int64Value, i64);
+wrapper!(UInt64Value, u64);
+wrapper!(Int32Value, i32);
+wrapper!(UInt32Value, u32);
+wrapper!(BoolValue, bool);
+wrapper!(StringValue, String);
+wrapper!(BytesValue, Vec<u8>);